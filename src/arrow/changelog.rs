@@ -0,0 +1,80 @@
+/*!
+ * Metadata columns of the changelog of a table in arrow record batches
+*/
+
+use std::sync::Arc;
+
+use anyhow::Result;
+
+use arrow::{
+    array::{ArrayRef, Int32Array, Int64Array, StringArray},
+    datatypes::{DataType, Field, Schema as ArrowSchema},
+    record_batch::RecordBatch,
+};
+
+use crate::{model::schema::SchemaStruct, table::scan::changelog::ChangeType};
+
+use super::schema::iceberg_to_arrow_schema;
+
+/// Name of the metadata column with the type of a change, either `INSERT` or `DELETE`
+pub static CHANGE_TYPE: &str = "_change_type";
+/// Name of the metadata column with the position of the snapshot of a change in the changelog
+pub static CHANGE_ORDINAL: &str = "_change_ordinal";
+/// Name of the metadata column with the id of the snapshot that committed a change
+pub static COMMIT_SNAPSHOT_ID: &str = "_commit_snapshot_id";
+
+// Field ids of the metadata columns reserved by the iceberg spec
+const CHANGE_TYPE_ID: i64 = i32::MAX as i64 - 104;
+const CHANGE_ORDINAL_ID: i64 = i32::MAX as i64 - 105;
+const COMMIT_SNAPSHOT_ID_ID: i64 = i32::MAX as i64 - 106;
+
+/// Get the arrow schema of the changelog of a table with the given schema. The metadata columns follow the columns of
+/// the table.
+pub fn changelog_schema(schema: &SchemaStruct) -> Result<ArrowSchema> {
+    let mut fields = iceberg_to_arrow_schema(schema)?.fields;
+    fields.extend(change_fields());
+    Ok(ArrowSchema::new(fields))
+}
+
+/// Append the changelog metadata columns to the rows of a change. All rows get the same change type, ordinal and
+/// snapshot id.
+pub fn add_change_columns(
+    rows: &RecordBatch,
+    change_type: &ChangeType,
+    change_ordinal: i32,
+    commit_snapshot_id: i64,
+) -> Result<RecordBatch> {
+    let num_rows = rows.num_rows();
+    let mut fields = rows.schema().fields().clone();
+    fields.extend(change_fields());
+    let mut columns = rows.columns().to_vec();
+    columns.extend([
+        Arc::new(StringArray::from(vec![change_type.to_string(); num_rows])) as ArrayRef,
+        Arc::new(Int32Array::from(vec![change_ordinal; num_rows])) as ArrayRef,
+        Arc::new(Int64Array::from(vec![commit_snapshot_id; num_rows])) as ArrayRef,
+    ]);
+    Ok(RecordBatch::try_new(
+        Arc::new(ArrowSchema::new(fields)),
+        columns,
+    )?)
+}
+
+fn change_fields() -> Vec<Field> {
+    vec![
+        Field::new_dict(CHANGE_TYPE, DataType::Utf8, false, CHANGE_TYPE_ID, false),
+        Field::new_dict(
+            CHANGE_ORDINAL,
+            DataType::Int32,
+            false,
+            CHANGE_ORDINAL_ID,
+            false,
+        ),
+        Field::new_dict(
+            COMMIT_SNAPSHOT_ID,
+            DataType::Int64,
+            false,
+            COMMIT_SNAPSHOT_ID_ID,
+            false,
+        ),
+    ]
+}
@@ -1,4 +1,5 @@
 /*!
  * Conversion from arrow to iceberg and vice-versa
 */
+pub mod changelog;
 pub mod schema;
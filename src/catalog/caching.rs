@@ -0,0 +1,358 @@
+/*!
+Defining the [CachingCatalog] struct, a [Catalog] that caches the relations loaded from another catalog.
+
+Loading a relation reads its metadata file and manifest list from the object store. The caching catalog keeps loaded
+relations in memory until they expire, are invalidated with [Catalog::invalidate_table] or are changed through the
+caching catalog itself.
+*/
+
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, Result};
+use object_store::ObjectStore;
+
+use super::identifier::Identifier;
+use super::namespace::Namespace;
+use super::relation::Relation;
+use super::{Catalog, TableCommit};
+use crate::model::{partition::PartitionSpec, schema::SchemaV2, sort::SortOrder};
+use crate::table::Table;
+use crate::view::View;
+
+/// Catalog that wraps another catalog and caches the relations loaded from it.
+pub struct CachingCatalog<C: Catalog> {
+    catalog: Arc<C>,
+    expiration: Duration,
+    /// Cached relations together with the time they were loaded. The relations stay associated with the wrapped catalog,
+    /// so that the cache doesn't keep the caching catalog alive.
+    relations: RwLock<HashMap<Identifier, (Instant, Relation)>>,
+}
+
+impl<C: Catalog> CachingCatalog<C> {
+    /// Wrap the catalog so that loaded relations are cached for the given duration.
+    pub fn new(catalog: Arc<C>, expiration: Duration) -> Self {
+        CachingCatalog {
+            catalog,
+            expiration,
+            relations: RwLock::new(HashMap::new()),
+        }
+    }
+    /// Get the cached relation if it didn't expire yet.
+    fn cached(&self, identifier: &Identifier) -> Result<Option<Relation>> {
+        Ok(self
+            .relations
+            .read()
+            .map_err(|err| anyhow!(err.to_string()))?
+            .get(identifier)
+            .filter(|(loaded, _)| loaded.elapsed() < self.expiration)
+            .map(|(_, relation)| relation.clone()))
+    }
+    /// Remove the relation from the cache.
+    fn invalidate(&self, identifier: &Identifier) -> Result<()> {
+        self.relations
+            .write()
+            .map_err(|err| anyhow!(err.to_string()))?
+            .remove(identifier);
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl<C: Catalog + 'static> Catalog for CachingCatalog<C> {
+    async fn list_tables(&self, namespace: &Namespace) -> Result<Vec<Identifier>> {
+        self.catalog.list_tables(namespace).await
+    }
+    async fn list_namespaces(&self, parent: Option<&str>) -> Result<Vec<Namespace>> {
+        self.catalog.list_namespaces(parent).await
+    }
+    async fn create_namespace(
+        &self,
+        namespace: &Namespace,
+        properties: HashMap<String, String>,
+    ) -> Result<()> {
+        self.catalog.create_namespace(namespace, properties).await
+    }
+    async fn drop_namespace(&self, namespace: &Namespace) -> Result<()> {
+        self.catalog.drop_namespace(namespace).await
+    }
+    async fn load_namespace_properties(
+        &self,
+        namespace: &Namespace,
+    ) -> Result<HashMap<String, String>> {
+        self.catalog.load_namespace_properties(namespace).await
+    }
+    async fn update_namespace_properties(
+        &self,
+        namespace: &Namespace,
+        removals: &[String],
+        updates: HashMap<String, String>,
+    ) -> Result<()> {
+        self.catalog
+            .update_namespace_properties(namespace, removals, updates)
+            .await
+    }
+    async fn table_exists(&self, identifier: &Identifier) -> Result<bool> {
+        self.catalog.table_exists(identifier).await
+    }
+    async fn drop_table(&self, identifier: &Identifier) -> Result<()> {
+        let result = self.catalog.drop_table(identifier).await;
+        self.invalidate(identifier)?;
+        result
+    }
+    async fn rename_table(&self, from: &Identifier, to: &Identifier) -> Result<()> {
+        let result = self.catalog.rename_table(from, to).await;
+        self.invalidate(from)?;
+        self.invalidate(to)?;
+        result
+    }
+    async fn create_table(
+        self: Arc<Self>,
+        identifier: Identifier,
+        schema: SchemaV2,
+        partition_spec: Option<PartitionSpec>,
+        sort_order: Option<SortOrder>,
+        location: Option<&str>,
+        properties: HashMap<String, String>,
+    ) -> Result<Table> {
+        let mut table = self
+            .catalog
+            .clone()
+            .create_table(
+                identifier,
+                schema,
+                partition_spec,
+                sort_order,
+                location,
+                properties,
+            )
+            .await?;
+        table.set_catalog(self);
+        Ok(table)
+    }
+    async fn create_view(
+        self: Arc<Self>,
+        identifier: Identifier,
+        sql: &str,
+        schema: SchemaV2,
+        location: Option<&str>,
+        properties: HashMap<String, String>,
+    ) -> Result<View> {
+        let mut view = self
+            .catalog
+            .clone()
+            .create_view(identifier, sql, schema, location, properties)
+            .await?;
+        view.set_catalog(self);
+        Ok(view)
+    }
+    async fn drop_view(self: Arc<Self>, identifier: &Identifier) -> Result<()> {
+        let result = self.catalog.clone().drop_view(identifier).await;
+        self.invalidate(identifier)?;
+        result
+    }
+    async fn load_table(self: Arc<Self>, identifier: &Identifier) -> Result<Relation> {
+        let mut relation = match self.cached(identifier)? {
+            Some(relation) => relation,
+            None => {
+                let relation = self.catalog.clone().load_table(identifier).await?;
+                self.relations
+                    .write()
+                    .map_err(|err| anyhow!(err.to_string()))?
+                    .insert(identifier.clone(), (Instant::now(), relation.clone()));
+                relation
+            }
+        };
+        // Commits on the returned relation have to go through the caching catalog to invalidate the cache
+        relation.set_catalog(self);
+        Ok(relation)
+    }
+    async fn invalidate_table(&self, identifier: &Identifier) -> Result<()> {
+        self.invalidate(identifier)?;
+        self.catalog.invalidate_table(identifier).await
+    }
+    async fn register_table(
+        self: Arc<Self>,
+        identifier: Identifier,
+        metadata_file_location: &str,
+    ) -> Result<Relation> {
+        let mut relation = self
+            .catalog
+            .clone()
+            .register_table(identifier, metadata_file_location)
+            .await?;
+        relation.set_catalog(self);
+        Ok(relation)
+    }
+    async fn update_table(
+        self: Arc<Self>,
+        identifier: Identifier,
+        metadata_file_location: &str,
+        previous_metadata_file_location: &str,
+    ) -> Result<Relation> {
+        // The cached relation is outdated after a successful commit and possibly already before a failed one
+        let result = self
+            .catalog
+            .clone()
+            .update_table(
+                identifier.clone(),
+                metadata_file_location,
+                previous_metadata_file_location,
+            )
+            .await;
+        self.invalidate(&identifier)?;
+        let mut relation = result?;
+        relation.set_catalog(self);
+        Ok(relation)
+    }
+    async fn update_tables(self: Arc<Self>, commits: Vec<TableCommit>) -> Result<Vec<Relation>> {
+        let identifiers: Vec<Identifier> = commits
+            .iter()
+            .map(|commit| commit.identifier.clone())
+            .collect();
+        let result = self.catalog.clone().update_tables(commits).await;
+        for identifier in &identifiers {
+            self.invalidate(identifier)?;
+        }
+        let mut relations = result?;
+        for relation in &mut relations {
+            relation.set_catalog(self.clone());
+        }
+        Ok(relations)
+    }
+    fn supports_multi_table_updates(&self) -> bool {
+        self.catalog.supports_multi_table_updates()
+    }
+    async fn initialize(self: Arc<Self>, properties: &HashMap<String, String>) -> Result<()> {
+        self.catalog.clone().initialize(properties).await
+    }
+    fn object_store(&self) -> Arc<dyn ObjectStore> {
+        self.catalog.object_store()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::{sync::Arc, time::Duration};
+
+    use object_store::{memory::InMemory, ObjectStore};
+
+    use crate::{
+        catalog::{
+            caching::CachingCatalog, identifier::Identifier, memory::MemoryCatalog,
+            relation::Relation, Catalog,
+        },
+        table::table_builder::TableBuilder,
+    };
+
+    use crate::catalog::tests::schema;
+
+    #[tokio::test]
+    async fn test_cache_invalidation() {
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let memory_catalog = Arc::new(MemoryCatalog::new(object_store));
+        let catalog: Arc<dyn Catalog> = Arc::new(CachingCatalog::new(
+            Arc::clone(&memory_catalog),
+            Duration::from_secs(3600),
+        ));
+        let identifier = Identifier::parse("cache.table1").unwrap();
+        let table = TableBuilder::new_metastore_table(
+            "test/",
+            schema(),
+            identifier.clone(),
+            Arc::clone(&catalog),
+        )
+        .unwrap()
+        .commit()
+        .await
+        .unwrap();
+        let first_metadata_location = table.metadata_location().to_string();
+        catalog.clone().load_table(&identifier).await.unwrap();
+
+        // Commits through the wrapped catalog are not visible until the cache is invalidated
+        let mut uncached_table = match memory_catalog
+            .clone()
+            .load_table(&identifier)
+            .await
+            .unwrap()
+        {
+            Relation::Table(table) => table,
+            _ => panic!("Relation is not a table."),
+        };
+        uncached_table.new_transaction().commit().await.unwrap();
+        let second_metadata_location = uncached_table.metadata_location().to_string();
+        assert_eq!(
+            catalog
+                .clone()
+                .load_table(&identifier)
+                .await
+                .unwrap()
+                .metadata_location(),
+            first_metadata_location
+        );
+        catalog.invalidate_table(&identifier).await.unwrap();
+        let mut cached_table = match catalog.clone().load_table(&identifier).await.unwrap() {
+            Relation::Table(table) => table,
+            _ => panic!("Relation is not a table."),
+        };
+        assert_eq!(cached_table.metadata_location(), second_metadata_location);
+
+        // Commits through the caching catalog invalidate the cache
+        cached_table.new_transaction().commit().await.unwrap();
+        assert_eq!(
+            catalog
+                .clone()
+                .load_table(&identifier)
+                .await
+                .unwrap()
+                .metadata_location(),
+            cached_table.metadata_location()
+        );
+        assert_ne!(cached_table.metadata_location(), second_metadata_location);
+    }
+
+    #[tokio::test]
+    async fn test_cache_expiration() {
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let memory_catalog = Arc::new(MemoryCatalog::new(object_store));
+        let catalog: Arc<dyn Catalog> = Arc::new(CachingCatalog::new(
+            Arc::clone(&memory_catalog),
+            Duration::ZERO,
+        ));
+        let identifier = Identifier::parse("cache.table1").unwrap();
+        TableBuilder::new_metastore_table(
+            "test/",
+            schema(),
+            identifier.clone(),
+            Arc::clone(&catalog),
+        )
+        .unwrap()
+        .commit()
+        .await
+        .unwrap();
+        catalog.clone().load_table(&identifier).await.unwrap();
+
+        let mut uncached_table = match memory_catalog
+            .clone()
+            .load_table(&identifier)
+            .await
+            .unwrap()
+        {
+            Relation::Table(table) => table,
+            _ => panic!("Relation is not a table."),
+        };
+        uncached_table.new_transaction().commit().await.unwrap();
+        // Expired relations are loaded again
+        assert_eq!(
+            catalog
+                .clone()
+                .load_table(&identifier)
+                .await
+                .unwrap()
+                .metadata_location(),
+            uncached_table.metadata_location()
+        );
+    }
+}
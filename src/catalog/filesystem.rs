@@ -0,0 +1,877 @@
+/*!
+Defining the [FileSystemCatalog] struct, a [Catalog] that uses the directory structure of an [ObjectStore] as catalog.
+
+Below the warehouse location every directory corresponds to a namespace level, except for directories containing a
+`metadata` directory with versioned `v<N>.metadata.json` files, which are tables or views. The current metadata file of
+a relation is the one with the highest version. Commits are performed with the same `copy_if_not_exists` protocol that
+is used for filesystem tables, so no additional metastore is required.
+
+Relations keep their location when they are renamed, only their versioned metadata files are moved to the directory
+of the new identifier. The old directory still contains the data and the other metadata files of the relation and is
+therefore neither listed as a relation nor as a namespace. No relation can be registered in a directory that contains
+files, so that relations never share their files.
+
+Object stores have no empty directories. Explicitly created namespaces therefore contain a `.namespace.json` file that
+stores the namespace properties. The `location` property of a namespace always refers to its directory, so that new
+relations are created where the catalog expects them.
+*/
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use futures::TryStreamExt;
+use object_store::{path::Path, ObjectMeta, ObjectStore};
+
+use super::identifier::{Identifier, SEPARATOR};
+use super::namespace::Namespace;
+use super::relation::{delete_relation_files, Relation};
+use super::{Catalog, CommitConflict};
+use crate::util;
+
+/// Name of the file that stores the properties of a namespace.
+static NAMESPACE_FILE: &str = ".namespace.json";
+
+/// Catalog that maps the directories below a warehouse location to namespaces and relations.
+pub struct FileSystemCatalog {
+    warehouse: Path,
+    object_store: Arc<dyn ObjectStore>,
+}
+
+impl FileSystemCatalog {
+    /// Create a catalog for the warehouse location in the given object store.
+    pub fn new(warehouse: &str, object_store: Arc<dyn ObjectStore>) -> Self {
+        FileSystemCatalog {
+            warehouse: util::strip_prefix(warehouse).into(),
+            object_store,
+        }
+    }
+    /// Get the directory that corresponds to the given namespace or identifier levels.
+    fn location<'a>(&self, levels: impl IntoIterator<Item = &'a String>) -> Path {
+        levels
+            .into_iter()
+            .fold(self.warehouse.clone(), |path, level| {
+                path.child(level.as_str())
+            })
+    }
+    /// Get the directory of a relation.
+    fn relation_location(&self, identifier: &Identifier) -> Path {
+        self.location(
+            identifier
+                .namespace()
+                .levels()
+                .iter()
+                .chain(std::iter::once(&identifier.name().to_string())),
+        )
+    }
+    /// Get the versions and paths of the versioned metadata files of the relation at the given location, ordered by
+    /// version.
+    async fn metadata_files(&self, location: &Path) -> Result<Vec<(i64, Path)>> {
+        let files = self
+            .object_store
+            .list_with_delimiter(Some(&location.child("metadata")))
+            .await
+            .map_err(|err| anyhow!(err.to_string()))?;
+        let mut metadata_files: Vec<(i64, Path)> = files
+            .objects
+            .into_iter()
+            .filter_map(|object_meta| {
+                metadata_version(&object_meta.location)
+                    .map(|version| (version, object_meta.location))
+            })
+            .collect();
+        metadata_files.sort_by_key(|(version, _)| *version);
+        Ok(metadata_files)
+    }
+    /// Get the version and path of the current metadata file of the relation at the given location.
+    /// Returns None if the location doesn't contain a relation.
+    async fn current_metadata(&self, location: &Path) -> Result<Option<(i64, Path)>> {
+        Ok(self.metadata_files(location).await?.pop())
+    }
+    /// Read the properties of an explicitly created namespace. Returns None if the namespace has no properties file.
+    async fn namespace_properties(
+        &self,
+        namespace: &Namespace,
+    ) -> Result<Option<HashMap<String, String>>> {
+        let path = self.location(namespace.levels()).child(NAMESPACE_FILE);
+        match self.object_store.get(&path).await {
+            Ok(result) => {
+                let bytes = result
+                    .bytes()
+                    .await
+                    .map_err(|err| anyhow!(err.to_string()))?;
+                Ok(Some(
+                    serde_json::from_slice(&bytes).map_err(|err| anyhow!(err.to_string()))?,
+                ))
+            }
+            Err(object_store::Error::NotFound { .. }) => Ok(None),
+            Err(err) => Err(anyhow!(err.to_string())),
+        }
+    }
+    /// Write the properties file of a namespace.
+    async fn write_namespace_properties(
+        &self,
+        namespace: &Namespace,
+        properties: &HashMap<String, String>,
+    ) -> Result<()> {
+        let path = self.location(namespace.levels()).child(NAMESPACE_FILE);
+        let json = serde_json::to_string(properties).map_err(|err| anyhow!(err.to_string()))?;
+        self.object_store
+            .put(&path, json.into())
+            .await
+            .map_err(|err| anyhow!(err.to_string()))
+    }
+    /// Delete the given files, ignoring errors. Used to remove the copies of an aborted rename.
+    async fn delete_files(&self, paths: &[Path]) {
+        for path in paths {
+            self.object_store.delete(path).await.ok();
+        }
+    }
+    /// Get the directories directly below the given location.
+    async fn directories(&self, location: &Path) -> Result<Vec<Path>> {
+        Ok(self
+            .object_store
+            .list_with_delimiter(Some(location))
+            .await
+            .map_err(|err| anyhow!(err.to_string()))?
+            .common_prefixes)
+    }
+}
+
+/// Parse the version of a metadata file named `v<N>.metadata.json`.
+fn metadata_version(path: &Path) -> Option<i64> {
+    path.filename()?
+        .strip_prefix('v')?
+        .strip_suffix(".metadata.json")?
+        .parse()
+        .ok()
+}
+
+#[async_trait::async_trait]
+impl Catalog for FileSystemCatalog {
+    async fn list_tables(&self, namespace: &Namespace) -> Result<Vec<Identifier>> {
+        let mut identifiers = Vec::new();
+        for directory in self.directories(&self.location(namespace.levels())).await? {
+            if self.current_metadata(&directory).await?.is_some() {
+                let name = directory
+                    .filename()
+                    .ok_or_else(|| anyhow!("Table location path is empty."))?;
+                identifiers.push(Identifier::try_new(
+                    &[namespace.levels(), &[name.to_string()]].concat(),
+                )?);
+            }
+        }
+        identifiers.sort_by_key(|identifier| identifier.to_string());
+        Ok(identifiers)
+    }
+    async fn list_namespaces(&self, parent: Option<&str>) -> Result<Vec<Namespace>> {
+        let parent = match parent {
+            Some(parent) if !parent.is_empty() => parent
+                .split(SEPARATOR)
+                .map(|x| x.to_string())
+                .collect::<Vec<String>>(),
+            _ => vec![],
+        };
+        let mut namespaces = Vec::new();
+        for directory in self.directories(&self.location(&parent)).await? {
+            // Directories with a metadata directory are relations or the locations of renamed relations
+            if !self
+                .directories(&directory)
+                .await?
+                .contains(&directory.child("metadata"))
+            {
+                let name = directory
+                    .filename()
+                    .ok_or_else(|| anyhow!("Namespace location path is empty."))?;
+                namespaces.push(Namespace::try_new(
+                    &[&parent[..], &[name.to_string()]].concat(),
+                )?);
+            }
+        }
+        namespaces.sort_by_key(|namespace| namespace.to_string());
+        Ok(namespaces)
+    }
+    async fn create_namespace(
+        &self,
+        namespace: &Namespace,
+        properties: HashMap<String, String>,
+    ) -> Result<()> {
+        let contents = self
+            .object_store
+            .list_with_delimiter(Some(&self.location(namespace.levels())))
+            .await
+            .map_err(|err| anyhow!(err.to_string()))?;
+        if !contents.objects.is_empty() || !contents.common_prefixes.is_empty() {
+            return Err(anyhow!(
+                "Namespace {} already exists in the catalog.",
+                namespace
+            ));
+        }
+        self.write_namespace_properties(namespace, &properties)
+            .await
+    }
+    async fn drop_namespace(&self, namespace: &Namespace) -> Result<()> {
+        if !self
+            .directories(&self.location(namespace.levels()))
+            .await?
+            .is_empty()
+        {
+            return Err(anyhow!(
+                "Namespace {} is not empty and can't be dropped.",
+                namespace
+            ));
+        }
+        if self.namespace_properties(namespace).await?.is_none() {
+            return Err(anyhow!(
+                "Namespace {} doesn't exist in the catalog.",
+                namespace
+            ));
+        }
+        self.object_store
+            .delete(&self.location(namespace.levels()).child(NAMESPACE_FILE))
+            .await
+            .map_err(|err| anyhow!(err.to_string()))
+    }
+    async fn load_namespace_properties(
+        &self,
+        namespace: &Namespace,
+    ) -> Result<HashMap<String, String>> {
+        let location = self.location(namespace.levels());
+        let mut properties = match self.namespace_properties(namespace).await? {
+            Some(properties) => properties,
+            // Namespaces that only exist implicitly as directories have no properties
+            None if !self.directories(&location).await?.is_empty() => HashMap::new(),
+            None => {
+                return Err(anyhow!(
+                    "Namespace {} doesn't exist in the catalog.",
+                    namespace
+                ))
+            }
+        };
+        // New relations are created in the directory of their namespace
+        properties.insert("location".to_string(), location.to_string());
+        Ok(properties)
+    }
+    async fn update_namespace_properties(
+        &self,
+        namespace: &Namespace,
+        removals: &[String],
+        updates: HashMap<String, String>,
+    ) -> Result<()> {
+        let mut properties = self.load_namespace_properties(namespace).await?;
+        properties.remove("location");
+        for key in removals {
+            properties.remove(key);
+        }
+        properties.extend(updates);
+        self.write_namespace_properties(namespace, &properties)
+            .await
+    }
+    async fn table_exists(&self, identifier: &Identifier) -> Result<bool> {
+        Ok(self
+            .current_metadata(&self.relation_location(identifier))
+            .await?
+            .is_some())
+    }
+    async fn drop_table(&self, identifier: &Identifier) -> Result<()> {
+        let location = self.relation_location(identifier);
+        let (_, metadata_location) = self
+            .current_metadata(&location)
+            .await?
+            .ok_or_else(|| anyhow!("Table {} doesn't exist in the catalog.", identifier))?;
+        delete_relation_files(self.object_store(), metadata_location.as_ref()).await?;
+        // The metadata files of a renamed relation are not stored below its location
+        for (_, path) in self.metadata_files(&location).await? {
+            self.object_store
+                .delete(&path)
+                .await
+                .map_err(|err| anyhow!(err.to_string()))?;
+        }
+        Ok(())
+    }
+    async fn rename_table(&self, from: &Identifier, to: &Identifier) -> Result<()> {
+        // The paths in the metadata, manifest lists and manifests are absolute, so the relation keeps its location and
+        // only its versioned metadata files are moved to the directory of the new identifier. The current version is
+        // copied first and deleted last, so that both directories always contain the current version.
+        let location = self.relation_location(from);
+        let new_location = self.relation_location(to);
+        if self.current_metadata(&new_location).await?.is_some() {
+            return Err(anyhow!("Table {} already exists in the catalog.", to));
+        }
+        let metadata_files = self.metadata_files(&location).await?;
+        let version = match metadata_files.last() {
+            Some((version, _)) => *version,
+            None => return Err(anyhow!("Table {} doesn't exist in the catalog.", from)),
+        };
+        let mut copies = Vec::with_capacity(metadata_files.len());
+        for (version, path) in metadata_files.iter().rev() {
+            let new_path = new_location
+                .child("metadata")
+                .child("v".to_string() + &version.to_string() + ".metadata.json");
+            // Copying fails if a concurrent rename or registration created the target in the meantime
+            if let Err(err) = self.object_store.copy_if_not_exists(path, &new_path).await {
+                self.delete_files(&copies).await;
+                return Err(anyhow!(
+                    "Failed to rename table {} to {}: {}",
+                    from,
+                    to,
+                    err
+                ));
+            }
+            copies.push(new_path);
+        }
+        // A commit that created a new version while the files were copied would be lost by the rename. A commit that
+        // lands between this check and the deletion of the current version is not detected.
+        if let Some((current_version, _)) = self.current_metadata(&location).await? {
+            if current_version != version {
+                self.delete_files(&copies).await;
+                return Err(CommitConflict(format!(
+                    "Failed to rename table {} to {}: the version {} was created by a concurrent commit.",
+                    from, to, current_version
+                ))
+                .into());
+            }
+        }
+        for (_, path) in &metadata_files {
+            self.object_store
+                .delete(path)
+                .await
+                .map_err(|err| anyhow!(err.to_string()))?;
+        }
+        Ok(())
+    }
+    async fn load_table(self: Arc<Self>, identifier: &Identifier) -> Result<Relation> {
+        let (_, metadata_location) = self
+            .current_metadata(&self.relation_location(identifier))
+            .await?
+            .ok_or_else(|| anyhow!("Table {} doesn't exist in the catalog.", identifier))?;
+        Relation::try_from_metadata_location(identifier.clone(), self, metadata_location.as_ref())
+            .await
+    }
+    async fn invalidate_table(&self, _identifier: &Identifier) -> Result<()> {
+        Ok(())
+    }
+    async fn register_table(
+        self: Arc<Self>,
+        identifier: Identifier,
+        metadata_file_location: &str,
+    ) -> Result<Relation> {
+        // The metadata file is copied to the first version of the relation directory. Files that were staged in the
+        // metadata directory of the relation are removed afterwards so that only versioned metadata files remain.
+        let location = self.relation_location(&identifier);
+        if self.current_metadata(&location).await?.is_some() {
+            return Err(anyhow!(
+                "Table {} already exists in the catalog.",
+                identifier
+            ));
+        }
+        let metadata_file: Path = util::strip_prefix(metadata_file_location).into();
+        // The directory of a renamed relation still contains its files, which a new relation must not share
+        let objects: Vec<ObjectMeta> = self
+            .object_store
+            .list(Some(&location))
+            .await
+            .map_err(|err| anyhow!(err.to_string()))?
+            .try_collect()
+            .await
+            .map_err(|err| anyhow!(err.to_string()))?;
+        if objects
+            .iter()
+            .any(|object| object.location != metadata_file)
+        {
+            return Err(anyhow!(
+                "Table {} can't be registered because its directory {} contains the files of another relation.",
+                identifier,
+                location
+            ));
+        }
+        let final_path = location.child("metadata").child("v1.metadata.json");
+        self.object_store
+            .copy_if_not_exists(&metadata_file, &final_path)
+            .await
+            .map_err(|err| anyhow!(err.to_string()))?;
+        // The table is registered once the copy succeeded, failing to delete the staged file doesn't undo that
+        if metadata_file.prefix_matches(&location.child("metadata")) {
+            self.object_store.delete(&metadata_file).await.ok();
+        }
+        Relation::try_from_metadata_location(identifier, self, final_path.as_ref()).await
+    }
+    async fn update_table(
+        self: Arc<Self>,
+        identifier: Identifier,
+        metadata_file_location: &str,
+        previous_metadata_file_location: &str,
+    ) -> Result<Relation> {
+        // Copying the new metadata file to the next version fails if another commit created that version in the
+        // meantime, which makes the swap atomic.
+        let location = self.relation_location(&identifier);
+        let (version, current_metadata_location) = self
+            .current_metadata(&location)
+            .await?
+            .ok_or_else(|| anyhow!("Table {} doesn't exist in the catalog.", identifier))?;
+        if current_metadata_location != util::strip_prefix(previous_metadata_file_location).into() {
+            return Err(CommitConflict(format!(
+                "Failed to update table {}: the metadata location {} is not current anymore.",
+                identifier, previous_metadata_file_location
+            ))
+            .into());
+        }
+        let metadata_file: Path = util::strip_prefix(metadata_file_location).into();
+        let final_path = location
+            .child("metadata")
+            .child("v".to_string() + &(version + 1).to_string() + ".metadata.json");
+        self.object_store
+            .copy_if_not_exists(&metadata_file, &final_path)
+            .await
+            .map_err(|err| match err {
+                object_store::Error::AlreadyExists { .. } => CommitConflict(format!(
+                    "Failed to update table {}: the version {} was created by a concurrent commit.",
+                    identifier,
+                    version + 1
+                ))
+                .into(),
+                err => anyhow!("Failed to update table {}: {}", identifier, err),
+            })?;
+        // The commit succeeded once the copy succeeded, failing to delete the staged file must not report it as failed
+        self.object_store.delete(&metadata_file).await.ok();
+        Relation::try_from_metadata_location(identifier, self, final_path.as_ref()).await
+    }
+    async fn initialize(self: Arc<Self>, _properties: &HashMap<String, String>) -> Result<()> {
+        Ok(())
+    }
+    fn object_store(&self) -> Arc<dyn ObjectStore> {
+        Arc::clone(&self.object_store)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::{
+        collections::HashMap,
+        fmt,
+        ops::Range,
+        sync::{Arc, Mutex},
+    };
+
+    use bytes::Bytes;
+    use futures::stream::BoxStream;
+    use object_store::{
+        memory::InMemory, path::Path, GetResult, ListResult, MultipartId, ObjectMeta, ObjectStore,
+    };
+    use tokio::io::AsyncWrite;
+
+    use crate::{
+        catalog::{
+            filesystem::FileSystemCatalog, identifier::Identifier, namespace::Namespace,
+            relation::Relation, Catalog, CommitConflict,
+        },
+        table::table_builder::TableBuilder,
+    };
+
+    use crate::catalog::tests::{check_rename_table, schema, UndeletableStore};
+
+    #[tokio::test]
+    async fn test_list_and_load_table() {
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let catalog: Arc<dyn Catalog> = Arc::new(FileSystemCatalog::new("warehouse", object_store));
+        for name in ["level1.level2.table1", "level1.table2"] {
+            TableBuilder::new_metastore_table(
+                "warehouse/",
+                schema(),
+                Identifier::parse(name).unwrap(),
+                Arc::clone(&catalog),
+            )
+            .unwrap()
+            .commit()
+            .await
+            .unwrap();
+        }
+
+        assert_eq!(
+            catalog.list_namespaces(None).await.unwrap(),
+            vec![Namespace::try_new(&["level1".to_string()]).unwrap()]
+        );
+        assert_eq!(
+            catalog.list_namespaces(Some("level1")).await.unwrap(),
+            vec![Namespace::try_new(&["level1".to_string(), "level2".to_string()]).unwrap()]
+        );
+        let identifier = Identifier::parse("level1.table2").unwrap();
+        assert_eq!(
+            catalog.list_tables(identifier.namespace()).await.unwrap(),
+            vec![identifier.clone()]
+        );
+        assert!(catalog.table_exists(&identifier).await.unwrap());
+        let relation = catalog.clone().load_table(&identifier).await.unwrap();
+        assert!(matches!(relation, Relation::Table(_)));
+        assert_eq!(
+            relation.metadata_location(),
+            "warehouse/level1/table2/metadata/v1.metadata.json"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_update_table() {
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let catalog: Arc<dyn Catalog> = Arc::new(FileSystemCatalog::new("warehouse", object_store));
+        let identifier = Identifier::parse("update.table1").unwrap();
+        let mut table = TableBuilder::new_metastore_table(
+            "warehouse/",
+            schema(),
+            identifier.clone(),
+            Arc::clone(&catalog),
+        )
+        .unwrap()
+        .commit()
+        .await
+        .unwrap();
+        let previous_metadata_location = table.metadata_location().to_string();
+
+        table.new_transaction().commit().await.unwrap();
+        assert_eq!(
+            table.metadata_location(),
+            "warehouse/update/table1/metadata/v2.metadata.json"
+        );
+        assert_eq!(
+            catalog
+                .clone()
+                .load_table(&identifier)
+                .await
+                .unwrap()
+                .metadata_location(),
+            table.metadata_location()
+        );
+
+        // Committing based on an outdated metadata file has to fail
+        assert!(catalog
+            .clone()
+            .update_table(
+                identifier.clone(),
+                &previous_metadata_location,
+                &previous_metadata_location
+            )
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_update_table_failed_cleanup() {
+        let object_store: Arc<dyn ObjectStore> = Arc::new(UndeletableStore(InMemory::new()));
+        let catalog: Arc<dyn Catalog> = Arc::new(FileSystemCatalog::new("warehouse", object_store));
+        let identifier = Identifier::parse("update.table1").unwrap();
+        let mut table = TableBuilder::new_metastore_table(
+            "warehouse/",
+            schema(),
+            identifier.clone(),
+            Arc::clone(&catalog),
+        )
+        .unwrap()
+        .commit()
+        .await
+        .unwrap();
+
+        // The staged metadata file can't be deleted, which doesn't affect the commit
+        table
+            .new_transaction()
+            .fast_append(vec![
+                "warehouse/update/table1/data/file1.parquet".to_string()
+            ])
+            .commit()
+            .await
+            .unwrap();
+        assert_eq!(
+            table.metadata_location(),
+            "warehouse/update/table1/metadata/v2.metadata.json"
+        );
+        assert_eq!(table.manifests().len(), 1);
+        assert_eq!(
+            catalog
+                .clone()
+                .load_table(&identifier)
+                .await
+                .unwrap()
+                .metadata_location(),
+            table.metadata_location()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_drop_table() {
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let catalog: Arc<dyn Catalog> = Arc::new(FileSystemCatalog::new("warehouse", object_store));
+        let identifier = Identifier::parse("drop.table1").unwrap();
+        TableBuilder::new_metastore_table(
+            "warehouse/",
+            schema(),
+            identifier.clone(),
+            Arc::clone(&catalog),
+        )
+        .unwrap()
+        .commit()
+        .await
+        .unwrap();
+
+        catalog.drop_table(&identifier).await.unwrap();
+        assert!(!catalog.table_exists(&identifier).await.unwrap());
+        assert!(catalog.clone().load_table(&identifier).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_rename_table() {
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let catalog: Arc<dyn Catalog> = Arc::new(FileSystemCatalog::new("warehouse", object_store));
+        check_rename_table(catalog).await;
+    }
+
+    /// Object store that performs a concurrent commit, given as a copy from a source to a target path, the first time
+    /// a file is copied below the trigger path.
+    #[derive(Debug)]
+    struct ConcurrentCommitStore {
+        store: InMemory,
+        trigger: Path,
+        commit: Mutex<Option<(Path, Path)>>,
+    }
+
+    impl fmt::Display for ConcurrentCommitStore {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "ConcurrentCommitStore({})", self.store)
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl ObjectStore for ConcurrentCommitStore {
+        async fn put(&self, location: &Path, bytes: Bytes) -> object_store::Result<()> {
+            self.store.put(location, bytes).await
+        }
+        async fn put_multipart(
+            &self,
+            location: &Path,
+        ) -> object_store::Result<(MultipartId, Box<dyn AsyncWrite + Unpin + Send>)> {
+            self.store.put_multipart(location).await
+        }
+        async fn abort_multipart(
+            &self,
+            location: &Path,
+            multipart_id: &MultipartId,
+        ) -> object_store::Result<()> {
+            self.store.abort_multipart(location, multipart_id).await
+        }
+        async fn get(&self, location: &Path) -> object_store::Result<GetResult> {
+            self.store.get(location).await
+        }
+        async fn get_range(
+            &self,
+            location: &Path,
+            range: Range<usize>,
+        ) -> object_store::Result<Bytes> {
+            self.store.get_range(location, range).await
+        }
+        async fn head(&self, location: &Path) -> object_store::Result<ObjectMeta> {
+            self.store.head(location).await
+        }
+        async fn delete(&self, location: &Path) -> object_store::Result<()> {
+            self.store.delete(location).await
+        }
+        async fn list(
+            &self,
+            prefix: Option<&Path>,
+        ) -> object_store::Result<BoxStream<'_, object_store::Result<ObjectMeta>>> {
+            self.store.list(prefix).await
+        }
+        async fn list_with_delimiter(
+            &self,
+            prefix: Option<&Path>,
+        ) -> object_store::Result<ListResult> {
+            self.store.list_with_delimiter(prefix).await
+        }
+        async fn copy(&self, from: &Path, to: &Path) -> object_store::Result<()> {
+            self.store.copy(from, to).await
+        }
+        async fn copy_if_not_exists(&self, from: &Path, to: &Path) -> object_store::Result<()> {
+            self.store.copy_if_not_exists(from, to).await?;
+            if to.prefix_matches(&self.trigger) {
+                let commit = self.commit.lock().unwrap().take();
+                if let Some((source, target)) = commit {
+                    self.store.copy_if_not_exists(&source, &target).await?;
+                }
+            }
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_rename_table_concurrent_commit() {
+        let object_store = Arc::new(ConcurrentCommitStore {
+            store: InMemory::new(),
+            trigger: "warehouse/renamed".into(),
+            commit: Mutex::new(None),
+        });
+        let catalog: Arc<dyn Catalog> = Arc::new(FileSystemCatalog::new(
+            "warehouse",
+            object_store.clone() as Arc<dyn ObjectStore>,
+        ));
+        let identifier = Identifier::parse("rename.table1").unwrap();
+        let renamed_identifier = Identifier::parse("renamed.table1").unwrap();
+        let mut table = TableBuilder::new_metastore_table(
+            "warehouse/",
+            schema(),
+            identifier.clone(),
+            Arc::clone(&catalog),
+        )
+        .unwrap()
+        .commit()
+        .await
+        .unwrap();
+        table.new_transaction().commit().await.unwrap();
+
+        // Another writer commits the third version while the metadata files are copied
+        *object_store.commit.lock().unwrap() = Some((
+            "warehouse/rename/table1/metadata/v2.metadata.json".into(),
+            "warehouse/rename/table1/metadata/v3.metadata.json".into(),
+        ));
+        let result = catalog.rename_table(&identifier, &renamed_identifier).await;
+        assert!(result.unwrap_err().is::<CommitConflict>());
+
+        // The rename didn't change the table and left no copies behind
+        assert_eq!(
+            catalog
+                .clone()
+                .load_table(&identifier)
+                .await
+                .unwrap()
+                .metadata_location(),
+            "warehouse/rename/table1/metadata/v3.metadata.json"
+        );
+        assert!(!catalog.table_exists(&renamed_identifier).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_recreate_renamed_table() {
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let catalog: Arc<dyn Catalog> =
+            Arc::new(FileSystemCatalog::new("warehouse", object_store.clone()));
+        for name in ["rename", "renamed"] {
+            catalog
+                .create_namespace(
+                    &Namespace::try_new(&[name.to_string()]).unwrap(),
+                    HashMap::new(),
+                )
+                .await
+                .unwrap();
+        }
+        let identifier = Identifier::parse("rename.table1").unwrap();
+        let renamed_identifier = Identifier::parse("renamed.table1").unwrap();
+        let create_table = || {
+            catalog.clone().create_table(
+                identifier.clone(),
+                schema(),
+                None,
+                None,
+                None,
+                HashMap::new(),
+            )
+        };
+        let mut table = create_table().await.unwrap();
+        let data_file = "warehouse/rename/table1/data/file1.parquet";
+        object_store
+            .put(&data_file.into(), "data".into())
+            .await
+            .unwrap();
+        table
+            .new_transaction()
+            .fast_append(vec![data_file.to_string()])
+            .commit()
+            .await
+            .unwrap();
+        catalog
+            .rename_table(&identifier, &renamed_identifier)
+            .await
+            .unwrap();
+
+        // The directory of the renamed table still contains its files
+        assert!(create_table().await.is_err());
+        assert!(catalog.drop_table(&identifier).await.is_err());
+        match catalog
+            .clone()
+            .load_table(&renamed_identifier)
+            .await
+            .unwrap()
+        {
+            Relation::Table(renamed) => {
+                assert_eq!(renamed.files(None).await.unwrap()[0].file_path(), data_file)
+            }
+            Relation::View(_) => panic!("Relation is not a table."),
+        }
+        assert!(object_store.head(&data_file.into()).await.is_ok());
+
+        // Once the renamed table is dropped, its old directory can be used again
+        catalog.drop_table(&renamed_identifier).await.unwrap();
+        create_table().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_namespace_properties() {
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let catalog: Arc<dyn Catalog> = Arc::new(FileSystemCatalog::new("warehouse", object_store));
+        let namespace = Namespace::try_new(&["namespace".to_string()]).unwrap();
+        catalog
+            .create_namespace(
+                &namespace,
+                HashMap::from_iter(vec![
+                    ("owner".to_string(), "me".to_string()),
+                    ("comment".to_string(), "test".to_string()),
+                ]),
+            )
+            .await
+            .unwrap();
+        assert!(catalog
+            .create_namespace(&namespace, HashMap::new())
+            .await
+            .is_err());
+        assert_eq!(
+            catalog.list_namespaces(None).await.unwrap(),
+            vec![namespace.clone()]
+        );
+
+        catalog
+            .update_namespace_properties(
+                &namespace,
+                &["comment".to_string()],
+                HashMap::from_iter(vec![("description".to_string(), "test".to_string())]),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            catalog.load_namespace_properties(&namespace).await.unwrap(),
+            HashMap::from_iter(vec![
+                ("owner".to_string(), "me".to_string()),
+                ("description".to_string(), "test".to_string()),
+                ("location".to_string(), "warehouse/namespace".to_string()),
+            ])
+        );
+
+        // Without a location the table is created in the directory of its namespace
+        let identifier = Identifier::parse("namespace.table1").unwrap();
+        let table = catalog
+            .clone()
+            .create_table(
+                identifier.clone(),
+                schema(),
+                None,
+                None,
+                None,
+                HashMap::new(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            table.metadata_location(),
+            "warehouse/namespace/table1/metadata/v1.metadata.json"
+        );
+        // Namespaces that contain tables can't be dropped
+        assert!(catalog.drop_namespace(&namespace).await.is_err());
+        catalog.drop_table(&identifier).await.unwrap();
+        catalog.drop_namespace(&namespace).await.unwrap();
+        assert!(catalog.load_namespace_properties(&namespace).await.is_err());
+    }
+}
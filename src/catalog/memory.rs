@@ -0,0 +1,564 @@
+/*!
+Defining the [MemoryCatalog] struct, an in-memory implementation of the [Catalog] trait.
+
+The catalog only keeps the pointers from [Identifier]s to their current metadata files. The metadata files themselves,
+as well as manifests and data files, are stored in the associated [ObjectStore].
+*/
+
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+use anyhow::{anyhow, Result};
+use object_store::ObjectStore;
+
+use super::identifier::{Identifier, SEPARATOR};
+use super::namespace::Namespace;
+use super::relation::{delete_relation_files, Relation};
+use super::{Catalog, CommitConflict, TableCommit};
+
+/// Catalog that stores the metadata pointers of its tables and views in memory.
+pub struct MemoryCatalog {
+    object_store: Arc<dyn ObjectStore>,
+    namespaces: RwLock<HashMap<Namespace, HashMap<String, String>>>,
+    tables: RwLock<HashMap<Identifier, String>>,
+}
+
+impl MemoryCatalog {
+    /// Create an empty catalog whose metadata and data files are stored in the given object store.
+    pub fn new(object_store: Arc<dyn ObjectStore>) -> Self {
+        MemoryCatalog {
+            object_store,
+            namespaces: RwLock::new(HashMap::new()),
+            tables: RwLock::new(HashMap::new()),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Catalog for MemoryCatalog {
+    async fn list_tables(&self, namespace: &Namespace) -> Result<Vec<Identifier>> {
+        Ok(self
+            .tables
+            .read()
+            .map_err(|err| anyhow!(err.to_string()))?
+            .keys()
+            .filter(|identifier| identifier.namespace() == namespace)
+            .cloned()
+            .collect())
+    }
+    async fn list_namespaces(&self, parent: Option<&str>) -> Result<Vec<Namespace>> {
+        let parent = match parent {
+            Some(parent) if !parent.is_empty() => parent
+                .split(SEPARATOR)
+                .map(|x| x.to_string())
+                .collect::<Vec<String>>(),
+            _ => vec![],
+        };
+        let mut namespaces = self
+            .namespaces
+            .read()
+            .map_err(|err| anyhow!(err.to_string()))?
+            .keys()
+            .filter(|namespace| {
+                namespace.len() > parent.len() && namespace.levels()[..parent.len()] == parent[..]
+            })
+            .map(|namespace| Namespace::try_new(&namespace.levels()[..parent.len() + 1]))
+            .collect::<Result<Vec<Namespace>>>()?;
+        namespaces.sort_by_key(|namespace| namespace.to_string());
+        namespaces.dedup();
+        Ok(namespaces)
+    }
+    async fn create_namespace(
+        &self,
+        namespace: &Namespace,
+        properties: HashMap<String, String>,
+    ) -> Result<()> {
+        let mut namespaces = self
+            .namespaces
+            .write()
+            .map_err(|err| anyhow!(err.to_string()))?;
+        if namespaces.contains_key(namespace) {
+            return Err(anyhow!(
+                "Namespace {} already exists in the catalog.",
+                namespace
+            ));
+        }
+        namespaces.insert(namespace.clone(), properties);
+        Ok(())
+    }
+    async fn drop_namespace(&self, namespace: &Namespace) -> Result<()> {
+        let mut namespaces = self
+            .namespaces
+            .write()
+            .map_err(|err| anyhow!(err.to_string()))?;
+        if !namespaces.contains_key(namespace) {
+            return Err(anyhow!(
+                "Namespace {} doesn't exist in the catalog.",
+                namespace
+            ));
+        }
+        let contains_namespaces = namespaces.keys().any(|child| {
+            child.len() > namespace.len()
+                && child.levels()[..namespace.len()] == namespace.levels()[..]
+        });
+        let contains_tables = self
+            .tables
+            .read()
+            .map_err(|err| anyhow!(err.to_string()))?
+            .keys()
+            .any(|identifier| identifier.namespace() == namespace);
+        if contains_namespaces || contains_tables {
+            return Err(anyhow!(
+                "Namespace {} is not empty and can't be dropped.",
+                namespace
+            ));
+        }
+        namespaces.remove(namespace);
+        Ok(())
+    }
+    async fn load_namespace_properties(
+        &self,
+        namespace: &Namespace,
+    ) -> Result<HashMap<String, String>> {
+        self.namespaces
+            .read()
+            .map_err(|err| anyhow!(err.to_string()))?
+            .get(namespace)
+            .cloned()
+            .ok_or_else(|| anyhow!("Namespace {} doesn't exist in the catalog.", namespace))
+    }
+    async fn update_namespace_properties(
+        &self,
+        namespace: &Namespace,
+        removals: &[String],
+        updates: HashMap<String, String>,
+    ) -> Result<()> {
+        let mut namespaces = self
+            .namespaces
+            .write()
+            .map_err(|err| anyhow!(err.to_string()))?;
+        let properties = namespaces
+            .get_mut(namespace)
+            .ok_or_else(|| anyhow!("Namespace {} doesn't exist in the catalog.", namespace))?;
+        for key in removals {
+            properties.remove(key);
+        }
+        properties.extend(updates);
+        Ok(())
+    }
+    async fn table_exists(&self, identifier: &Identifier) -> Result<bool> {
+        Ok(self
+            .tables
+            .read()
+            .map_err(|err| anyhow!(err.to_string()))?
+            .contains_key(identifier))
+    }
+    async fn drop_table(&self, identifier: &Identifier) -> Result<()> {
+        let metadata_location = self
+            .tables
+            .write()
+            .map_err(|err| anyhow!(err.to_string()))?
+            .remove(identifier)
+            .ok_or_else(|| anyhow!("Table {} doesn't exist in the catalog.", identifier))?;
+        delete_relation_files(self.object_store(), &metadata_location).await
+    }
+    async fn rename_table(&self, from: &Identifier, to: &Identifier) -> Result<()> {
+        {
+            let mut tables = self
+                .tables
+                .write()
+                .map_err(|err| anyhow!(err.to_string()))?;
+            if tables.contains_key(to) {
+                return Err(anyhow!("Table {} already exists in the catalog.", to));
+            }
+            let metadata_location = tables
+                .remove(from)
+                .ok_or_else(|| anyhow!("Table {} doesn't exist in the catalog.", from))?;
+            tables.insert(to.clone(), metadata_location);
+        }
+        if !to.namespace().is_empty() {
+            self.namespaces
+                .write()
+                .map_err(|err| anyhow!(err.to_string()))?
+                .entry(to.namespace().clone())
+                .or_default();
+        }
+        Ok(())
+    }
+    async fn load_table(self: Arc<Self>, identifier: &Identifier) -> Result<Relation> {
+        let metadata_location = self
+            .tables
+            .read()
+            .map_err(|err| anyhow!(err.to_string()))?
+            .get(identifier)
+            .cloned()
+            .ok_or_else(|| anyhow!("Table {} doesn't exist in the catalog.", identifier))?;
+        Relation::try_from_metadata_location(identifier.clone(), self, &metadata_location).await
+    }
+    async fn invalidate_table(&self, _identifier: &Identifier) -> Result<()> {
+        Ok(())
+    }
+    async fn register_table(
+        self: Arc<Self>,
+        identifier: Identifier,
+        metadata_file_location: &str,
+    ) -> Result<Relation> {
+        if self.table_exists(&identifier).await? {
+            return Err(anyhow!(
+                "Table {} already exists in the catalog.",
+                identifier
+            ));
+        }
+        let relation = Relation::try_from_metadata_location(
+            identifier.clone(),
+            self.clone(),
+            metadata_file_location,
+        )
+        .await?;
+        {
+            let mut tables = self
+                .tables
+                .write()
+                .map_err(|err| anyhow!(err.to_string()))?;
+            if tables.contains_key(&identifier) {
+                return Err(anyhow!(
+                    "Table {} already exists in the catalog.",
+                    identifier
+                ));
+            }
+            tables.insert(identifier.clone(), metadata_file_location.to_string());
+        }
+        if !identifier.namespace().is_empty() {
+            self.namespaces
+                .write()
+                .map_err(|err| anyhow!(err.to_string()))?
+                .entry(identifier.namespace().clone())
+                .or_default();
+        }
+        Ok(relation)
+    }
+    async fn update_table(
+        self: Arc<Self>,
+        identifier: Identifier,
+        metadata_file_location: &str,
+        previous_metadata_file_location: &str,
+    ) -> Result<Relation> {
+        let relation = Relation::try_from_metadata_location(
+            identifier.clone(),
+            self.clone(),
+            metadata_file_location,
+        )
+        .await?;
+        let mut tables = self
+            .tables
+            .write()
+            .map_err(|err| anyhow!(err.to_string()))?;
+        let current_metadata_file_location = tables
+            .get_mut(&identifier)
+            .ok_or_else(|| anyhow!("Table {} doesn't exist in the catalog.", identifier))?;
+        if current_metadata_file_location != previous_metadata_file_location {
+            return Err(CommitConflict(format!(
+                "Failed to update table {}: the metadata location {} is not current anymore.",
+                identifier, previous_metadata_file_location
+            ))
+            .into());
+        }
+        *current_metadata_file_location = metadata_file_location.to_string();
+        Ok(relation)
+    }
+    async fn update_tables(self: Arc<Self>, commits: Vec<TableCommit>) -> Result<Vec<Relation>> {
+        let mut relations = Vec::with_capacity(commits.len());
+        for commit in &commits {
+            relations.push(
+                Relation::try_from_metadata_location(
+                    commit.identifier.clone(),
+                    self.clone(),
+                    &commit.metadata_file_location,
+                )
+                .await?,
+            );
+        }
+        // All tables are checked before any of them is changed while holding the lock
+        let mut tables = self
+            .tables
+            .write()
+            .map_err(|err| anyhow!(err.to_string()))?;
+        for commit in &commits {
+            let current_metadata_file_location =
+                tables.get(&commit.identifier).ok_or_else(|| {
+                    anyhow!("Table {} doesn't exist in the catalog.", commit.identifier)
+                })?;
+            if current_metadata_file_location != &commit.previous_metadata_file_location {
+                return Err(CommitConflict(format!(
+                    "Failed to update table {}: the metadata location {} is not current anymore.",
+                    commit.identifier, commit.previous_metadata_file_location
+                ))
+                .into());
+            }
+        }
+        for commit in commits {
+            tables.insert(commit.identifier, commit.metadata_file_location);
+        }
+        Ok(relations)
+    }
+    fn supports_multi_table_updates(&self) -> bool {
+        true
+    }
+    async fn initialize(self: Arc<Self>, _properties: &HashMap<String, String>) -> Result<()> {
+        Ok(())
+    }
+    fn object_store(&self) -> Arc<dyn ObjectStore> {
+        Arc::clone(&self.object_store)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::{collections::HashMap, sync::Arc};
+
+    use object_store::{memory::InMemory, ObjectStore};
+
+    use crate::{
+        catalog::{
+            identifier::Identifier, memory::MemoryCatalog, namespace::Namespace,
+            relation::Relation, Catalog,
+        },
+        table::table_builder::TableBuilder,
+    };
+
+    use crate::catalog::tests::{check_namespace_properties, check_rename_table, schema};
+
+    #[tokio::test]
+    async fn test_register_and_load_table() {
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let catalog: Arc<dyn Catalog> = Arc::new(MemoryCatalog::new(object_store));
+        let identifier = Identifier::parse("load.test.table1").unwrap();
+        let table = TableBuilder::new_metastore_table(
+            "test/",
+            schema(),
+            identifier.clone(),
+            Arc::clone(&catalog),
+        )
+        .unwrap()
+        .commit()
+        .await
+        .unwrap();
+
+        assert!(catalog.table_exists(&identifier).await.unwrap());
+        assert_eq!(
+            catalog
+                .list_tables(identifier.namespace())
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+        let relation = catalog.clone().load_table(&identifier).await.unwrap();
+        assert!(matches!(relation, Relation::Table(_)));
+        assert_eq!(relation.metadata_location(), table.metadata_location());
+        assert_eq!(
+            catalog.list_namespaces(None).await.unwrap(),
+            vec![Namespace::try_new(&["load".to_string()]).unwrap()]
+        );
+        assert_eq!(
+            catalog.list_namespaces(Some("load")).await.unwrap(),
+            vec![Namespace::try_new(&["load".to_string(), "test".to_string()]).unwrap()]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_update_table() {
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let catalog: Arc<dyn Catalog> = Arc::new(MemoryCatalog::new(object_store));
+        let identifier = Identifier::parse("update.table1").unwrap();
+        let mut table = TableBuilder::new_metastore_table(
+            "test/",
+            schema(),
+            identifier.clone(),
+            Arc::clone(&catalog),
+        )
+        .unwrap()
+        .commit()
+        .await
+        .unwrap();
+        let previous_metadata_location = table.metadata_location().to_string();
+
+        table.new_transaction().commit().await.unwrap();
+        assert_ne!(table.metadata_location(), previous_metadata_location);
+        assert_eq!(
+            catalog
+                .clone()
+                .load_table(&identifier)
+                .await
+                .unwrap()
+                .metadata_location(),
+            table.metadata_location()
+        );
+
+        // Swapping the pointer based on an outdated metadata location has to fail
+        assert!(catalog
+            .clone()
+            .update_table(
+                identifier.clone(),
+                &previous_metadata_location,
+                &previous_metadata_location
+            )
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_drop_table() {
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let catalog: Arc<dyn Catalog> = Arc::new(MemoryCatalog::new(object_store));
+        let identifier = Identifier::parse("drop.table1").unwrap();
+        TableBuilder::new_metastore_table(
+            "test/",
+            schema(),
+            identifier.clone(),
+            Arc::clone(&catalog),
+        )
+        .unwrap()
+        .commit()
+        .await
+        .unwrap();
+
+        catalog.drop_table(&identifier).await.unwrap();
+        assert!(!catalog.table_exists(&identifier).await.unwrap());
+        assert!(catalog.clone().load_table(&identifier).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_drop_table_with_shared_location() {
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let catalog: Arc<dyn Catalog> = Arc::new(MemoryCatalog::new(object_store.clone()));
+        catalog
+            .create_namespace(
+                &Namespace::try_new(&["drop".to_string()]).unwrap(),
+                HashMap::new(),
+            )
+            .await
+            .unwrap();
+        let mut tables = Vec::new();
+        for name in ["table1", "table2"] {
+            let mut table = catalog
+                .clone()
+                .create_table(
+                    Identifier::parse(&("drop.".to_string() + name)).unwrap(),
+                    schema(),
+                    None,
+                    None,
+                    Some("test/shared"),
+                    HashMap::new(),
+                )
+                .await
+                .unwrap();
+            let data_file = "test/shared/data/".to_string() + name + ".parquet";
+            object_store
+                .put(&data_file.as_str().into(), "data".into())
+                .await
+                .unwrap();
+            table
+                .new_transaction()
+                .fast_append(vec![data_file])
+                .commit()
+                .await
+                .unwrap();
+            tables.push(table);
+        }
+
+        // Only the files of the dropped table are deleted
+        catalog
+            .drop_table(&Identifier::parse("drop.table1").unwrap())
+            .await
+            .unwrap();
+        assert!(object_store
+            .head(&tables[0].metadata_location().into())
+            .await
+            .is_err());
+        assert!(object_store
+            .head(&"test/shared/data/table1.parquet".into())
+            .await
+            .is_err());
+        assert!(object_store
+            .head(&"test/shared/data/table2.parquet".into())
+            .await
+            .is_ok());
+        match catalog
+            .clone()
+            .load_table(&Identifier::parse("drop.table2").unwrap())
+            .await
+            .unwrap()
+        {
+            Relation::Table(table) => assert_eq!(
+                table.files(None).await.unwrap()[0].file_path(),
+                "test/shared/data/table2.parquet"
+            ),
+            Relation::View(_) => panic!("Relation is not a table."),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_rename_table() {
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let catalog: Arc<dyn Catalog> = Arc::new(MemoryCatalog::new(object_store));
+        check_rename_table(catalog).await;
+    }
+
+    #[tokio::test]
+    async fn test_namespace_properties() {
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let catalog: Arc<dyn Catalog> = Arc::new(MemoryCatalog::new(object_store));
+        check_namespace_properties(catalog).await;
+    }
+
+    #[tokio::test]
+    async fn test_create_table_and_view() {
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let catalog: Arc<dyn Catalog> = Arc::new(MemoryCatalog::new(object_store));
+        let namespace = Namespace::try_new(&["create".to_string()]).unwrap();
+        catalog
+            .create_namespace(
+                &namespace,
+                HashMap::from_iter(vec![("location".to_string(), "test/create/".to_string())]),
+            )
+            .await
+            .unwrap();
+
+        let table_identifier = Identifier::parse("create.table1").unwrap();
+        let table = catalog
+            .clone()
+            .create_table(
+                table_identifier.clone(),
+                schema(),
+                None,
+                None,
+                None,
+                HashMap::from_iter(vec![("owner".to_string(), "me".to_string())]),
+            )
+            .await
+            .unwrap();
+        assert_eq!(table.metadata().location(), "test/create/table1");
+        assert!(catalog.table_exists(&table_identifier).await.unwrap());
+
+        let view_identifier = Identifier::parse("create.view1").unwrap();
+        let view = catalog
+            .clone()
+            .create_view(
+                view_identifier.clone(),
+                "SELECT one FROM create.table1",
+                schema(),
+                None,
+                HashMap::new(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(view.metadata().location(), "test/create/view1");
+
+        // Tables can't be dropped as views
+        assert!(catalog.clone().drop_view(&table_identifier).await.is_err());
+        catalog.clone().drop_view(&view_identifier).await.unwrap();
+        assert!(!catalog.table_exists(&view_identifier).await.unwrap());
+    }
+}
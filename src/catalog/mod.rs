@@ -3,20 +3,31 @@ Defines traits to communicate with an iceberg catalog.
 */
 
 use std::collections::HashMap;
+use std::fmt;
 use std::sync::Arc;
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 
+pub mod caching;
+pub mod filesystem;
 pub mod identifier;
+pub mod memory;
 pub mod namespace;
+pub mod sql;
 
 use identifier::Identifier;
 use object_store::ObjectStore;
 
 use self::namespace::Namespace;
-use self::relation::Relation;
+use self::relation::{write_metadata, Relation};
+use crate::model::{partition::PartitionSpec, schema::SchemaV2, sort::SortOrder};
+use crate::table::{table_builder::new_table_metadata, Table};
+use crate::util;
+use crate::view::{view_builder::new_view_metadata, View};
 
 pub mod relation;
+pub mod rest;
+pub mod transaction;
 
 /// Trait to create, replace and drop tables in an iceberg catalog.
 #[async_trait::async_trait]
@@ -25,11 +36,94 @@ pub trait Catalog: Send + Sync {
     async fn list_tables(&self, namespace: &Namespace) -> Result<Vec<Identifier>>;
     /// Lists all namespaces in the catalog.
     async fn list_namespaces(&self, parent: Option<&str>) -> Result<Vec<Namespace>>;
-    /// Create a table from an identifier and a schema
+    /// Create a namespace with the given properties.
+    async fn create_namespace(
+        &self,
+        namespace: &Namespace,
+        properties: HashMap<String, String>,
+    ) -> Result<()>;
+    /// Drop a namespace. Fails if the namespace still contains tables or namespaces.
+    async fn drop_namespace(&self, namespace: &Namespace) -> Result<()>;
+    /// Load the properties of a namespace.
+    async fn load_namespace_properties(
+        &self,
+        namespace: &Namespace,
+    ) -> Result<HashMap<String, String>>;
+    /// Remove the properties with the given keys from a namespace and set the updated properties.
+    async fn update_namespace_properties(
+        &self,
+        namespace: &Namespace,
+        removals: &[String],
+        updates: HashMap<String, String>,
+    ) -> Result<()>;
     /// Check if a table exists
     async fn table_exists(&self, identifier: &Identifier) -> Result<bool>;
-    /// Drop a table and delete all data and metadata files.
+    /// Drop a table and delete the data and metadata files that are referenced by its metadata.
     async fn drop_table(&self, identifier: &Identifier) -> Result<()>;
+    /// Rename a table or view. The relation keeps its metadata location. Fails if the target already exists.
+    async fn rename_table(&self, from: &Identifier, to: &Identifier) -> Result<()>;
+    /// Create a table with the given schema, partition spec, sort order and properties. Without a location the table
+    /// is stored below the `location` property of its namespace. Without a partition spec or sort order the table is
+    /// unpartitioned and unsorted.
+    async fn create_table(
+        self: Arc<Self>,
+        identifier: Identifier,
+        schema: SchemaV2,
+        partition_spec: Option<PartitionSpec>,
+        sort_order: Option<SortOrder>,
+        location: Option<&str>,
+        properties: HashMap<String, String>,
+    ) -> Result<Table> {
+        let location = relation_location(self.as_ref(), &identifier, location).await?;
+        let metadata =
+            new_table_metadata(&location, schema, partition_spec, sort_order, properties)?;
+        let metadata_location = write_metadata(
+            self.object_store(),
+            &location,
+            metadata.last_sequence_number,
+            &metadata,
+        )
+        .await?;
+        match register_metadata(self, identifier, &metadata_location).await? {
+            Relation::Table(table) => Ok(table),
+            Relation::View(_) => Err(anyhow!(
+                "Creating the table failed because registering the table in the catalog didn't return a table."
+            )),
+        }
+    }
+    /// Create a view for the sql query with the given schema and properties. Without a location the view is stored
+    /// below the `location` property of its namespace.
+    async fn create_view(
+        self: Arc<Self>,
+        identifier: Identifier,
+        sql: &str,
+        schema: SchemaV2,
+        location: Option<&str>,
+        properties: HashMap<String, String>,
+    ) -> Result<View> {
+        let location = relation_location(self.as_ref(), &identifier, location).await?;
+        let metadata = new_view_metadata(sql, &location, schema, properties)?;
+        let metadata_location = write_metadata(
+            self.object_store(),
+            &location,
+            metadata.current_version_id,
+            &metadata,
+        )
+        .await?;
+        match register_metadata(self, identifier, &metadata_location).await? {
+            Relation::View(view) => Ok(view),
+            Relation::Table(_) => Err(anyhow!(
+                "Creating the view failed because registering the view in the catalog didn't return a view."
+            )),
+        }
+    }
+    /// Drop a view and delete its metadata files. Fails if the identifier refers to a table.
+    async fn drop_view(self: Arc<Self>, identifier: &Identifier) -> Result<()> {
+        match self.clone().load_table(identifier).await? {
+            Relation::View(_) => self.drop_table(identifier).await,
+            Relation::Table(_) => Err(anyhow!("{} is a table and not a view.", identifier)),
+        }
+    }
     /// Load a table.
     async fn load_table(self: Arc<Self>, identifier: &Identifier) -> Result<Relation>;
     /// Invalidate cached table metadata from current catalog.
@@ -47,6 +141,27 @@ pub trait Catalog: Send + Sync {
         metadata_file_location: &str,
         previous_metadata_file_location: &str,
     ) -> Result<Relation>;
+    /// Update several tables in one atomic step, so that either all metadata pointers are changed or none. Catalogs
+    /// that can't change several pointers at once only support updating a single table.
+    async fn update_tables(self: Arc<Self>, commits: Vec<TableCommit>) -> Result<Vec<Relation>> {
+        match commits.as_slice() {
+            [commit] => Ok(vec![
+                self.update_table(
+                    commit.identifier.clone(),
+                    &commit.metadata_file_location,
+                    &commit.previous_metadata_file_location,
+                )
+                .await?,
+            ]),
+            _ => Err(anyhow!(
+                "The catalog doesn't support atomic updates of multiple tables."
+            )),
+        }
+    }
+    /// Whether [Catalog::update_tables] can update several tables in one atomic step.
+    fn supports_multi_table_updates(&self) -> bool {
+        false
+    }
     /// Initialize a catalog given a custom name and a map of catalog properties.
     /// A custom Catalog implementation must have a no-arg constructor. A compute engine like Spark
     /// or Flink will first initialize the catalog without any arguments, and then call this method to
@@ -55,3 +170,357 @@ pub trait Catalog: Send + Sync {
     /// Return the associated object store to the catalog
     fn object_store(&self) -> Arc<dyn ObjectStore>;
 }
+
+/// Change of the metadata pointer of a table that is committed together with other tables in
+/// [Catalog::update_tables].
+#[derive(Debug, Clone)]
+pub struct TableCommit {
+    /// Identifier of the table
+    pub identifier: Identifier,
+    /// Location of the new metadata file
+    pub metadata_file_location: String,
+    /// Location of the metadata file the new metadata is based on
+    pub previous_metadata_file_location: String,
+}
+
+/// Error of a commit whose atomic swap of the metadata pointer was rejected, because the table was changed by a
+/// concurrent commit. Such a commit can be retried on the refreshed table.
+#[derive(Debug)]
+pub struct CommitConflict(pub String);
+
+impl fmt::Display for CommitConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for CommitConflict {}
+
+/// Error of a commit that the catalog applied, but whose new table state couldn't be loaded afterwards. The files written
+/// for the commit are referenced by the tables and must not be deleted, the tables have to be loaded again instead.
+#[derive(Debug)]
+pub struct CommitReloadFailed(pub String);
+
+impl fmt::Display for CommitReloadFailed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for CommitReloadFailed {}
+
+/// Get the location of a new relation. Defaults to a directory named after the relation below the `location` property
+/// of its namespace.
+async fn relation_location<C: Catalog + ?Sized>(
+    catalog: &C,
+    identifier: &Identifier,
+    location: Option<&str>,
+) -> Result<String> {
+    match location {
+        Some(location) => Ok(location.to_string()),
+        None => catalog
+            .load_namespace_properties(identifier.namespace())
+            .await?
+            .get("location")
+            .map(|location| location.trim_end_matches('/').to_string() + "/" + identifier.name())
+            .ok_or_else(|| {
+                anyhow!(
+                    "Failed to create {}: no location was given and namespace {} has no location property.",
+                    identifier,
+                    identifier.namespace()
+                )
+            }),
+    }
+}
+
+/// Register the metadata file of a new relation with the catalog. The metadata file is deleted if the registration
+/// fails, so that it doesn't remain in the location of the relation.
+async fn register_metadata<C: Catalog + ?Sized>(
+    catalog: Arc<C>,
+    identifier: Identifier,
+    metadata_location: &str,
+) -> Result<Relation> {
+    let object_store = catalog.object_store();
+    match catalog.register_table(identifier, metadata_location).await {
+        Err(err) => {
+            object_store
+                .delete(&util::strip_prefix(metadata_location).into())
+                .await
+                .ok();
+            Err(err)
+        }
+        relation => relation,
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+
+    use std::{collections::HashMap, fmt, ops::Range, sync::Arc};
+
+    use bytes::Bytes;
+    use futures::stream::BoxStream;
+    use object_store::{
+        memory::InMemory, path::Path, GetResult, ListResult, MultipartId, ObjectMeta, ObjectStore,
+    };
+    use tokio::io::AsyncWrite;
+
+    use crate::{
+        catalog::{identifier::Identifier, namespace::Namespace, relation::Relation, Catalog},
+        model::schema::{AllType, PrimitiveType, SchemaStruct, SchemaV2, StructField},
+        table::table_builder::TableBuilder,
+    };
+
+    /// Object store that fails to delete files.
+    #[derive(Debug)]
+    pub(crate) struct UndeletableStore(pub(crate) InMemory);
+
+    impl fmt::Display for UndeletableStore {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "UndeletableStore({})", self.0)
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl ObjectStore for UndeletableStore {
+        async fn put(&self, location: &Path, bytes: Bytes) -> object_store::Result<()> {
+            self.0.put(location, bytes).await
+        }
+        async fn put_multipart(
+            &self,
+            location: &Path,
+        ) -> object_store::Result<(MultipartId, Box<dyn AsyncWrite + Unpin + Send>)> {
+            self.0.put_multipart(location).await
+        }
+        async fn abort_multipart(
+            &self,
+            location: &Path,
+            multipart_id: &MultipartId,
+        ) -> object_store::Result<()> {
+            self.0.abort_multipart(location, multipart_id).await
+        }
+        async fn get(&self, location: &Path) -> object_store::Result<GetResult> {
+            self.0.get(location).await
+        }
+        async fn get_range(
+            &self,
+            location: &Path,
+            range: Range<usize>,
+        ) -> object_store::Result<Bytes> {
+            self.0.get_range(location, range).await
+        }
+        async fn head(&self, location: &Path) -> object_store::Result<ObjectMeta> {
+            self.0.head(location).await
+        }
+        async fn delete(&self, _location: &Path) -> object_store::Result<()> {
+            Err(object_store::Error::NotImplemented)
+        }
+        async fn list(
+            &self,
+            prefix: Option<&Path>,
+        ) -> object_store::Result<BoxStream<'_, object_store::Result<ObjectMeta>>> {
+            self.0.list(prefix).await
+        }
+        async fn list_with_delimiter(
+            &self,
+            prefix: Option<&Path>,
+        ) -> object_store::Result<ListResult> {
+            self.0.list_with_delimiter(prefix).await
+        }
+        async fn copy(&self, from: &Path, to: &Path) -> object_store::Result<()> {
+            self.0.copy(from, to).await
+        }
+        async fn copy_if_not_exists(&self, from: &Path, to: &Path) -> object_store::Result<()> {
+            self.0.copy_if_not_exists(from, to).await
+        }
+    }
+
+    /// Schema of the relations that are created in the catalog tests.
+    pub(crate) fn schema() -> SchemaV2 {
+        SchemaV2 {
+            schema_id: 1,
+            identifier_field_ids: Some(vec![1, 2]),
+            name_mapping: None,
+            struct_fields: SchemaStruct {
+                fields: vec![
+                    StructField {
+                        id: 1,
+                        name: "one".to_string(),
+                        required: false,
+                        field_type: AllType::Primitive(PrimitiveType::String),
+                        doc: None,
+                    },
+                    StructField {
+                        id: 2,
+                        name: "two".to_string(),
+                        required: false,
+                        field_type: AllType::Primitive(PrimitiveType::String),
+                        doc: None,
+                    },
+                ],
+            },
+        }
+    }
+
+    /// Check the lifecycle and the properties of a namespace in the catalog.
+    pub(crate) async fn check_namespace_properties(catalog: Arc<dyn Catalog>) {
+        let namespace = Namespace::try_new(&["namespace".to_string()]).unwrap();
+        catalog
+            .create_namespace(
+                &namespace,
+                HashMap::from_iter(vec![
+                    ("owner".to_string(), "me".to_string()),
+                    ("comment".to_string(), "test".to_string()),
+                ]),
+            )
+            .await
+            .unwrap();
+        assert!(catalog
+            .create_namespace(&namespace, HashMap::new())
+            .await
+            .is_err());
+        assert_eq!(
+            catalog.list_namespaces(None).await.unwrap(),
+            vec![namespace.clone()]
+        );
+
+        catalog
+            .update_namespace_properties(
+                &namespace,
+                &["comment".to_string()],
+                HashMap::from_iter(vec![("location".to_string(), "test/".to_string())]),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            catalog.load_namespace_properties(&namespace).await.unwrap(),
+            HashMap::from_iter(vec![
+                ("owner".to_string(), "me".to_string()),
+                ("location".to_string(), "test/".to_string()),
+            ])
+        );
+
+        let identifier = Identifier::parse("namespace.table1").unwrap();
+        TableBuilder::new_metastore_table(
+            "test/",
+            schema(),
+            identifier.clone(),
+            Arc::clone(&catalog),
+        )
+        .unwrap()
+        .commit()
+        .await
+        .unwrap();
+        // Namespaces that contain tables can't be dropped
+        assert!(catalog.drop_namespace(&namespace).await.is_err());
+        catalog.drop_table(&identifier).await.unwrap();
+        catalog.drop_namespace(&namespace).await.unwrap();
+        assert!(catalog.load_namespace_properties(&namespace).await.is_err());
+    }
+
+    /// Check renaming tables and views in the catalog.
+    pub(crate) async fn check_rename_table(catalog: Arc<dyn Catalog>) {
+        for name in ["rename", "renamed"] {
+            catalog
+                .create_namespace(
+                    &Namespace::try_new(&[name.to_string()]).unwrap(),
+                    HashMap::from_iter(vec![("location".to_string(), format!("test/{}", name))]),
+                )
+                .await
+                .unwrap();
+        }
+        let create_table = |name: &str| {
+            catalog.clone().create_table(
+                Identifier::parse(name).unwrap(),
+                schema(),
+                None,
+                None,
+                None,
+                HashMap::new(),
+            )
+        };
+        let table = create_table("rename.table1").await.unwrap();
+        let existing = create_table("renamed.existing").await.unwrap();
+        let view = catalog
+            .clone()
+            .create_view(
+                Identifier::parse("rename.view1").unwrap(),
+                "SELECT one FROM rename.table1",
+                schema(),
+                None,
+                HashMap::new(),
+            )
+            .await
+            .unwrap();
+
+        // Tables and views keep their location
+        let table_identifier = Identifier::parse("renamed.table2").unwrap();
+        let view_identifier = Identifier::parse("renamed.view2").unwrap();
+        catalog
+            .rename_table(
+                &Identifier::parse("rename.table1").unwrap(),
+                &table_identifier,
+            )
+            .await
+            .unwrap();
+        catalog
+            .rename_table(
+                &Identifier::parse("rename.view1").unwrap(),
+                &view_identifier,
+            )
+            .await
+            .unwrap();
+        let rename = Namespace::try_new(&["rename".to_string()]).unwrap();
+        assert!(catalog.list_tables(&rename).await.unwrap().is_empty());
+        assert!(catalog
+            .list_namespaces(Some("rename"))
+            .await
+            .unwrap()
+            .is_empty());
+        match catalog.clone().load_table(&table_identifier).await.unwrap() {
+            Relation::Table(mut renamed) => {
+                assert_eq!(renamed.metadata().location(), table.metadata().location());
+                // Renamed tables can be committed to
+                renamed.new_transaction().commit().await.unwrap();
+            }
+            Relation::View(_) => panic!("Relation is not a table."),
+        }
+        match catalog.clone().load_table(&view_identifier).await.unwrap() {
+            Relation::View(renamed) => {
+                assert_eq!(renamed.metadata().location(), view.metadata().location())
+            }
+            Relation::Table(_) => panic!("Relation is not a view."),
+        }
+
+        // Renaming onto an existing relation or renaming a missing relation fails
+        let existing_identifier = Identifier::parse("renamed.existing").unwrap();
+        assert!(catalog
+            .rename_table(&table_identifier, &existing_identifier)
+            .await
+            .is_err());
+        assert!(catalog
+            .rename_table(&view_identifier, &existing_identifier)
+            .await
+            .is_err());
+        assert!(catalog
+            .rename_table(
+                &Identifier::parse("rename.table1").unwrap(),
+                &Identifier::parse("renamed.table3").unwrap()
+            )
+            .await
+            .is_err());
+        assert!(catalog.table_exists(&table_identifier).await.unwrap());
+        assert_eq!(
+            catalog
+                .clone()
+                .load_table(&existing_identifier)
+                .await
+                .unwrap()
+                .metadata_location(),
+            existing.metadata_location()
+        );
+
+        catalog.drop_table(&table_identifier).await.unwrap();
+        assert!(!catalog.table_exists(&table_identifier).await.unwrap());
+    }
+}
@@ -10,7 +10,7 @@ use crate::catalog::identifier::SEPARATOR;
 use anyhow::{anyhow, Result};
 
 /// Namespace struct for iceberg catalogs
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct Namespace {
     levels: Vec<String>,
 }
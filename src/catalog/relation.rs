@@ -2,13 +2,21 @@
  * Provides the [Relation] enum to refer to any queriable entity like a table or a view
 */
 
+use std::{collections::HashSet, sync::Arc};
+
+use anyhow::{anyhow, Result};
+use object_store::{path::Path, ObjectStore};
 use serde::{self, Deserialize, Serialize};
+use uuid::Uuid;
 
+use crate::catalog::{identifier::Identifier, Catalog};
 use crate::model::table_metadata::TableMetadata;
 use crate::model::view_metadata::ViewMetadata;
-use crate::table::Table;
+use crate::table::{files::read_manifest, read_manifest_list, Table};
+use crate::util;
 use crate::view::View;
 /// Enum for different types that can be queried like a table, for example view
+#[derive(Clone)]
 pub enum Relation {
     /// An iceberg table
     Table(Table),
@@ -17,6 +25,31 @@ pub enum Relation {
 }
 
 impl Relation {
+    /// Load the relation whose metadata file is stored at the given location and associate it with the catalog.
+    /// Catalog implementations use this to turn a metadata pointer into a [Table] or [View].
+    pub async fn try_from_metadata_location(
+        identifier: Identifier,
+        catalog: Arc<dyn Catalog>,
+        metadata_location: &str,
+    ) -> Result<Self> {
+        let metadata = read_relation_metadata(&catalog.object_store(), metadata_location).await?;
+        match metadata {
+            RelationMetadata::Table(metadata) => Ok(Relation::Table(
+                Table::new_metastore_table(identifier, catalog, metadata, metadata_location)
+                    .await?,
+            )),
+            RelationMetadata::View(metadata) => Ok(Relation::View(
+                View::new_metastore_view(identifier, catalog, metadata, metadata_location).await?,
+            )),
+        }
+    }
+    /// Associate the relation with another catalog.
+    pub(crate) fn set_catalog(&mut self, catalog: Arc<dyn Catalog>) {
+        match self {
+            Relation::Table(table) => table.set_catalog(catalog),
+            Relation::View(view) => view.set_catalog(catalog),
+        }
+    }
     /// Return metadata location for relation.
     pub fn metadata_location(&self) -> &str {
         match self {
@@ -35,3 +68,93 @@ pub enum RelationMetadata {
     /// View metadata
     View(ViewMetadata),
 }
+
+/// Read the metadata file at the given location from the object store.
+pub(crate) async fn read_relation_metadata(
+    object_store: &Arc<dyn ObjectStore>,
+    metadata_location: &str,
+) -> Result<RelationMetadata> {
+    let bytes = &object_store
+        .get(&util::strip_prefix(metadata_location).into())
+        .await
+        .map_err(|err| anyhow!(err.to_string()))?
+        .bytes()
+        .await
+        .map_err(|err| anyhow!(err.to_string()))?;
+    serde_json::from_str(std::str::from_utf8(bytes).map_err(|err| anyhow!(err.to_string()))?)
+        .map_err(|err| anyhow!(err.to_string()))
+}
+
+/// Delete the files of the relation whose metadata file is stored at the given location. Only the files that are
+/// referenced by the metadata are deleted: the data and delete files of the manifests, the manifests, the manifest
+/// lists of the snapshots and the metadata files of the metadata log. Other files below the location of the relation
+/// are kept, because the location might be shared with other relations.
+pub(crate) async fn delete_relation_files(
+    object_store: Arc<dyn ObjectStore>,
+    metadata_location: &str,
+) -> Result<()> {
+    let mut paths = Vec::new();
+    if let RelationMetadata::Table(metadata) =
+        read_relation_metadata(&object_store, metadata_location).await?
+    {
+        let format_version = metadata.format_version();
+        let mut manifests = HashSet::new();
+        let mut files = HashSet::new();
+        for manifest_list in metadata.manifest_lists() {
+            for manifest in
+                read_manifest_list(manifest_list, format_version.clone(), object_store.clone())
+                    .await?
+            {
+                if manifests.insert(manifest.manifest_path().to_string()) {
+                    for entry in
+                        read_manifest(object_store.clone(), &manifest, format_version.clone())
+                            .await?
+                    {
+                        files.insert(entry.file_path().to_string());
+                    }
+                }
+            }
+        }
+        paths.extend(files);
+        paths.extend(manifests);
+        paths.extend(metadata.manifest_lists().into_iter().map(str::to_string));
+        paths.extend(
+            metadata
+                .metadata_log()
+                .iter()
+                .map(|log| log.metadata_file.clone()),
+        );
+    }
+    paths.push(metadata_location.to_string());
+    for path in paths {
+        match object_store.delete(&util::strip_prefix(&path).into()).await {
+            // Files can already be deleted, for example data files of expired snapshots
+            Ok(()) | Err(object_store::Error::NotFound { .. }) => (),
+            Err(err) => return Err(anyhow!(err.to_string())),
+        }
+    }
+    Ok(())
+}
+
+/// Write a new metadata file below the location of a relation and return its path. The file name contains a random
+/// uuid so that concurrent writers don't overwrite each other.
+pub(crate) async fn write_metadata<T: Serialize>(
+    object_store: Arc<dyn ObjectStore>,
+    location: &str,
+    version: i64,
+    metadata: &T,
+) -> Result<String> {
+    let metadata_json = serde_json::to_string(metadata).map_err(|err| anyhow!(err.to_string()))?;
+    let path: Path = (location.to_string()
+        + "/metadata/"
+        + &version.to_string()
+        + "-"
+        + &Uuid::new_v4().to_string()
+        + ".metadata.json")
+        .into();
+    object_store
+        .put(&path, metadata_json.into())
+        .await
+        .map_err(|err| anyhow!(err.to_string()))?;
+    Ok(path.to_string())
+}
@@ -0,0 +1,1649 @@
+/*!
+Defining the [RestCatalog] struct, a [Catalog] that communicates with a server implementing the
+[iceberg REST catalog protocol](https://github.com/apache/iceberg/blob/master/open-api/rest-catalog-open-api.yaml).
+
+The catalog is configured through [Catalog::initialize] with the following properties:
+- `uri`: Base uri of the REST catalog server.
+- `warehouse`: Optional warehouse location that is sent to the config endpoint.
+- `credential`: Optional client credentials in the form `client_id:client_secret` that are exchanged for an OAuth2 token.
+- `token`: Optional bearer token that is used when no credentials are provided.
+- `scope`: Optional OAuth2 scope, defaults to `catalog`.
+- `oauth2-server-uri`: Optional OAuth2 token endpoint, defaults to `{uri}/v1/oauth/tokens`.
+
+The properties returned by the config endpoint of the server are merged with these properties. Server defaults have the
+lowest and server overrides the highest precedence.
+
+The catalog only manages tables, views are not supported.
+*/
+
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, Result};
+use object_store::ObjectStore;
+use reqwest::{Client, Method, RequestBuilder, Response, StatusCode};
+use serde::{Deserialize, Serialize};
+use url::Url;
+use uuid::Uuid;
+
+use super::identifier::{Identifier, SEPARATOR};
+use super::namespace::Namespace;
+use super::relation::{read_relation_metadata, Relation, RelationMetadata};
+use super::{Catalog, CommitConflict, CommitReloadFailed, TableCommit};
+use crate::model::{
+    partition::PartitionSpec,
+    schema::SchemaV2,
+    snapshot::SnapshotV2,
+    sort::SortOrder,
+    table_metadata::{TableMetadata, TableMetadataV2},
+};
+use crate::table::Table;
+use crate::util;
+use crate::view::View;
+
+/// Catalog property for the base uri of the REST server.
+pub static URI: &str = "uri";
+/// Catalog property for the warehouse location.
+pub static WAREHOUSE: &str = "warehouse";
+/// Catalog property for the OAuth2 client credentials.
+pub static CREDENTIAL: &str = "credential";
+/// Catalog property for a bearer token.
+pub static TOKEN: &str = "token";
+/// Catalog property for the OAuth2 scope.
+pub static SCOPE: &str = "scope";
+/// Catalog property for the OAuth2 token endpoint.
+pub static OAUTH2_SERVER_URI: &str = "oauth2-server-uri";
+/// Catalog property for the prefix that is inserted into all resource paths.
+pub static PREFIX: &str = "prefix";
+
+/// Separator of namespace levels in resource paths.
+static NAMESPACE_SEPARATOR: &str = "\u{1f}";
+
+/// Catalog that communicates with an iceberg REST catalog server.
+pub struct RestCatalog {
+    object_store: Arc<dyn ObjectStore>,
+    client: Client,
+    config: RwLock<Option<RestConfig>>,
+}
+
+/// Configuration of an initialized REST catalog.
+#[derive(Clone)]
+struct RestConfig {
+    uri: String,
+    properties: HashMap<String, String>,
+    token: Option<Token>,
+}
+
+/// OAuth2 access token
+#[derive(Clone)]
+struct Token {
+    access_token: String,
+    expires_at: Option<Instant>,
+}
+
+impl RestCatalog {
+    /// Create a new REST catalog. The catalog has to be initialized with [Catalog::initialize] before it can be used.
+    pub fn new(object_store: Arc<dyn ObjectStore>) -> Self {
+        RestCatalog {
+            object_store,
+            client: Client::new(),
+            config: RwLock::new(None),
+        }
+    }
+    /// Get the configuration of the catalog. Fails if the catalog is not initialized.
+    fn config(&self) -> Result<RestConfig> {
+        self.config
+            .read()
+            .map_err(|err| anyhow!(err.to_string()))?
+            .clone()
+            .ok_or_else(|| anyhow!("REST catalog is not initialized."))
+    }
+    /// Get the url of a catalog resource.
+    fn url(&self, segments: &[&str]) -> Result<Url> {
+        let config = self.config()?;
+        endpoint(
+            &config.uri,
+            config.properties.get(PREFIX).map(|prefix| prefix.as_str()),
+            segments,
+        )
+    }
+    /// Get the url of a table resource.
+    fn table_url(&self, identifier: &Identifier) -> Result<Url> {
+        self.url(&[
+            "namespaces",
+            &identifier.namespace().levels().join(NAMESPACE_SEPARATOR),
+            "tables",
+            identifier.name(),
+        ])
+    }
+    /// Get a valid access token. Expired tokens are refreshed if client credentials are available.
+    async fn token(&self) -> Result<Option<String>> {
+        let config = self.config()?;
+        match &config.token {
+            Some(token)
+                if token
+                    .expires_at
+                    .map(|expires_at| Instant::now() < expires_at)
+                    .unwrap_or(true) =>
+            {
+                Ok(Some(token.access_token.clone()))
+            }
+            _ if config.properties.contains_key(CREDENTIAL) => {
+                let token = fetch_token(&self.client, &config.uri, &config.properties).await?;
+                let access_token = token.access_token.clone();
+                if let Some(config) = self
+                    .config
+                    .write()
+                    .map_err(|err| anyhow!(err.to_string()))?
+                    .as_mut()
+                {
+                    config.token = Some(token);
+                }
+                Ok(Some(access_token))
+            }
+            _ => Ok(config.token.map(|token| token.access_token)),
+        }
+    }
+    /// Read the previous and the new metadata of a table commit.
+    async fn read_commit_metadata(
+        &self,
+        metadata_file_location: &str,
+        previous_metadata_file_location: &str,
+    ) -> Result<(TableMetadataV2, TableMetadataV2)> {
+        let object_store = self.object_store();
+        let previous =
+            read_relation_metadata(&object_store, previous_metadata_file_location).await?;
+        let new = read_relation_metadata(&object_store, metadata_file_location).await?;
+        match (previous, new) {
+            (
+                RelationMetadata::Table(TableMetadata::V2(previous)),
+                RelationMetadata::Table(TableMetadata::V2(new)),
+            ) => Ok((previous, new)),
+            _ => Err(anyhow!(
+                "The REST catalog only supports commits to tables with format version 2."
+            )),
+        }
+    }
+    /// Delete the metadata file that was written for a successful commit. The server writes its own metadata file,
+    /// so the file was only used to compute the changes. Failing to delete it doesn't affect the commit.
+    async fn delete_staged_metadata(&self, metadata_file_location: &str) {
+        self.object_store
+            .delete(&util::strip_prefix(metadata_file_location).into())
+            .await
+            .ok();
+    }
+    /// Create an authenticated request.
+    async fn request(&self, method: Method, url: Url) -> Result<RequestBuilder> {
+        let request = self.client.request(method, url);
+        match self.token().await? {
+            Some(token) => Ok(request.bearer_auth(token)),
+            None => Ok(request),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Catalog for RestCatalog {
+    async fn list_tables(&self, namespace: &Namespace) -> Result<Vec<Identifier>> {
+        let url = self.url(&[
+            "namespaces",
+            &namespace.levels().join(NAMESPACE_SEPARATOR),
+            "tables",
+        ])?;
+        let response: ListTablesResponse =
+            check_response(self.request(Method::GET, url).await?.send().await?)
+                .await?
+                .json()
+                .await?;
+        response
+            .identifiers
+            .into_iter()
+            .map(|identifier| {
+                Identifier::try_new(&[identifier.namespace, vec![identifier.name]].concat())
+            })
+            .collect()
+    }
+    async fn list_namespaces(&self, parent: Option<&str>) -> Result<Vec<Namespace>> {
+        let mut url = self.url(&["namespaces"])?;
+        if let Some(parent) = parent.filter(|parent| !parent.is_empty()) {
+            url.query_pairs_mut().append_pair(
+                "parent",
+                &parent
+                    .split(SEPARATOR)
+                    .collect::<Vec<&str>>()
+                    .join(NAMESPACE_SEPARATOR),
+            );
+        }
+        let response: ListNamespacesResponse =
+            check_response(self.request(Method::GET, url).await?.send().await?)
+                .await?
+                .json()
+                .await?;
+        response
+            .namespaces
+            .iter()
+            .map(|levels| Namespace::try_new(levels))
+            .collect()
+    }
+    async fn create_namespace(
+        &self,
+        namespace: &Namespace,
+        properties: HashMap<String, String>,
+    ) -> Result<()> {
+        let request = CreateNamespaceRequest {
+            namespace: namespace.levels(),
+            properties,
+        };
+        check_response(
+            self.request(Method::POST, self.url(&["namespaces"])?)
+                .await?
+                .json(&request)
+                .send()
+                .await?,
+        )
+        .await?;
+        Ok(())
+    }
+    async fn drop_namespace(&self, namespace: &Namespace) -> Result<()> {
+        let url = self.url(&["namespaces", &namespace.levels().join(NAMESPACE_SEPARATOR)])?;
+        check_response(self.request(Method::DELETE, url).await?.send().await?).await?;
+        Ok(())
+    }
+    async fn load_namespace_properties(
+        &self,
+        namespace: &Namespace,
+    ) -> Result<HashMap<String, String>> {
+        let url = self.url(&["namespaces", &namespace.levels().join(NAMESPACE_SEPARATOR)])?;
+        let response: LoadNamespaceResponse =
+            check_response(self.request(Method::GET, url).await?.send().await?)
+                .await?
+                .json()
+                .await?;
+        Ok(response.properties.unwrap_or_default())
+    }
+    async fn update_namespace_properties(
+        &self,
+        namespace: &Namespace,
+        removals: &[String],
+        updates: HashMap<String, String>,
+    ) -> Result<()> {
+        let url = self.url(&[
+            "namespaces",
+            &namespace.levels().join(NAMESPACE_SEPARATOR),
+            "properties",
+        ])?;
+        let request = UpdateNamespacePropertiesRequest { removals, updates };
+        check_response(
+            self.request(Method::POST, url)
+                .await?
+                .json(&request)
+                .send()
+                .await?,
+        )
+        .await?;
+        Ok(())
+    }
+    async fn table_exists(&self, identifier: &Identifier) -> Result<bool> {
+        let response = self
+            .request(Method::HEAD, self.table_url(identifier)?)
+            .await?
+            .send()
+            .await?;
+        match response.status() {
+            StatusCode::NOT_FOUND => Ok(false),
+            _ => check_response(response).await.map(|_| true),
+        }
+    }
+    async fn drop_table(&self, identifier: &Identifier) -> Result<()> {
+        let mut url = self.table_url(identifier)?;
+        url.query_pairs_mut().append_pair("purgeRequested", "true");
+        check_response(self.request(Method::DELETE, url).await?.send().await?).await?;
+        Ok(())
+    }
+    async fn rename_table(&self, from: &Identifier, to: &Identifier) -> Result<()> {
+        let request = RenameTableRequest {
+            source: from.into(),
+            destination: to.into(),
+        };
+        check_response(
+            self.request(Method::POST, self.url(&["tables", "rename"])?)
+                .await?
+                .json(&request)
+                .send()
+                .await?,
+        )
+        .await?;
+        Ok(())
+    }
+    async fn create_table(
+        self: Arc<Self>,
+        identifier: Identifier,
+        schema: SchemaV2,
+        partition_spec: Option<PartitionSpec>,
+        sort_order: Option<SortOrder>,
+        location: Option<&str>,
+        properties: HashMap<String, String>,
+    ) -> Result<Table> {
+        // The server owns the creation of tables, including the choice of a location and the first metadata file.
+        let url = self.url(&[
+            "namespaces",
+            &identifier.namespace().levels().join(NAMESPACE_SEPARATOR),
+            "tables",
+        ])?;
+        let request = CreateTableRequest {
+            name: identifier.name(),
+            location,
+            schema,
+            partition_spec,
+            write_order: sort_order,
+            properties,
+        };
+        let response: LoadTableResponse = check_response(
+            self.request(Method::POST, url)
+                .await?
+                .json(&request)
+                .send()
+                .await?,
+        )
+        .await?
+        .json()
+        .await?;
+        let metadata_location = response.metadata_location.ok_or_else(|| {
+            anyhow!(
+                "REST catalog returned no metadata location for table {}.",
+                identifier
+            )
+        })?;
+        Table::new_metastore_table(identifier, self, response.metadata, &metadata_location).await
+    }
+    async fn create_view(
+        self: Arc<Self>,
+        identifier: Identifier,
+        _sql: &str,
+        _schema: SchemaV2,
+        _location: Option<&str>,
+        _properties: HashMap<String, String>,
+    ) -> Result<View> {
+        Err(anyhow!(
+            "Failed to create view {}: the REST catalog doesn't support views.",
+            identifier
+        ))
+    }
+    async fn drop_view(self: Arc<Self>, identifier: &Identifier) -> Result<()> {
+        Err(anyhow!(
+            "Failed to drop view {}: the REST catalog doesn't support views.",
+            identifier
+        ))
+    }
+    async fn load_table(self: Arc<Self>, identifier: &Identifier) -> Result<Relation> {
+        let response: LoadTableResponse = check_response(
+            self.request(Method::GET, self.table_url(identifier)?)
+                .await?
+                .send()
+                .await?,
+        )
+        .await?
+        .json()
+        .await?;
+        let metadata_location = response.metadata_location.ok_or_else(|| {
+            anyhow!(
+                "REST catalog returned no metadata location for table {}.",
+                identifier
+            )
+        })?;
+        Ok(Relation::Table(
+            Table::new_metastore_table(
+                identifier.clone(),
+                self,
+                response.metadata,
+                &metadata_location,
+            )
+            .await?,
+        ))
+    }
+    async fn invalidate_table(&self, _identifier: &Identifier) -> Result<()> {
+        Ok(())
+    }
+    async fn register_table(
+        self: Arc<Self>,
+        identifier: Identifier,
+        metadata_file_location: &str,
+    ) -> Result<Relation> {
+        let url = self.url(&[
+            "namespaces",
+            &identifier.namespace().levels().join(NAMESPACE_SEPARATOR),
+            "register",
+        ])?;
+        let request = RegisterTableRequest {
+            name: identifier.name(),
+            metadata_location: metadata_file_location,
+        };
+        let response: LoadTableResponse = check_response(
+            self.request(Method::POST, url)
+                .await?
+                .json(&request)
+                .send()
+                .await?,
+        )
+        .await?
+        .json()
+        .await?;
+        Ok(Relation::Table(
+            Table::new_metastore_table(
+                identifier,
+                self,
+                response.metadata,
+                response
+                    .metadata_location
+                    .as_deref()
+                    .unwrap_or(metadata_file_location),
+            )
+            .await?,
+        ))
+    }
+    async fn update_table(
+        self: Arc<Self>,
+        identifier: Identifier,
+        metadata_file_location: &str,
+        previous_metadata_file_location: &str,
+    ) -> Result<Relation> {
+        // The REST protocol commits changes instead of metadata files. The changes are derived from the difference
+        // between the previous and the new metadata file and the server checks that the previous state is still current.
+        let (previous, new) = self
+            .read_commit_metadata(metadata_file_location, previous_metadata_file_location)
+            .await?;
+        let updates = table_updates(&previous, &new);
+        let request = CommitTableRequest {
+            identifier: None,
+            requirements: table_requirements(&previous, &updates),
+            updates,
+        };
+        let response: CommitTableResponse = check_commit_response(
+            self.request(Method::POST, self.table_url(&identifier)?)
+                .await?
+                .json(&request)
+                .send()
+                .await?,
+        )
+        .await?
+        .json()
+        .await?;
+        // The server writes its own metadata file, the staged file is not referenced by the table
+        self.delete_staged_metadata(metadata_file_location).await;
+        let table = Table::new_metastore_table(
+            identifier.clone(),
+            self,
+            response.metadata,
+            &response.metadata_location,
+        )
+        .await
+        .map_err(|err| {
+            CommitReloadFailed(format!(
+                "Committed the update of table {}, but failed to load the new table state: {}",
+                identifier, err
+            ))
+        })?;
+        Ok(Relation::Table(table))
+    }
+    async fn update_tables(self: Arc<Self>, commits: Vec<TableCommit>) -> Result<Vec<Relation>> {
+        let mut metadata = Vec::with_capacity(commits.len());
+        for commit in &commits {
+            metadata.push(
+                self.read_commit_metadata(
+                    &commit.metadata_file_location,
+                    &commit.previous_metadata_file_location,
+                )
+                .await?,
+            );
+        }
+        let request = CommitTransactionRequest {
+            table_changes: commits
+                .iter()
+                .zip(metadata.iter())
+                .map(|(commit, (previous, new))| {
+                    let updates = table_updates(previous, new);
+                    CommitTableRequest {
+                        identifier: Some((&commit.identifier).into()),
+                        requirements: table_requirements(previous, &updates),
+                        updates,
+                    }
+                })
+                .collect(),
+        };
+        check_commit_response(
+            self.request(Method::POST, self.url(&["transactions", "commit"])?)
+                .await?
+                .json(&request)
+                .send()
+                .await?,
+        )
+        .await?;
+        for commit in &commits {
+            self.delete_staged_metadata(&commit.metadata_file_location)
+                .await;
+        }
+        // The server doesn't return the new metadata of the tables, so they are loaded again. The commit can't be
+        // undone at this point, a failure is reported as such.
+        let mut relations = Vec::with_capacity(commits.len());
+        for commit in &commits {
+            relations.push(
+                self.clone()
+                    .load_table(&commit.identifier)
+                    .await
+                    .map_err(|err| {
+                        CommitReloadFailed(format!(
+                            "Committed the transaction, but failed to load the new state of table {}: {}",
+                            commit.identifier, err
+                        ))
+                    })?,
+            );
+        }
+        Ok(relations)
+    }
+    fn supports_multi_table_updates(&self) -> bool {
+        true
+    }
+    async fn initialize(self: Arc<Self>, properties: &HashMap<String, String>) -> Result<()> {
+        let uri = properties
+            .get(URI)
+            .ok_or_else(|| anyhow!("REST catalog requires the property {}.", URI))?;
+        let token = match properties.get(TOKEN) {
+            _ if properties.contains_key(CREDENTIAL) => {
+                Some(fetch_token(&self.client, uri, properties).await?)
+            }
+            Some(token) => Some(Token {
+                access_token: token.clone(),
+                expires_at: None,
+            }),
+            None => None,
+        };
+        let mut url = endpoint(uri, None, &["config"])?;
+        if let Some(warehouse) = properties.get(WAREHOUSE) {
+            url.query_pairs_mut().append_pair("warehouse", warehouse);
+        }
+        let mut request = self.client.get(url);
+        if let Some(token) = &token {
+            request = request.bearer_auth(&token.access_token);
+        }
+        let config: ConfigResponse = check_response(request.send().await?).await?.json().await?;
+        let mut merged = config.defaults;
+        merged.extend(properties.clone());
+        merged.extend(config.overrides);
+        let uri = merged.get(URI).cloned().unwrap_or_else(|| uri.to_string());
+        *self
+            .config
+            .write()
+            .map_err(|err| anyhow!(err.to_string()))? = Some(RestConfig {
+            uri,
+            properties: merged,
+            token,
+        });
+        Ok(())
+    }
+    fn object_store(&self) -> Arc<dyn ObjectStore> {
+        Arc::clone(&self.object_store)
+    }
+}
+
+/// Build the url of an endpoint from the base uri, the optional catalog prefix and the path segments.
+fn endpoint(uri: &str, prefix: Option<&str>, segments: &[&str]) -> Result<Url> {
+    let mut url = Url::parse(uri)?;
+    url.path_segments_mut()
+        .map_err(|_| anyhow!("Uri {} can not be used as base for the REST catalog.", uri))?
+        .pop_if_empty()
+        .push("v1")
+        .extend(prefix.into_iter().flat_map(|prefix| prefix.split('/')))
+        .extend(segments);
+    Ok(url)
+}
+
+/// Exchange the client credentials for an OAuth2 access token.
+async fn fetch_token(
+    client: &Client,
+    uri: &str,
+    properties: &HashMap<String, String>,
+) -> Result<Token> {
+    let credential = properties
+        .get(CREDENTIAL)
+        .ok_or_else(|| anyhow!("REST catalog requires the property {}.", CREDENTIAL))?;
+    let (client_id, client_secret) = match credential.split_once(':') {
+        Some((client_id, client_secret)) => (Some(client_id), client_secret),
+        None => (None, credential.as_str()),
+    };
+    let url = match properties.get(OAUTH2_SERVER_URI) {
+        Some(oauth2_server_uri) => Url::parse(oauth2_server_uri)?,
+        None => endpoint(uri, None, &["oauth", "tokens"])?,
+    };
+    let mut form = vec![
+        ("grant_type", "client_credentials"),
+        ("client_secret", client_secret),
+        (
+            "scope",
+            properties
+                .get(SCOPE)
+                .map(|scope| scope.as_str())
+                .unwrap_or("catalog"),
+        ),
+    ];
+    if let Some(client_id) = client_id {
+        form.push(("client_id", client_id));
+    }
+    let response: TokenResponse = check_response(client.post(url).form(&form).send().await?)
+        .await?
+        .json()
+        .await?;
+    Ok(Token {
+        access_token: response.access_token,
+        expires_at: response
+            .expires_in
+            .map(|expires_in| Instant::now() + Duration::from_secs(expires_in)),
+    })
+}
+
+/// Turn unsuccessful responses into errors containing the error message of the server.
+async fn check_response(response: Response) -> Result<Response> {
+    let status = response.status();
+    if status.is_success() {
+        Ok(response)
+    } else {
+        let message = response
+            .json::<ErrorResponse>()
+            .await
+            .map(|response| response.error.message)
+            .unwrap_or_default();
+        Err(anyhow!(
+            "REST catalog request failed with status {}: {}",
+            status,
+            message
+        ))
+    }
+}
+
+/// Check the response of a commit. The server responds with a conflict if the requirements of the commit aren't
+/// fulfilled by the current table state anymore.
+async fn check_commit_response(response: Response) -> Result<Response> {
+    if response.status() == StatusCode::CONFLICT {
+        let message = response
+            .json::<ErrorResponse>()
+            .await
+            .map(|response| response.error.message)
+            .unwrap_or_default();
+        Err(CommitConflict(format!("REST catalog rejected the commit: {}", message)).into())
+    } else {
+        check_response(response).await
+    }
+}
+
+/// Requirements that the current table state on the server has to fulfill for a commit based on the previous metadata.
+/// Besides the identity of the table, only the parts of the table state that the updates change are asserted, so that
+/// concurrent changes of the same parts are rejected by the server.
+fn table_requirements<'a>(
+    previous: &'a TableMetadataV2,
+    updates: &[TableUpdate<'_>],
+) -> Vec<TableRequirement<'a>> {
+    let mut requirements = vec![TableRequirement::TableUuid {
+        uuid: &previous.table_uuid,
+    }];
+    if updates.iter().any(|update| {
+        matches!(
+            update,
+            TableUpdate::AddSchema { .. } | TableUpdate::SetCurrentSchema { .. }
+        )
+    }) {
+        requirements.push(TableRequirement::CurrentSchemaId {
+            current_schema_id: previous.current_schema_id,
+        });
+        requirements.push(TableRequirement::LastAssignedFieldId {
+            last_assigned_field_id: previous.last_column_id,
+        });
+    }
+    if updates.iter().any(|update| {
+        matches!(
+            update,
+            TableUpdate::AddSpec { .. } | TableUpdate::SetDefaultSpec { .. }
+        )
+    }) {
+        requirements.push(TableRequirement::DefaultSpecId {
+            default_spec_id: previous.default_spec_id,
+        });
+        requirements.push(TableRequirement::LastAssignedPartitionId {
+            last_assigned_partition_id: previous.last_partition_id,
+        });
+    }
+    if updates.iter().any(|update| {
+        matches!(
+            update,
+            TableUpdate::AddSortOrder { .. } | TableUpdate::SetDefaultSortOrder { .. }
+        )
+    }) {
+        requirements.push(TableRequirement::DefaultSortOrderId {
+            default_sort_order_id: previous.default_sort_order_id,
+        });
+    }
+    if updates.iter().any(|update| {
+        matches!(
+            update,
+            TableUpdate::AddSnapshot { .. } | TableUpdate::SetSnapshotRef { .. }
+        )
+    }) {
+        requirements.push(TableRequirement::RefSnapshotId {
+            r#ref: "main",
+            snapshot_id: previous.current_snapshot_id,
+        });
+    }
+    requirements
+}
+
+/// Updates that turn the previous table metadata into the new table metadata.
+fn table_updates<'a>(previous: &TableMetadataV2, new: &'a TableMetadataV2) -> Vec<TableUpdate<'a>> {
+    let mut updates = Vec::new();
+    if previous.location != new.location {
+        updates.push(TableUpdate::SetLocation {
+            location: &new.location,
+        });
+    }
+    for schema in &new.schemas {
+        if !previous
+            .schemas
+            .iter()
+            .any(|previous| previous.schema_id == schema.schema_id)
+        {
+            updates.push(TableUpdate::AddSchema {
+                schema,
+                last_column_id: Some(new.last_column_id),
+            });
+        }
+    }
+    if previous.current_schema_id != new.current_schema_id {
+        updates.push(TableUpdate::SetCurrentSchema {
+            schema_id: new.current_schema_id,
+        });
+    }
+    for spec in &new.partition_specs {
+        if !previous
+            .partition_specs
+            .iter()
+            .any(|previous| previous.spec_id == spec.spec_id)
+        {
+            updates.push(TableUpdate::AddSpec { spec });
+        }
+    }
+    if previous.default_spec_id != new.default_spec_id {
+        updates.push(TableUpdate::SetDefaultSpec {
+            spec_id: new.default_spec_id,
+        });
+    }
+    for sort_order in &new.sort_orders {
+        if !previous
+            .sort_orders
+            .iter()
+            .any(|previous| previous.order_id == sort_order.order_id)
+        {
+            updates.push(TableUpdate::AddSortOrder { sort_order });
+        }
+    }
+    if previous.default_sort_order_id != new.default_sort_order_id {
+        updates.push(TableUpdate::SetDefaultSortOrder {
+            sort_order_id: new.default_sort_order_id,
+        });
+    }
+    for snapshot in new.snapshots.iter().flatten() {
+        if !previous
+            .snapshots
+            .iter()
+            .flatten()
+            .any(|previous| previous.snapshot_id == snapshot.snapshot_id)
+        {
+            updates.push(TableUpdate::AddSnapshot { snapshot });
+        }
+    }
+    if let Some(snapshot_id) = new.current_snapshot_id {
+        if previous.current_snapshot_id != Some(snapshot_id) {
+            updates.push(TableUpdate::SetSnapshotRef {
+                ref_name: "main",
+                r#type: "branch",
+                snapshot_id,
+            });
+        }
+    }
+    let changed_properties = new
+        .properties
+        .iter()
+        .flatten()
+        .filter(|(key, value)| {
+            previous
+                .properties
+                .as_ref()
+                .and_then(|properties| properties.get(*key))
+                != Some(*value)
+        })
+        .map(|(key, value)| (key.as_str(), value.as_str()))
+        .collect::<HashMap<&str, &str>>();
+    if !changed_properties.is_empty() {
+        updates.push(TableUpdate::SetProperties {
+            updates: changed_properties,
+        });
+    }
+    let removed_properties = previous
+        .properties
+        .iter()
+        .flatten()
+        .filter(|(key, _)| {
+            !new.properties
+                .as_ref()
+                .map(|properties| properties.contains_key(*key))
+                .unwrap_or(false)
+        })
+        .map(|(key, _)| key.clone())
+        .collect::<Vec<String>>();
+    if !removed_properties.is_empty() {
+        updates.push(TableUpdate::RemoveProperties {
+            removals: removed_properties,
+        });
+    }
+    updates
+}
+
+#[derive(Debug, Deserialize)]
+struct ConfigResponse {
+    defaults: HashMap<String, String>,
+    overrides: HashMap<String, String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    expires_in: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ErrorResponse {
+    error: ErrorModel,
+}
+
+#[derive(Debug, Deserialize)]
+struct ErrorModel {
+    message: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ListNamespacesResponse {
+    namespaces: Vec<Vec<String>>,
+}
+
+#[derive(Debug, Serialize)]
+struct CreateNamespaceRequest<'a> {
+    namespace: &'a [String],
+    properties: HashMap<String, String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LoadNamespaceResponse {
+    properties: Option<HashMap<String, String>>,
+}
+
+#[derive(Debug, Serialize)]
+struct UpdateNamespacePropertiesRequest<'a> {
+    removals: &'a [String],
+    updates: HashMap<String, String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct TableIdentifier {
+    namespace: Vec<String>,
+    name: String,
+}
+
+impl From<&Identifier> for TableIdentifier {
+    fn from(identifier: &Identifier) -> Self {
+        TableIdentifier {
+            namespace: identifier.namespace().levels().to_vec(),
+            name: identifier.name().to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct RenameTableRequest {
+    source: TableIdentifier,
+    destination: TableIdentifier,
+}
+
+#[derive(Debug, Deserialize)]
+struct ListTablesResponse {
+    identifiers: Vec<TableIdentifier>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct LoadTableResponse {
+    metadata_location: Option<String>,
+    metadata: TableMetadata,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "kebab-case")]
+struct CreateTableRequest<'a> {
+    name: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    location: Option<&'a str>,
+    schema: SchemaV2,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    partition_spec: Option<PartitionSpec>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    write_order: Option<SortOrder>,
+    properties: HashMap<String, String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "kebab-case")]
+struct RegisterTableRequest<'a> {
+    name: &'a str,
+    metadata_location: &'a str,
+}
+
+#[derive(Debug, Serialize)]
+struct CommitTableRequest<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    identifier: Option<TableIdentifier>,
+    requirements: Vec<TableRequirement<'a>>,
+    updates: Vec<TableUpdate<'a>>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "kebab-case")]
+struct CommitTransactionRequest<'a> {
+    table_changes: Vec<CommitTableRequest<'a>>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct CommitTableResponse {
+    metadata_location: String,
+    metadata: TableMetadata,
+}
+
+/// Assertion on the current table state on the server.
+#[derive(Debug, Serialize)]
+#[serde(tag = "type")]
+enum TableRequirement<'a> {
+    #[serde(rename = "assert-table-uuid")]
+    TableUuid { uuid: &'a Uuid },
+    #[serde(rename = "assert-ref-snapshot-id", rename_all = "kebab-case")]
+    RefSnapshotId {
+        r#ref: &'a str,
+        snapshot_id: Option<i64>,
+    },
+    #[serde(rename = "assert-current-schema-id", rename_all = "kebab-case")]
+    CurrentSchemaId { current_schema_id: i32 },
+    #[serde(rename = "assert-last-assigned-field-id", rename_all = "kebab-case")]
+    LastAssignedFieldId { last_assigned_field_id: i32 },
+    #[serde(rename = "assert-default-spec-id", rename_all = "kebab-case")]
+    DefaultSpecId { default_spec_id: i32 },
+    #[serde(
+        rename = "assert-last-assigned-partition-id",
+        rename_all = "kebab-case"
+    )]
+    LastAssignedPartitionId { last_assigned_partition_id: i32 },
+    #[serde(rename = "assert-default-sort-order-id", rename_all = "kebab-case")]
+    DefaultSortOrderId { default_sort_order_id: i64 },
+}
+
+/// Change to the table metadata.
+#[derive(Debug, Serialize)]
+#[serde(tag = "action", rename_all = "kebab-case")]
+enum TableUpdate<'a> {
+    SetLocation {
+        location: &'a str,
+    },
+    #[serde(rename_all = "kebab-case")]
+    AddSchema {
+        schema: &'a SchemaV2,
+        last_column_id: Option<i32>,
+    },
+    #[serde(rename_all = "kebab-case")]
+    SetCurrentSchema {
+        schema_id: i32,
+    },
+    AddSpec {
+        spec: &'a PartitionSpec,
+    },
+    #[serde(rename_all = "kebab-case")]
+    SetDefaultSpec {
+        spec_id: i32,
+    },
+    #[serde(rename_all = "kebab-case")]
+    AddSortOrder {
+        sort_order: &'a SortOrder,
+    },
+    #[serde(rename_all = "kebab-case")]
+    SetDefaultSortOrder {
+        sort_order_id: i64,
+    },
+    AddSnapshot {
+        snapshot: &'a SnapshotV2,
+    },
+    #[serde(rename_all = "kebab-case")]
+    SetSnapshotRef {
+        ref_name: &'a str,
+        r#type: &'a str,
+        snapshot_id: i64,
+    },
+    SetProperties {
+        updates: HashMap<&'a str, &'a str>,
+    },
+    RemoveProperties {
+        removals: Vec<String>,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::{collections::HashMap, sync::Arc};
+
+    use futures::TryStreamExt;
+    use object_store::{memory::InMemory, ObjectStore};
+    use serde_json::json;
+    use wiremock::{
+        http::Method,
+        matchers::{bearer_token, body_partial_json, method, path, query_param},
+        Mock, MockServer, ResponseTemplate,
+    };
+
+    use crate::catalog::{
+        identifier::Identifier,
+        namespace::Namespace,
+        relation::Relation,
+        rest::{RestCatalog, CREDENTIAL, URI},
+        transaction::CatalogTransaction,
+        Catalog, CommitReloadFailed, TableCommit,
+    };
+
+    use crate::catalog::tests::schema;
+
+    fn metadata(properties: serde_json::Value) -> serde_json::Value {
+        json!({
+            "format-version" : 2,
+            "table-uuid": "fb072c92-a02b-11e9-ae9c-1bb7bc9eca94",
+            "location": "test/ns/table1",
+            "last-sequence-number" : 1,
+            "last-updated-ms": 1515100955770_i64,
+            "last-column-id": 1,
+            "schemas": [
+                {
+                    "schema-id" : 1,
+                    "type" : "struct",
+                    "fields" :[
+                        {
+                            "id": 1,
+                            "name": "one",
+                            "required": true,
+                            "type": "string"
+                        }
+                    ]
+                }
+            ],
+            "current-schema-id" : 1,
+            "partition-specs": [
+                {
+                    "spec-id": 1,
+                    "fields": []
+                }
+            ],
+            "default-spec-id": 1,
+            "last-partition-id": 1,
+            "properties": properties,
+            "sort-orders": [],
+            "default-sort-order-id": 0
+        })
+    }
+
+    async fn catalog(server: &MockServer, object_store: Arc<dyn ObjectStore>) -> Arc<dyn Catalog> {
+        Mock::given(method("POST"))
+            .and(path("/v1/oauth/tokens"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "access_token": "secret-token",
+                "token_type": "bearer",
+                "expires_in": 3600
+            })))
+            .mount(server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/v1/config"))
+            .and(bearer_token("secret-token"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "defaults": {},
+                "overrides": { "prefix": "warehouse" }
+            })))
+            .mount(server)
+            .await;
+        let catalog = Arc::new(RestCatalog::new(object_store));
+        catalog
+            .clone()
+            .initialize(&HashMap::from_iter(vec![
+                (URI.to_string(), server.uri()),
+                (CREDENTIAL.to_string(), "client:secret".to_string()),
+            ]))
+            .await
+            .unwrap();
+        catalog
+    }
+
+    /// Get the body of the request that committed changes to the given path.
+    async fn commit_request(server: &MockServer, url_path: &str) -> serde_json::Value {
+        server
+            .received_requests()
+            .await
+            .unwrap()
+            .into_iter()
+            .find(|request| request.method == Method::Post && request.url.path() == url_path)
+            .map(|request| serde_json::from_slice(&request.body).unwrap())
+            .unwrap()
+    }
+
+    /// Commit the change from the previous to the new metadata of table `ns.table1` and get the requirements that
+    /// were sent to the server.
+    async fn commit_requirements(
+        previous: serde_json::Value,
+        new: serde_json::Value,
+    ) -> serde_json::Value {
+        let server = MockServer::start().await;
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let catalog = catalog(&server, Arc::clone(&object_store)).await;
+        object_store
+            .put(
+                &"test/ns/table1/metadata/1.metadata.json".into(),
+                serde_json::to_vec(&previous).unwrap().into(),
+            )
+            .await
+            .unwrap();
+        object_store
+            .put(
+                &"test/ns/table1/metadata/2.metadata.json".into(),
+                serde_json::to_vec(&new).unwrap().into(),
+            )
+            .await
+            .unwrap();
+        Mock::given(method("POST"))
+            .and(path("/v1/warehouse/namespaces/ns/tables/table1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "metadata-location": "test/ns/table1/metadata/3.metadata.json",
+                "metadata": previous
+            })))
+            .mount(&server)
+            .await;
+
+        catalog
+            .update_table(
+                Identifier::parse("ns.table1").unwrap(),
+                "test/ns/table1/metadata/2.metadata.json",
+                "test/ns/table1/metadata/1.metadata.json",
+            )
+            .await
+            .unwrap();
+        commit_request(&server, "/v1/warehouse/namespaces/ns/tables/table1").await["requirements"]
+            .clone()
+    }
+
+    #[tokio::test]
+    async fn test_list_and_load() {
+        let server = MockServer::start().await;
+        let catalog = catalog(&server, Arc::new(InMemory::new())).await;
+        Mock::given(method("GET"))
+            .and(path("/v1/warehouse/namespaces"))
+            .and(query_param("parent", "level1"))
+            .and(bearer_token("secret-token"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "namespaces": [["level1", "ns"]]
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/v1/warehouse/namespaces/ns/tables"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "identifiers": [{ "namespace": ["ns"], "name": "table1" }]
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/v1/warehouse/namespaces/ns/tables/table1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "metadata-location": "test/ns/table1/metadata/1.metadata.json",
+                "metadata": metadata(json!({}))
+            })))
+            .mount(&server)
+            .await;
+
+        assert_eq!(
+            catalog.list_namespaces(Some("level1")).await.unwrap(),
+            vec![Namespace::try_new(&["level1".to_string(), "ns".to_string()]).unwrap()]
+        );
+        let identifier = Identifier::parse("ns.table1").unwrap();
+        assert_eq!(
+            catalog.list_tables(identifier.namespace()).await.unwrap(),
+            vec![identifier.clone()]
+        );
+        let relation = catalog.clone().load_table(&identifier).await.unwrap();
+        assert!(matches!(relation, Relation::Table(_)));
+        assert_eq!(
+            relation.metadata_location(),
+            "test/ns/table1/metadata/1.metadata.json"
+        );
+        assert!(!catalog
+            .table_exists(&Identifier::parse("ns.table2").unwrap())
+            .await
+            .unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_create_table() {
+        let server = MockServer::start().await;
+        let catalog = catalog(&server, Arc::new(InMemory::new())).await;
+        Mock::given(method("POST"))
+            .and(path("/v1/warehouse/namespaces/ns/tables"))
+            .and(body_partial_json(json!({
+                "name": "table1",
+                "properties": { "owner": "me" }
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "metadata-location": "test/ns/table1/metadata/1.metadata.json",
+                "metadata": metadata(json!({ "owner": "me" }))
+            })))
+            .mount(&server)
+            .await;
+
+        let table = catalog
+            .create_table(
+                Identifier::parse("ns.table1").unwrap(),
+                schema(),
+                None,
+                None,
+                None,
+                HashMap::from_iter(vec![("owner".to_string(), "me".to_string())]),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            table.metadata_location(),
+            "test/ns/table1/metadata/1.metadata.json"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rename_table() {
+        let server = MockServer::start().await;
+        let catalog = catalog(&server, Arc::new(InMemory::new())).await;
+        Mock::given(method("POST"))
+            .and(path("/v1/warehouse/tables/rename"))
+            .and(body_partial_json(json!({
+                "source": { "namespace": ["ns"], "name": "table1" },
+                "destination": { "namespace": ["level1", "ns"], "name": "table2" }
+            })))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&server)
+            .await;
+
+        catalog
+            .rename_table(
+                &Identifier::parse("ns.table1").unwrap(),
+                &Identifier::parse("level1.ns.table2").unwrap(),
+            )
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_namespace_properties() {
+        let server = MockServer::start().await;
+        let catalog = catalog(&server, Arc::new(InMemory::new())).await;
+        Mock::given(method("POST"))
+            .and(path("/v1/warehouse/namespaces"))
+            .and(body_partial_json(json!({
+                "namespace": ["level1", "ns"],
+                "properties": { "owner": "me" }
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "namespace": ["level1", "ns"],
+                "properties": { "owner": "me" }
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/v1/warehouse/namespaces/level1%1Fns/properties"))
+            .and(body_partial_json(json!({
+                "removals": ["owner"],
+                "updates": { "comment": "test" }
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "updated": ["comment"],
+                "removed": ["owner"]
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/v1/warehouse/namespaces/level1%1Fns"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "namespace": ["level1", "ns"],
+                "properties": { "comment": "test" }
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("DELETE"))
+            .and(path("/v1/warehouse/namespaces/level1%1Fns"))
+            .respond_with(ResponseTemplate::new(409).set_body_json(json!({
+                "error": {
+                    "message": "Namespace is not empty",
+                    "type": "NamespaceNotEmptyException",
+                    "code": 409
+                }
+            })))
+            .mount(&server)
+            .await;
+
+        let namespace = Namespace::try_new(&["level1".to_string(), "ns".to_string()]).unwrap();
+        catalog
+            .create_namespace(
+                &namespace,
+                HashMap::from_iter(vec![("owner".to_string(), "me".to_string())]),
+            )
+            .await
+            .unwrap();
+        catalog
+            .update_namespace_properties(
+                &namespace,
+                &["owner".to_string()],
+                HashMap::from_iter(vec![("comment".to_string(), "test".to_string())]),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            catalog.load_namespace_properties(&namespace).await.unwrap(),
+            HashMap::from_iter(vec![("comment".to_string(), "test".to_string())])
+        );
+        let error = catalog.drop_namespace(&namespace).await.unwrap_err();
+        assert!(error.to_string().contains("Namespace is not empty"));
+    }
+
+    #[tokio::test]
+    async fn test_update_table() {
+        let server = MockServer::start().await;
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let catalog = catalog(&server, Arc::clone(&object_store)).await;
+        object_store
+            .put(
+                &"test/ns/table1/metadata/1.metadata.json".into(),
+                serde_json::to_vec(&metadata(json!({}))).unwrap().into(),
+            )
+            .await
+            .unwrap();
+        object_store
+            .put(
+                &"test/ns/table1/metadata/2.metadata.json".into(),
+                serde_json::to_vec(&metadata(json!({ "owner": "me" })))
+                    .unwrap()
+                    .into(),
+            )
+            .await
+            .unwrap();
+        Mock::given(method("POST"))
+            .and(path("/v1/warehouse/namespaces/ns/tables/table1"))
+            .and(body_partial_json(json!({
+                "updates": [{ "action": "set-properties", "updates": { "owner": "me" } }]
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "metadata-location": "test/ns/table1/metadata/3.metadata.json",
+                "metadata": metadata(json!({ "owner": "me" }))
+            })))
+            .mount(&server)
+            .await;
+
+        let relation = catalog
+            .clone()
+            .update_table(
+                Identifier::parse("ns.table1").unwrap(),
+                "test/ns/table1/metadata/2.metadata.json",
+                "test/ns/table1/metadata/1.metadata.json",
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            relation.metadata_location(),
+            "test/ns/table1/metadata/3.metadata.json"
+        );
+        // Changing properties only requires the table to be the same
+        assert_eq!(
+            commit_request(&server, "/v1/warehouse/namespaces/ns/tables/table1").await
+                ["requirements"],
+            json!([{ "type": "assert-table-uuid", "uuid": "fb072c92-a02b-11e9-ae9c-1bb7bc9eca94" }])
+        );
+        // The server wrote its own metadata file, the staged file was deleted
+        assert!(object_store
+            .head(&"test/ns/table1/metadata/2.metadata.json".into())
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_commit_requirements() {
+        let previous = metadata(json!({}));
+        let mut new = previous.clone();
+        new["last-column-id"] = json!(2);
+        new["schemas"].as_array_mut().unwrap().push(json!({
+            "schema-id" : 2,
+            "type" : "struct",
+            "fields" :[
+                { "id": 1, "name": "one", "required": true, "type": "string" },
+                { "id": 2, "name": "two", "required": false, "type": "string" }
+            ]
+        }));
+        new["current-schema-id"] = json!(2);
+        new["partition-specs"].as_array_mut().unwrap().push(json!({
+            "spec-id": 2,
+            "fields": [{ "source-id": 1, "field-id": 1000, "name": "one", "transform": "identity" }]
+        }));
+        new["default-spec-id"] = json!(2);
+        new["last-partition-id"] = json!(1000);
+        new["sort-orders"] = json!([{
+            "order-id": 1,
+            "fields": [{ "source-id": 1, "transform": "identity", "direction": "asc", "null-order": "nulls-first" }]
+        }]);
+        new["default-sort-order-id"] = json!(1);
+
+        assert_eq!(
+            commit_requirements(previous, new).await,
+            json!([
+                { "type": "assert-table-uuid", "uuid": "fb072c92-a02b-11e9-ae9c-1bb7bc9eca94" },
+                { "type": "assert-current-schema-id", "current-schema-id": 1 },
+                { "type": "assert-last-assigned-field-id", "last-assigned-field-id": 1 },
+                { "type": "assert-default-spec-id", "default-spec-id": 1 },
+                { "type": "assert-last-assigned-partition-id", "last-assigned-partition-id": 1 },
+                { "type": "assert-default-sort-order-id", "default-sort-order-id": 0 }
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_commit_snapshot_requirements() {
+        let previous = metadata(json!({}));
+        let mut new = previous.clone();
+        new["snapshots"] = json!([{
+            "snapshot-id": 1,
+            "sequence-number": 1,
+            "timestamp-ms": 1515100955770_i64,
+            "manifest-list": "test/ns/table1/metadata/snap-1.avro",
+            "summary": { "operation": "append" }
+        }]);
+        new["current-snapshot-id"] = json!(1);
+
+        assert_eq!(
+            commit_requirements(previous, new).await,
+            json!([
+                { "type": "assert-table-uuid", "uuid": "fb072c92-a02b-11e9-ae9c-1bb7bc9eca94" },
+                { "type": "assert-ref-snapshot-id", "ref": "main", "snapshot-id": null }
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_views_unsupported() {
+        let server = MockServer::start().await;
+        let catalog = catalog(&server, Arc::new(InMemory::new())).await;
+
+        let identifier = Identifier::parse("ns.view1").unwrap();
+        assert!(catalog
+            .clone()
+            .create_view(
+                identifier.clone(),
+                "SELECT 1",
+                schema(),
+                None,
+                HashMap::new()
+            )
+            .await
+            .is_err());
+        assert!(catalog.drop_view(&identifier).await.is_err());
+        // No request for views was sent to the server
+        assert!(server
+            .received_requests()
+            .await
+            .unwrap()
+            .iter()
+            .all(|request| !request.url.path().contains("view")));
+    }
+
+    #[tokio::test]
+    async fn test_update_tables() {
+        let server = MockServer::start().await;
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let catalog = catalog(&server, Arc::clone(&object_store)).await;
+        object_store
+            .put(
+                &"test/ns/table1/metadata/1.metadata.json".into(),
+                serde_json::to_vec(&metadata(json!({}))).unwrap().into(),
+            )
+            .await
+            .unwrap();
+        object_store
+            .put(
+                &"test/ns/table1/metadata/2.metadata.json".into(),
+                serde_json::to_vec(&metadata(json!({ "owner": "me" })))
+                    .unwrap()
+                    .into(),
+            )
+            .await
+            .unwrap();
+        Mock::given(method("POST"))
+            .and(path("/v1/warehouse/transactions/commit"))
+            .and(body_partial_json(json!({
+                "table-changes": [{
+                    "identifier": { "namespace": ["ns"], "name": "table1" },
+                    "updates": [{ "action": "set-properties", "updates": { "owner": "me" } }]
+                }]
+            })))
+            .respond_with(ResponseTemplate::new(204))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/v1/warehouse/namespaces/ns/tables/table1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "metadata-location": "test/ns/table1/metadata/3.metadata.json",
+                "metadata": metadata(json!({ "owner": "me" }))
+            })))
+            .mount(&server)
+            .await;
+
+        let relations = catalog
+            .clone()
+            .update_tables(vec![TableCommit {
+                identifier: Identifier::parse("ns.table1").unwrap(),
+                metadata_file_location: "test/ns/table1/metadata/2.metadata.json".to_string(),
+                previous_metadata_file_location: "test/ns/table1/metadata/1.metadata.json"
+                    .to_string(),
+            }])
+            .await
+            .unwrap();
+        assert_eq!(
+            relations[0].metadata_location(),
+            "test/ns/table1/metadata/3.metadata.json"
+        );
+        assert_eq!(
+            commit_request(&server, "/v1/warehouse/transactions/commit").await["table-changes"][0]
+                ["requirements"],
+            json!([{ "type": "assert-table-uuid", "uuid": "fb072c92-a02b-11e9-ae9c-1bb7bc9eca94" }])
+        );
+        assert!(object_store
+            .head(&"test/ns/table1/metadata/2.metadata.json".into())
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_update_tables_reload_failure() {
+        let server = MockServer::start().await;
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let catalog = catalog(&server, Arc::clone(&object_store)).await;
+        object_store
+            .put(
+                &"test/ns/table1/metadata/1.metadata.json".into(),
+                serde_json::to_vec(&metadata(json!({}))).unwrap().into(),
+            )
+            .await
+            .unwrap();
+        Mock::given(method("GET"))
+            .and(path("/v1/warehouse/namespaces/ns/tables/table1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "metadata-location": "test/ns/table1/metadata/1.metadata.json",
+                "metadata": metadata(json!({}))
+            })))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/v1/warehouse/namespaces/ns/tables/table1"))
+            .respond_with(ResponseTemplate::new(503))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/v1/warehouse/transactions/commit"))
+            .respond_with(ResponseTemplate::new(204))
+            .mount(&server)
+            .await;
+        let mut table = match catalog
+            .clone()
+            .load_table(&Identifier::parse("ns.table1").unwrap())
+            .await
+            .unwrap()
+        {
+            Relation::Table(table) => table,
+            Relation::View(_) => panic!("Relation is not a table."),
+        };
+
+        // The commit succeeded, but the new state of the table can't be loaded
+        let error = CatalogTransaction::new(Arc::clone(&catalog))
+            .with_transaction(
+                table
+                    .new_transaction()
+                    .fast_append(vec!["test/ns/table1/data/file1.parquet".to_string()]),
+            )
+            .unwrap()
+            .commit()
+            .await
+            .unwrap_err();
+        assert!(error.is::<CommitReloadFailed>());
+        // The manifest list and the manifest referenced by the committed snapshot were kept
+        let files: Vec<String> = object_store
+            .list(None)
+            .await
+            .unwrap()
+            .map_ok(|object_meta| object_meta.location.to_string())
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(
+            files.iter().filter(|file| file.ends_with(".avro")).count(),
+            2
+        );
+    }
+}
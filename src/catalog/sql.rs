@@ -0,0 +1,645 @@
+/*!
+Defining the [SqlCatalog] struct, a [Catalog] that stores its metadata pointers in a relational database.
+
+The catalog stores its data in a SQLite database with the table layout of the JDBC catalog of the java implementation.
+The metadata pointers of tables and views are stored in the `iceberg_tables` table and namespace properties in the
+`iceberg_namespace_properties` table. Views are stored like tables, without a column for the type of the relation,
+so the database can't be shared with the JDBC catalog.
+
+The catalog is configured through [Catalog::initialize] with the following properties:
+- `uri`: Connection string of the database, for example `sqlite://catalog.db?mode=rwc`.
+- `pool.max-connections`: Optional maximum number of connections in the connection pool.
+*/
+
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+use anyhow::{anyhow, Result};
+use object_store::ObjectStore;
+use sqlx::{
+    any::{AnyPool, AnyPoolOptions},
+    Row,
+};
+
+use super::identifier::{Identifier, SEPARATOR};
+use super::namespace::Namespace;
+use super::relation::{delete_relation_files, Relation};
+use super::{Catalog, CommitConflict, TableCommit};
+
+/// Catalog property for the connection string of the database.
+pub static URI: &str = "uri";
+/// Catalog property for the maximum number of connections to the database.
+pub static MAX_CONNECTIONS: &str = "pool.max-connections";
+/// Property that marks explicitly created namespaces, like in the JDBC catalog of the java implementation.
+static NAMESPACE_EXISTS_PROPERTY: &str = "exists";
+
+/// Catalog that stores the metadata pointers of its tables and views in a sql database.
+pub struct SqlCatalog {
+    name: String,
+    object_store: Arc<dyn ObjectStore>,
+    pool: RwLock<Option<AnyPool>>,
+}
+
+impl SqlCatalog {
+    /// Create a new sql catalog with the given name. The catalog has to be initialized with [Catalog::initialize] before it can be used.
+    pub fn new(name: &str, object_store: Arc<dyn ObjectStore>) -> Self {
+        SqlCatalog {
+            name: name.to_string(),
+            object_store,
+            pool: RwLock::new(None),
+        }
+    }
+    /// Get the connection pool of the catalog. Fails if the catalog is not initialized.
+    fn pool(&self) -> Result<AnyPool> {
+        self.pool
+            .read()
+            .map_err(|err| anyhow!(err.to_string()))?
+            .clone()
+            .ok_or_else(|| anyhow!("Sql catalog {} is not initialized.", self.name))
+    }
+    /// Get the metadata location of the table from the database
+    async fn metadata_location(&self, identifier: &Identifier) -> Result<Option<String>> {
+        let row = sqlx::query(
+            "SELECT metadata_location FROM iceberg_tables WHERE catalog_name = $1 AND table_namespace = $2 AND table_name = $3",
+        )
+        .bind(&self.name)
+        .bind(identifier.namespace().to_string())
+        .bind(identifier.name())
+        .fetch_optional(&self.pool()?)
+        .await?;
+        row.map(|row| row.try_get::<String, _>(0).map_err(anyhow::Error::msg))
+            .transpose()
+    }
+    /// Get the levels of all namespaces that contain tables or have properties.
+    async fn namespace_levels(&self) -> Result<Vec<Vec<String>>> {
+        let rows = sqlx::query(
+            "SELECT DISTINCT table_namespace FROM iceberg_tables WHERE catalog_name = $1 UNION SELECT DISTINCT namespace FROM iceberg_namespace_properties WHERE catalog_name = $2",
+        )
+        .bind(&self.name)
+        .bind(&self.name)
+        .fetch_all(&self.pool()?)
+        .await?;
+        rows.iter()
+            .map(|row| {
+                Ok(row
+                    .try_get::<String, _>(0)?
+                    .split(SEPARATOR)
+                    .map(|x| x.to_string())
+                    .collect::<Vec<String>>())
+            })
+            .collect()
+    }
+    /// Check if the namespace exists, either explicitly or as parent of another namespace.
+    async fn namespace_exists(&self, namespace: &Namespace) -> Result<bool> {
+        Ok(self.namespace_levels().await?.iter().any(|levels| {
+            levels.len() >= namespace.len() && levels[..namespace.len()] == namespace.levels()[..]
+        }))
+    }
+}
+
+#[async_trait::async_trait]
+impl Catalog for SqlCatalog {
+    async fn list_tables(&self, namespace: &Namespace) -> Result<Vec<Identifier>> {
+        let rows = sqlx::query(
+            "SELECT table_name FROM iceberg_tables WHERE catalog_name = $1 AND table_namespace = $2",
+        )
+        .bind(&self.name)
+        .bind(namespace.to_string())
+        .fetch_all(&self.pool()?)
+        .await?;
+        rows.iter()
+            .map(|row| {
+                let name: String = row.try_get(0)?;
+                Identifier::try_new(&[namespace.levels(), &[name]].concat())
+            })
+            .collect()
+    }
+    async fn list_namespaces(&self, parent: Option<&str>) -> Result<Vec<Namespace>> {
+        let parent = match parent {
+            Some(parent) if !parent.is_empty() => parent
+                .split(SEPARATOR)
+                .map(|x| x.to_string())
+                .collect::<Vec<String>>(),
+            _ => vec![],
+        };
+        let mut namespaces = self
+            .namespace_levels()
+            .await?
+            .into_iter()
+            .filter(|levels| levels.len() > parent.len() && levels[..parent.len()] == parent[..])
+            .map(|levels| Namespace::try_new(&levels[..parent.len() + 1]))
+            .collect::<Result<Vec<Namespace>>>()?;
+        namespaces.sort_by_key(|namespace| namespace.to_string());
+        namespaces.dedup();
+        Ok(namespaces)
+    }
+    async fn create_namespace(
+        &self,
+        namespace: &Namespace,
+        properties: HashMap<String, String>,
+    ) -> Result<()> {
+        if self.namespace_exists(namespace).await? {
+            return Err(anyhow!(
+                "Namespace {} already exists in the catalog.",
+                namespace
+            ));
+        }
+        let mut transaction = self.pool()?.begin().await?;
+        for (key, value) in
+            std::iter::once((NAMESPACE_EXISTS_PROPERTY.to_string(), "true".to_string()))
+                .chain(properties)
+        {
+            sqlx::query(
+                "INSERT INTO iceberg_namespace_properties (catalog_name, namespace, property_key, property_value) VALUES ($1, $2, $3, $4)",
+            )
+            .bind(&self.name)
+            .bind(namespace.to_string())
+            .bind(key)
+            .bind(value)
+            .execute(&mut transaction)
+            .await?;
+        }
+        transaction.commit().await?;
+        Ok(())
+    }
+    async fn drop_namespace(&self, namespace: &Namespace) -> Result<()> {
+        if !self.namespace_exists(namespace).await? {
+            return Err(anyhow!(
+                "Namespace {} doesn't exist in the catalog.",
+                namespace
+            ));
+        }
+        let contains_namespaces = self.namespace_levels().await?.iter().any(|levels| {
+            levels.len() > namespace.len() && levels[..namespace.len()] == namespace.levels()[..]
+        });
+        if contains_namespaces || !self.list_tables(namespace).await?.is_empty() {
+            return Err(anyhow!(
+                "Namespace {} is not empty and can't be dropped.",
+                namespace
+            ));
+        }
+        sqlx::query(
+            "DELETE FROM iceberg_namespace_properties WHERE catalog_name = $1 AND namespace = $2",
+        )
+        .bind(&self.name)
+        .bind(namespace.to_string())
+        .execute(&self.pool()?)
+        .await?;
+        Ok(())
+    }
+    async fn load_namespace_properties(
+        &self,
+        namespace: &Namespace,
+    ) -> Result<HashMap<String, String>> {
+        if !self.namespace_exists(namespace).await? {
+            return Err(anyhow!(
+                "Namespace {} doesn't exist in the catalog.",
+                namespace
+            ));
+        }
+        let rows = sqlx::query(
+            "SELECT property_key, property_value FROM iceberg_namespace_properties WHERE catalog_name = $1 AND namespace = $2",
+        )
+        .bind(&self.name)
+        .bind(namespace.to_string())
+        .fetch_all(&self.pool()?)
+        .await?;
+        rows.iter()
+            .map(|row| Ok((row.try_get::<String, _>(0)?, row.try_get::<String, _>(1)?)))
+            .filter(
+                |property| !matches!(property, Ok((key, _)) if key == NAMESPACE_EXISTS_PROPERTY),
+            )
+            .collect()
+    }
+    async fn update_namespace_properties(
+        &self,
+        namespace: &Namespace,
+        removals: &[String],
+        updates: HashMap<String, String>,
+    ) -> Result<()> {
+        if !self.namespace_exists(namespace).await? {
+            return Err(anyhow!(
+                "Namespace {} doesn't exist in the catalog.",
+                namespace
+            ));
+        }
+        let mut transaction = self.pool()?.begin().await?;
+        for key in removals.iter().chain(updates.keys()) {
+            sqlx::query(
+                "DELETE FROM iceberg_namespace_properties WHERE catalog_name = $1 AND namespace = $2 AND property_key = $3",
+            )
+            .bind(&self.name)
+            .bind(namespace.to_string())
+            .bind(key)
+            .execute(&mut transaction)
+            .await?;
+        }
+        for (key, value) in updates {
+            sqlx::query(
+                "INSERT INTO iceberg_namespace_properties (catalog_name, namespace, property_key, property_value) VALUES ($1, $2, $3, $4)",
+            )
+            .bind(&self.name)
+            .bind(namespace.to_string())
+            .bind(key)
+            .bind(value)
+            .execute(&mut transaction)
+            .await?;
+        }
+        transaction.commit().await?;
+        Ok(())
+    }
+    async fn table_exists(&self, identifier: &Identifier) -> Result<bool> {
+        Ok(self.metadata_location(identifier).await?.is_some())
+    }
+    async fn drop_table(&self, identifier: &Identifier) -> Result<()> {
+        let metadata_location = self
+            .metadata_location(identifier)
+            .await?
+            .ok_or_else(|| anyhow!("Table {} doesn't exist in the catalog.", identifier))?;
+        sqlx::query(
+            "DELETE FROM iceberg_tables WHERE catalog_name = $1 AND table_namespace = $2 AND table_name = $3",
+        )
+        .bind(&self.name)
+        .bind(identifier.namespace().to_string())
+        .bind(identifier.name())
+        .execute(&self.pool()?)
+        .await?;
+        delete_relation_files(self.object_store(), &metadata_location).await
+    }
+    async fn rename_table(&self, from: &Identifier, to: &Identifier) -> Result<()> {
+        if self.table_exists(to).await? {
+            return Err(anyhow!("Table {} already exists in the catalog.", to));
+        }
+        // The primary key prevents a concurrent rename or registration from creating the target twice.
+        let result = sqlx::query(
+            "UPDATE iceberg_tables SET table_namespace = $1, table_name = $2 WHERE catalog_name = $3 AND table_namespace = $4 AND table_name = $5",
+        )
+        .bind(to.namespace().to_string())
+        .bind(to.name())
+        .bind(&self.name)
+        .bind(from.namespace().to_string())
+        .bind(from.name())
+        .execute(&self.pool()?)
+        .await
+        .map_err(|err| anyhow!("Failed to rename table {} to {}: {}", from, to, err))?;
+        if result.rows_affected() != 1 {
+            return Err(anyhow!("Table {} doesn't exist in the catalog.", from));
+        }
+        Ok(())
+    }
+    async fn load_table(self: Arc<Self>, identifier: &Identifier) -> Result<Relation> {
+        let metadata_location = self
+            .metadata_location(identifier)
+            .await?
+            .ok_or_else(|| anyhow!("Table {} doesn't exist in the catalog.", identifier))?;
+        Relation::try_from_metadata_location(identifier.clone(), self, &metadata_location).await
+    }
+    async fn invalidate_table(&self, _identifier: &Identifier) -> Result<()> {
+        Ok(())
+    }
+    async fn register_table(
+        self: Arc<Self>,
+        identifier: Identifier,
+        metadata_file_location: &str,
+    ) -> Result<Relation> {
+        if self.table_exists(&identifier).await? {
+            return Err(anyhow!(
+                "Table {} already exists in the catalog.",
+                identifier
+            ));
+        }
+        let relation = Relation::try_from_metadata_location(
+            identifier.clone(),
+            self.clone(),
+            metadata_file_location,
+        )
+        .await?;
+        sqlx::query(
+            "INSERT INTO iceberg_tables (catalog_name, table_namespace, table_name, metadata_location, previous_metadata_location) VALUES ($1, $2, $3, $4, NULL)",
+        )
+        .bind(&self.name)
+        .bind(identifier.namespace().to_string())
+        .bind(identifier.name())
+        .bind(metadata_file_location)
+        .execute(&self.pool()?)
+        .await
+        .map_err(|err| {
+            anyhow!(
+                "Failed to register table {} in the catalog: {}",
+                identifier,
+                err
+            )
+        })?;
+        Ok(relation)
+    }
+    async fn update_table(
+        self: Arc<Self>,
+        identifier: Identifier,
+        metadata_file_location: &str,
+        previous_metadata_file_location: &str,
+    ) -> Result<Relation> {
+        let relation = Relation::try_from_metadata_location(
+            identifier.clone(),
+            self.clone(),
+            metadata_file_location,
+        )
+        .await?;
+        // The metadata location is only swapped if it still equals the previous location. This performs the atomic compare-and-swap.
+        let result = sqlx::query(
+            "UPDATE iceberg_tables SET metadata_location = $1, previous_metadata_location = $2 WHERE catalog_name = $3 AND table_namespace = $4 AND table_name = $5 AND metadata_location = $6",
+        )
+        .bind(metadata_file_location)
+        .bind(previous_metadata_file_location)
+        .bind(&self.name)
+        .bind(identifier.namespace().to_string())
+        .bind(identifier.name())
+        .bind(previous_metadata_file_location)
+        .execute(&self.pool()?)
+        .await?;
+        if result.rows_affected() != 1 {
+            return Err(CommitConflict(format!(
+                "Failed to update table {}: the metadata location {} is not current anymore.",
+                identifier, previous_metadata_file_location
+            ))
+            .into());
+        }
+        Ok(relation)
+    }
+    async fn update_tables(self: Arc<Self>, commits: Vec<TableCommit>) -> Result<Vec<Relation>> {
+        let mut relations = Vec::with_capacity(commits.len());
+        for commit in &commits {
+            relations.push(
+                Relation::try_from_metadata_location(
+                    commit.identifier.clone(),
+                    self.clone(),
+                    &commit.metadata_file_location,
+                )
+                .await?,
+            );
+        }
+        // The database transaction is rolled back when it is dropped before all swaps succeeded
+        let mut transaction = self.pool()?.begin().await?;
+        for commit in &commits {
+            let result = sqlx::query(
+                "UPDATE iceberg_tables SET metadata_location = $1, previous_metadata_location = $2 WHERE catalog_name = $3 AND table_namespace = $4 AND table_name = $5 AND metadata_location = $6",
+            )
+            .bind(&commit.metadata_file_location)
+            .bind(&commit.previous_metadata_file_location)
+            .bind(&self.name)
+            .bind(commit.identifier.namespace().to_string())
+            .bind(commit.identifier.name())
+            .bind(&commit.previous_metadata_file_location)
+            .execute(&mut transaction)
+            .await?;
+            if result.rows_affected() != 1 {
+                return Err(CommitConflict(format!(
+                    "Failed to update table {}: the metadata location {} is not current anymore.",
+                    commit.identifier, commit.previous_metadata_file_location
+                ))
+                .into());
+            }
+        }
+        transaction.commit().await?;
+        Ok(relations)
+    }
+    fn supports_multi_table_updates(&self) -> bool {
+        true
+    }
+    async fn initialize(self: Arc<Self>, properties: &HashMap<String, String>) -> Result<()> {
+        let uri = properties
+            .get(URI)
+            .ok_or_else(|| anyhow!("Sql catalog requires the property {}.", URI))?;
+        let mut options = AnyPoolOptions::new();
+        if let Some(max_connections) = properties.get(MAX_CONNECTIONS) {
+            options = options.max_connections(max_connections.parse()?);
+        }
+        let pool = options.connect(uri).await?;
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS iceberg_tables (
+                catalog_name VARCHAR(255) NOT NULL,
+                table_namespace VARCHAR(255) NOT NULL,
+                table_name VARCHAR(255) NOT NULL,
+                metadata_location VARCHAR(1000),
+                previous_metadata_location VARCHAR(1000),
+                PRIMARY KEY (catalog_name, table_namespace, table_name)
+            )",
+        )
+        .execute(&pool)
+        .await?;
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS iceberg_namespace_properties (
+                catalog_name VARCHAR(255) NOT NULL,
+                namespace VARCHAR(255) NOT NULL,
+                property_key VARCHAR(255),
+                property_value VARCHAR(1000),
+                PRIMARY KEY (catalog_name, namespace, property_key)
+            )",
+        )
+        .execute(&pool)
+        .await?;
+        *self.pool.write().map_err(|err| anyhow!(err.to_string()))? = Some(pool);
+        Ok(())
+    }
+    fn object_store(&self) -> Arc<dyn ObjectStore> {
+        Arc::clone(&self.object_store)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::{collections::HashMap, sync::Arc};
+
+    use object_store::{memory::InMemory, ObjectStore};
+
+    use crate::{
+        catalog::{
+            identifier::Identifier,
+            namespace::Namespace,
+            relation::Relation,
+            sql::{SqlCatalog, MAX_CONNECTIONS, URI},
+            transaction::CatalogTransaction,
+            Catalog,
+        },
+        table::table_builder::TableBuilder,
+    };
+
+    use crate::catalog::tests::{check_namespace_properties, check_rename_table, schema};
+
+    async fn catalog() -> Arc<dyn Catalog> {
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let catalog = Arc::new(SqlCatalog::new("test", object_store));
+        // Every connection to an in-memory database opens a new database, so the pool has to keep a single connection
+        catalog
+            .clone()
+            .initialize(&HashMap::from_iter(vec![
+                (URI.to_string(), "sqlite::memory:".to_string()),
+                (MAX_CONNECTIONS.to_string(), "1".to_string()),
+            ]))
+            .await
+            .unwrap();
+        catalog
+    }
+
+    #[tokio::test]
+    async fn test_register_and_load_table() {
+        let catalog = catalog().await;
+        let identifier = Identifier::parse("load.test.table1").unwrap();
+        let table = TableBuilder::new_metastore_table(
+            "test/",
+            schema(),
+            identifier.clone(),
+            Arc::clone(&catalog),
+        )
+        .unwrap()
+        .commit()
+        .await
+        .unwrap();
+
+        assert!(catalog.table_exists(&identifier).await.unwrap());
+        assert_eq!(
+            catalog.list_tables(identifier.namespace()).await.unwrap(),
+            vec![identifier.clone()]
+        );
+        let relation = catalog.clone().load_table(&identifier).await.unwrap();
+        assert!(matches!(relation, Relation::Table(_)));
+        assert_eq!(relation.metadata_location(), table.metadata_location());
+        assert_eq!(
+            catalog.list_namespaces(None).await.unwrap(),
+            vec![Namespace::try_new(&["load".to_string()]).unwrap()]
+        );
+        assert!(catalog
+            .clone()
+            .register_table(identifier.clone(), table.metadata_location())
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_update_table() {
+        let catalog = catalog().await;
+        let identifier = Identifier::parse("update.table1").unwrap();
+        let mut table = TableBuilder::new_metastore_table(
+            "test/",
+            schema(),
+            identifier.clone(),
+            Arc::clone(&catalog),
+        )
+        .unwrap()
+        .commit()
+        .await
+        .unwrap();
+        let previous_metadata_location = table.metadata_location().to_string();
+
+        table.new_transaction().commit().await.unwrap();
+        assert_eq!(
+            catalog
+                .clone()
+                .load_table(&identifier)
+                .await
+                .unwrap()
+                .metadata_location(),
+            table.metadata_location()
+        );
+
+        // Swapping the pointer based on an outdated metadata location has to fail
+        assert!(catalog
+            .clone()
+            .update_table(
+                identifier.clone(),
+                &previous_metadata_location,
+                &previous_metadata_location
+            )
+            .await
+            .is_err());
+
+        catalog.drop_table(&identifier).await.unwrap();
+        assert!(!catalog.table_exists(&identifier).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_update_tables() {
+        let catalog = catalog().await;
+        let identifier1 = Identifier::parse("update.table1").unwrap();
+        let identifier2 = Identifier::parse("update.table2").unwrap();
+        let mut table1 = TableBuilder::new_metastore_table(
+            "test/",
+            schema(),
+            identifier1.clone(),
+            Arc::clone(&catalog),
+        )
+        .unwrap()
+        .commit()
+        .await
+        .unwrap();
+        let mut table2 = TableBuilder::new_metastore_table(
+            "test/",
+            schema(),
+            identifier2.clone(),
+            Arc::clone(&catalog),
+        )
+        .unwrap()
+        .commit()
+        .await
+        .unwrap();
+        let previous_metadata_location = table1.metadata_location().to_string();
+
+        // A conflict on one table rolls back the changes to the other tables
+        let mut concurrent_table2 = table2.clone();
+        concurrent_table2.new_transaction().commit().await.unwrap();
+        assert!(CatalogTransaction::new(Arc::clone(&catalog))
+            .with_transaction(table1.new_transaction())
+            .unwrap()
+            .with_transaction(table2.new_transaction())
+            .unwrap()
+            .commit()
+            .await
+            .is_err());
+        assert_eq!(
+            catalog
+                .clone()
+                .load_table(&identifier1)
+                .await
+                .unwrap()
+                .metadata_location(),
+            previous_metadata_location
+        );
+
+        CatalogTransaction::new(Arc::clone(&catalog))
+            .with_transaction(table1.new_transaction())
+            .unwrap()
+            .with_transaction(concurrent_table2.new_transaction())
+            .unwrap()
+            .commit()
+            .await
+            .unwrap();
+        assert_eq!(
+            catalog
+                .clone()
+                .load_table(&identifier1)
+                .await
+                .unwrap()
+                .metadata_location(),
+            table1.metadata_location()
+        );
+        assert_eq!(
+            catalog
+                .clone()
+                .load_table(&identifier2)
+                .await
+                .unwrap()
+                .metadata_location(),
+            concurrent_table2.metadata_location()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rename_table() {
+        check_rename_table(catalog().await).await;
+    }
+
+    #[tokio::test]
+    async fn test_namespace_properties() {
+        check_namespace_properties(catalog().await).await;
+    }
+}
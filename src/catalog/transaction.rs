@@ -0,0 +1,363 @@
+/*!
+Defines the [CatalogTransaction] type that commits [TableTransaction]s on several tables of a catalog in one atomic step.
+*/
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+
+use super::identifier::Identifier;
+use super::relation::{write_metadata, Relation};
+use super::{Catalog, CommitConflict, TableCommit};
+use crate::table::transaction::{delete_uncommitted_files, TableTransaction};
+use crate::table::Table;
+use crate::util;
+
+/// Transaction that changes several tables of a catalog together, so that either all of them change or none does.
+pub struct CatalogTransaction<'table> {
+    catalog: Arc<dyn Catalog>,
+    transactions: HashMap<Identifier, TableTransaction<'table>>,
+}
+
+impl<'table> CatalogTransaction<'table> {
+    /// Create a transaction on the tables of the given catalog.
+    pub fn new(catalog: Arc<dyn Catalog>) -> Self {
+        CatalogTransaction {
+            catalog,
+            transactions: HashMap::new(),
+        }
+    }
+    /// Add the transaction of a table of the catalog. Fails if the table isn't stored in the catalog of the
+    /// transaction or if the catalog transaction already contains a transaction for the table.
+    pub fn with_transaction(mut self, transaction: TableTransaction<'table>) -> Result<Self> {
+        let table = transaction.table();
+        let identifier = match (table.catalog(), table.identifier()) {
+            (Some(catalog), Some(identifier)) if Arc::ptr_eq(catalog, &self.catalog) => {
+                identifier.clone()
+            }
+            _ => {
+                return Err(anyhow!(
+                    "Only tables of the catalog of the transaction can be part of a catalog transaction."
+                ))
+            }
+        };
+        if self.transactions.contains_key(&identifier) {
+            return Err(anyhow!(
+                "The catalog transaction already contains a transaction for table {}.",
+                identifier
+            ));
+        }
+        self.transactions.insert(identifier, transaction);
+        Ok(self)
+    }
+    /// Commit the transactions of all tables in one atomic step. Unlike single table commits, a catalog transaction
+    /// is not retried if another writer changed one of the tables in the meantime. Fails if the catalog doesn't
+    /// support atomic updates of multiple tables. The files written for a commit that was rejected are deleted.
+    pub async fn commit(self) -> Result<()> {
+        if self.transactions.len() > 1 && !self.catalog.supports_multi_table_updates() {
+            return Err(anyhow!(
+                "The catalog doesn't support atomic updates of multiple tables."
+            ));
+        }
+        let mut tables = Vec::with_capacity(self.transactions.len());
+        let mut metadata_file_locations = Vec::with_capacity(self.transactions.len());
+        let commits = match self
+            .prepare(&mut tables, &mut metadata_file_locations)
+            .await
+        {
+            Ok(commits) => commits,
+            Err(err) => {
+                self.delete_files(&tables, &metadata_file_locations).await;
+                return Err(err);
+            }
+        };
+        // Only a rejected commit leaves the written files unreferenced. After other errors the catalog may have
+        // committed the new state, which references the files.
+        let relations = match self.catalog.clone().update_tables(commits).await {
+            Err(err) if err.is::<CommitConflict>() => {
+                self.delete_files(&tables, &metadata_file_locations).await;
+                return Err(err);
+            }
+            relations => relations?,
+        };
+        let mut transactions = self.transactions;
+        for relation in relations {
+            match relation {
+                Relation::Table(table) => {
+                    let transaction = table
+                        .identifier()
+                        .and_then(|identifier| transactions.remove(identifier))
+                        .ok_or_else(|| {
+                            anyhow!("Updating the tables returned a table that is not part of the transaction.")
+                        })?;
+                    transaction.finish(table);
+                }
+                Relation::View(_) => {
+                    return Err(anyhow!(
+                        "Updating the tables for the transaction didn't return a table."
+                    ))
+                }
+            }
+        }
+        Ok(())
+    }
+    /// Apply the transactions and write the metadata of the new table states. The applied tables and the written
+    /// metadata files are collected, so that they can be deleted if the commit fails.
+    async fn prepare(
+        &self,
+        tables: &mut Vec<Table>,
+        metadata_file_locations: &mut Vec<String>,
+    ) -> Result<Vec<TableCommit>> {
+        let mut commits = Vec::with_capacity(self.transactions.len());
+        for (identifier, transaction) in &self.transactions {
+            let table = transaction.apply().await?;
+            let written = write_metadata(
+                self.catalog.object_store(),
+                table.metadata().location(),
+                table.metadata().last_sequence_number(),
+                table.metadata(),
+            )
+            .await;
+            tables.push(table);
+            let metadata_file_location = written?;
+            metadata_file_locations.push(metadata_file_location.clone());
+            commits.push(TableCommit {
+                identifier: identifier.clone(),
+                metadata_file_location,
+                previous_metadata_file_location: transaction
+                    .table()
+                    .metadata_location()
+                    .to_string(),
+            });
+        }
+        Ok(commits)
+    }
+    /// Delete the manifest lists, manifests and metadata files written for a commit that failed. None of the tables
+    /// references them, failing to delete them doesn't affect the tables.
+    async fn delete_files(&self, tables: &[Table], metadata_file_locations: &[String]) {
+        for table in tables {
+            delete_uncommitted_files(table).await.ok();
+        }
+        for metadata_file_location in metadata_file_locations {
+            self.catalog
+                .object_store()
+                .delete(&util::strip_prefix(metadata_file_location).into())
+                .await
+                .ok();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::sync::Arc;
+
+    use futures::TryStreamExt;
+    use object_store::{memory::InMemory, path::Path, ObjectStore};
+
+    use crate::{
+        catalog::{
+            filesystem::FileSystemCatalog, identifier::Identifier, memory::MemoryCatalog,
+            relation::Relation, transaction::CatalogTransaction, Catalog,
+        },
+        table::{table_builder::TableBuilder, Table},
+    };
+
+    use crate::catalog::tests::schema;
+
+    async fn create(catalog: &Arc<dyn Catalog>, identifier: &str) -> Table {
+        TableBuilder::new_metastore_table(
+            "test/",
+            schema(),
+            Identifier::parse(identifier).unwrap(),
+            Arc::clone(catalog),
+        )
+        .unwrap()
+        .commit()
+        .await
+        .unwrap()
+    }
+
+    async fn metadata_location(catalog: &Arc<dyn Catalog>, identifier: &str) -> String {
+        catalog
+            .clone()
+            .load_table(&Identifier::parse(identifier).unwrap())
+            .await
+            .unwrap()
+            .metadata_location()
+            .to_string()
+    }
+
+    #[tokio::test]
+    async fn test_commit_multiple_tables() {
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let catalog: Arc<dyn Catalog> = Arc::new(MemoryCatalog::new(object_store));
+        let mut facts = create(&catalog, "sales.facts").await;
+        let mut dimensions = create(&catalog, "sales.dimensions").await;
+        let previous_facts_location = facts.metadata_location().to_string();
+
+        CatalogTransaction::new(Arc::clone(&catalog))
+            .with_transaction(
+                facts
+                    .new_transaction()
+                    .fast_append(vec!["test/sales/facts/data/file1.parquet".to_string()]),
+            )
+            .unwrap()
+            .with_transaction(
+                dimensions
+                    .new_transaction()
+                    .fast_append(vec!["test/sales/dimensions/data/file1.parquet".to_string()]),
+            )
+            .unwrap()
+            .commit()
+            .await
+            .unwrap();
+        assert_ne!(facts.metadata_location(), previous_facts_location);
+        assert_eq!(facts.manifests().len(), 1);
+        assert_eq!(dimensions.manifests().len(), 1);
+        assert_eq!(
+            metadata_location(&catalog, "sales.facts").await,
+            facts.metadata_location()
+        );
+        assert_eq!(
+            metadata_location(&catalog, "sales.dimensions").await,
+            dimensions.metadata_location()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_conflicting_commit() {
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let catalog: Arc<dyn Catalog> = Arc::new(MemoryCatalog::new(object_store));
+        let mut facts = create(&catalog, "sales.facts").await;
+        let mut dimensions = create(&catalog, "sales.dimensions").await;
+        let previous_facts_location = facts.metadata_location().to_string();
+
+        // Another writer changes one of the tables
+        match catalog
+            .clone()
+            .load_table(&Identifier::parse("sales.dimensions").unwrap())
+            .await
+            .unwrap()
+        {
+            Relation::Table(mut table) => table.new_transaction().commit().await.unwrap(),
+            Relation::View(_) => panic!("Relation is not a table."),
+        };
+
+        let result = CatalogTransaction::new(Arc::clone(&catalog))
+            .with_transaction(facts.new_transaction())
+            .unwrap()
+            .with_transaction(dimensions.new_transaction())
+            .unwrap()
+            .commit()
+            .await;
+        assert!(result.is_err());
+        // None of the tables changed
+        assert_eq!(facts.metadata_location(), previous_facts_location);
+        assert_eq!(
+            metadata_location(&catalog, "sales.facts").await,
+            previous_facts_location
+        );
+    }
+
+    async fn files(object_store: &Arc<dyn ObjectStore>) -> Vec<Path> {
+        let mut files: Vec<Path> = object_store
+            .list(None)
+            .await
+            .unwrap()
+            .map_ok(|object_meta| object_meta.location)
+            .try_collect()
+            .await
+            .unwrap();
+        files.sort();
+        files
+    }
+
+    #[tokio::test]
+    async fn test_conflicting_commit_cleanup() {
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let catalog: Arc<dyn Catalog> = Arc::new(MemoryCatalog::new(Arc::clone(&object_store)));
+        let mut facts = create(&catalog, "sales.facts").await;
+        let mut dimensions = create(&catalog, "sales.dimensions").await;
+
+        // Another writer changes one of the tables
+        match catalog
+            .clone()
+            .load_table(&Identifier::parse("sales.dimensions").unwrap())
+            .await
+            .unwrap()
+        {
+            Relation::Table(mut table) => table.new_transaction().commit().await.unwrap(),
+            Relation::View(_) => panic!("Relation is not a table."),
+        };
+        let previous_files = files(&object_store).await;
+
+        let result = CatalogTransaction::new(Arc::clone(&catalog))
+            .with_transaction(
+                facts
+                    .new_transaction()
+                    .fast_append(vec!["test/sales/facts/data/file1.parquet".to_string()]),
+            )
+            .unwrap()
+            .with_transaction(
+                dimensions
+                    .new_transaction()
+                    .fast_append(vec!["test/sales/dimensions/data/file1.parquet".to_string()]),
+            )
+            .unwrap()
+            .commit()
+            .await;
+        assert!(result.is_err());
+        // The metadata files, manifest lists and manifests written for the commit were deleted
+        assert_eq!(files(&object_store).await, previous_files);
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_table() {
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let catalog: Arc<dyn Catalog> = Arc::new(MemoryCatalog::new(object_store));
+        let mut first = create(&catalog, "sales.facts").await;
+        let mut second = first.clone();
+
+        assert!(CatalogTransaction::new(Arc::clone(&catalog))
+            .with_transaction(first.new_transaction())
+            .unwrap()
+            .with_transaction(second.new_transaction())
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_table_of_other_catalog() {
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let catalog: Arc<dyn Catalog> = Arc::new(MemoryCatalog::new(Arc::clone(&object_store)));
+        let other_catalog: Arc<dyn Catalog> = Arc::new(MemoryCatalog::new(object_store));
+        let mut facts = create(&other_catalog, "sales.facts").await;
+
+        assert!(CatalogTransaction::new(Arc::clone(&catalog))
+            .with_transaction(facts.new_transaction())
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_unsupported_catalog() {
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let catalog: Arc<dyn Catalog> =
+            Arc::new(FileSystemCatalog::new("test", Arc::clone(&object_store)));
+        let mut facts = create(&catalog, "sales.facts").await;
+        let mut dimensions = create(&catalog, "sales.dimensions").await;
+        let previous_files = files(&object_store).await;
+
+        let result = CatalogTransaction::new(Arc::clone(&catalog))
+            .with_transaction(facts.new_transaction())
+            .unwrap()
+            .with_transaction(dimensions.new_transaction())
+            .unwrap()
+            .commit()
+            .await;
+        assert!(result.is_err());
+        // Nothing was written for a commit that can't succeed
+        assert_eq!(files(&object_store).await, previous_files);
+    }
+}
@@ -0,0 +1,330 @@
+/*!
+Defines the [ManifestEvaluator] that decides with the partition summaries of the manifest list whether a manifest
+might contain rows matching a row filter.
+*/
+
+use anyhow::Result;
+
+use crate::model::{
+    bytes::bytes_to_value,
+    manifest_list::{FieldSummary, ManifestFile},
+    partition::PartitionField,
+    schema::PrimitiveType,
+};
+
+use super::{
+    compare_prefix, projection::inclusive_projection, BinaryOperator, BoundExpression,
+    BoundReference, Expression, Predicate, SetOperator, UnaryOperator,
+};
+
+/// Evaluates a row filter against the partition summaries of the manifests written with one partition spec.
+pub struct ManifestEvaluator {
+    /// Filter projected on the partition values
+    expression: BoundExpression,
+    /// Ids of the partition fields in the order of the field summaries
+    field_ids: Vec<i32>,
+}
+
+impl ManifestEvaluator {
+    /// Create an evaluator for manifests written with the partition spec. The filter has to be bound to the table schema.
+    pub fn new(filter: &BoundExpression, spec: &[PartitionField]) -> Result<Self> {
+        Ok(ManifestEvaluator {
+            expression: inclusive_projection(filter, spec)?,
+            field_ids: spec.iter().map(|field| field.field_id).collect(),
+        })
+    }
+    /// Check if the manifest might contain rows matching the filter. Returns false only if the partition summaries
+    /// prove that no row of the manifest matches.
+    pub fn eval(&self, manifest: &ManifestFile) -> Result<bool> {
+        match manifest.partitions() {
+            Some(summaries) => self.eval_expression(&self.expression, summaries),
+            None => Ok(true),
+        }
+    }
+    fn eval_expression(
+        &self,
+        expression: &BoundExpression,
+        summaries: &[FieldSummary],
+    ) -> Result<bool> {
+        match expression {
+            Expression::True => Ok(true),
+            Expression::False => Ok(false),
+            Expression::And(left, right) => {
+                Ok(self.eval_expression(left, summaries)?
+                    && self.eval_expression(right, summaries)?)
+            }
+            Expression::Or(left, right) => {
+                Ok(self.eval_expression(left, summaries)?
+                    || self.eval_expression(right, summaries)?)
+            }
+            // The projection doesn't contain negations, a negation is never used to exclude a manifest
+            Expression::Not(_) => Ok(true),
+            Expression::Predicate(predicate) => {
+                match self
+                    .field_ids
+                    .iter()
+                    .position(|id| *id == predicate.term().field_id)
+                    .and_then(|position| summaries.get(position))
+                {
+                    Some(summary) => might_match(predicate, summary),
+                    None => Ok(true),
+                }
+            }
+        }
+    }
+}
+
+/// Check if a partition field with the summary might contain values matching the predicate.
+fn might_match(predicate: &Predicate<BoundReference>, summary: &FieldSummary) -> Result<bool> {
+    let field_type = &predicate.term().field_type;
+    let lower = summary
+        .lower_bound
+        .as_ref()
+        .map(|bytes| bytes_to_value(bytes, field_type))
+        .transpose()?;
+    let upper = summary
+        .upper_bound
+        .as_ref()
+        .map(|bytes| bytes_to_value(bytes, field_type))
+        .transpose()?;
+    let floating = matches!(field_type, PrimitiveType::Float | PrimitiveType::Double);
+    // Bounds are only written for values that are neither null nor NaN
+    let only_nulls = summary.contains_null
+        && lower.is_none()
+        && (!floating || summary.contains_nan == Some(false));
+    let only_nans = summary.contains_nan == Some(true) && !summary.contains_null && lower.is_none();
+    match predicate {
+        Predicate::Unary { op, .. } => Ok(match op {
+            UnaryOperator::IsNull => summary.contains_null,
+            UnaryOperator::NotNull => !only_nulls,
+            UnaryOperator::IsNan => summary.contains_nan != Some(false),
+            UnaryOperator::NotNan => !only_nans,
+        }),
+        Predicate::Binary { op, literal, .. } => {
+            let (lower, upper) = match (lower, upper) {
+                (Some(lower), Some(upper)) => (lower, upper),
+                // Only null or NaN values, which never match a comparison
+                _ => {
+                    return Ok(matches!(
+                        op,
+                        BinaryOperator::NotEq | BinaryOperator::NotStartsWith
+                    ))
+                }
+            };
+            Ok(match op {
+                BinaryOperator::Lt => lower < *literal,
+                BinaryOperator::LtEq => lower <= *literal,
+                BinaryOperator::Gt => upper > *literal,
+                BinaryOperator::GtEq => upper >= *literal,
+                BinaryOperator::Eq => lower <= *literal && upper >= *literal,
+                BinaryOperator::NotEq => true,
+                BinaryOperator::StartsWith => {
+                    compare_prefix(&lower, literal) != Some(std::cmp::Ordering::Greater)
+                        && compare_prefix(&upper, literal) != Some(std::cmp::Ordering::Less)
+                }
+                // All values start with the prefix if both bounds do
+                BinaryOperator::NotStartsWith => {
+                    summary.contains_null
+                        || compare_prefix(&lower, literal) != Some(std::cmp::Ordering::Equal)
+                        || compare_prefix(&upper, literal) != Some(std::cmp::Ordering::Equal)
+                }
+            })
+        }
+        Predicate::Set { op, literals, .. } => match (op, lower, upper) {
+            (SetOperator::In, Some(lower), Some(upper)) => Ok(literals
+                .iter()
+                .any(|literal| lower <= *literal && upper >= *literal)),
+            (SetOperator::In, _, _) => Ok(false),
+            (SetOperator::NotIn, _, _) => Ok(true),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use serde_bytes::ByteBuf;
+
+    use crate::{
+        expr::Reference,
+        model::{
+            bytes::value_to_bytes,
+            manifest::Content,
+            manifest_list::{FieldSummary, ManifestFile, ManifestFileV2},
+            partition::{PartitionField, Transform},
+            schema::{AllType, PrimitiveType, SchemaStruct, StructField},
+            types::Value,
+        },
+    };
+
+    use super::ManifestEvaluator;
+
+    fn schema() -> SchemaStruct {
+        SchemaStruct {
+            fields: vec![
+                StructField {
+                    id: 1,
+                    name: "id".to_string(),
+                    required: true,
+                    field_type: AllType::Primitive(PrimitiveType::Long),
+                    doc: None,
+                },
+                StructField {
+                    id: 2,
+                    name: "region".to_string(),
+                    required: false,
+                    field_type: AllType::Primitive(PrimitiveType::String),
+                    doc: None,
+                },
+            ],
+        }
+    }
+
+    fn spec() -> Vec<PartitionField> {
+        vec![PartitionField {
+            source_id: 2,
+            field_id: 1000,
+            name: "region".to_string(),
+            transform: Transform::Identity,
+        }]
+    }
+
+    fn manifest(summary: FieldSummary) -> ManifestFile {
+        ManifestFile::V2(ManifestFileV2 {
+            manifest_path: "test/metadata/manifest.avro".to_string(),
+            manifest_length: 1200,
+            partition_spec_id: 0,
+            content: Content::Data,
+            sequence_number: 1,
+            min_sequence_number: 1,
+            added_snapshot_id: 1,
+            added_files_count: 1,
+            existing_files_count: 0,
+            deleted_files_count: 0,
+            added_rows_count: 100,
+            existing_rows_count: 0,
+            deleted_rows_count: 0,
+            partitions: Some(vec![summary]),
+            key_metadata: None,
+        })
+    }
+
+    fn summary(lower: &str, upper: &str, contains_null: bool) -> FieldSummary {
+        FieldSummary {
+            contains_null,
+            contains_nan: None,
+            lower_bound: Some(ByteBuf::from(
+                value_to_bytes(&Value::String(lower.to_string())).unwrap(),
+            )),
+            upper_bound: Some(ByteBuf::from(
+                value_to_bytes(&Value::String(upper.to_string())).unwrap(),
+            )),
+        }
+    }
+
+    fn eval(filter: crate::expr::UnboundExpression, manifest: &ManifestFile) -> bool {
+        ManifestEvaluator::new(&filter.bind(&schema()).unwrap(), &spec())
+            .unwrap()
+            .eval(manifest)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_comparisons() {
+        let manifest = manifest(summary("emea", "na", false));
+        let region = || Reference::new("region");
+        assert!(eval(
+            region().equal(Value::String("emea".to_string())),
+            &manifest
+        ));
+        assert!(!eval(
+            region().equal(Value::String("apac".to_string())),
+            &manifest
+        ));
+        assert!(!eval(
+            region().less_than(Value::String("emea".to_string())),
+            &manifest
+        ));
+        assert!(eval(
+            region().less_than_or_eq(Value::String("emea".to_string())),
+            &manifest
+        ));
+        assert!(!eval(
+            region().greater_than(Value::String("na".to_string())),
+            &manifest
+        ));
+        assert!(eval(
+            region().greater_than_or_eq(Value::String("na".to_string())),
+            &manifest
+        ));
+        assert!(!eval(
+            region().is_in(vec![
+                Value::String("apac".to_string()),
+                Value::String("sa".to_string())
+            ]),
+            &manifest
+        ));
+        assert!(eval(
+            region().is_in(vec![
+                Value::String("apac".to_string()),
+                Value::String("latam".to_string())
+            ]),
+            &manifest
+        ));
+        assert!(eval(
+            !region().equal(Value::String("apac".to_string())),
+            &manifest
+        ));
+        assert!(eval(region().starts_with("e"), &manifest));
+        assert!(!eval(region().starts_with("s"), &manifest));
+        // Predicates on columns that are not partitioned can't exclude manifests
+        assert!(eval(
+            Reference::new("id").equal(Value::LongInt(1)),
+            &manifest
+        ));
+    }
+
+    #[test]
+    fn test_nulls() {
+        let region = || Reference::new("region");
+        let without_nulls = manifest(summary("emea", "na", false));
+        assert!(!eval(region().is_null(), &without_nulls));
+        assert!(eval(region().not_null(), &without_nulls));
+        let only_nulls = manifest(FieldSummary {
+            contains_null: true,
+            contains_nan: None,
+            lower_bound: None,
+            upper_bound: None,
+        });
+        assert!(eval(region().is_null(), &only_nulls));
+        assert!(!eval(region().not_null(), &only_nulls));
+        assert!(!eval(
+            region().equal(Value::String("emea".to_string())),
+            &only_nulls
+        ));
+        assert!(eval(!region().starts_with("e"), &only_nulls));
+        assert!(!eval(
+            region()
+                .equal(Value::String("emea".to_string()))
+                .or(region().greater_than(Value::String("a".to_string()))),
+            &only_nulls
+        ));
+    }
+
+    #[test]
+    fn test_not_starts_with() {
+        let region = || Reference::new("region");
+        assert!(!eval(
+            region().not_starts_with("em"),
+            &manifest(summary("emea", "emeb", false))
+        ));
+        assert!(eval(
+            region().not_starts_with("em"),
+            &manifest(summary("emea", "emeb", true))
+        ));
+        assert!(eval(
+            region().not_starts_with("em"),
+            &manifest(summary("emea", "na", false))
+        ));
+    }
+}
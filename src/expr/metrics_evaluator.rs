@@ -0,0 +1,346 @@
+/*!
+Defines the [MetricsEvaluator] that decides with the column metrics of a manifest entry whether a data file might
+contain rows matching a row filter.
+*/
+
+use std::cmp::Ordering;
+
+use anyhow::Result;
+
+use serde_bytes::ByteBuf;
+
+use crate::model::{
+    bytes::bytes_to_value,
+    manifest::{AvroMap, ManifestEntry},
+    types::Value,
+};
+
+use super::{
+    compare_prefix, is_nan, BinaryOperator, BoundExpression, BoundReference, Expression, Predicate,
+    SetOperator, UnaryOperator,
+};
+
+/// Evaluates a row filter against the column metrics of data files. The evaluation is inclusive: a file is only
+/// excluded if its metrics prove that no row matches.
+pub struct MetricsEvaluator {
+    expression: BoundExpression,
+}
+
+impl MetricsEvaluator {
+    /// Create an evaluator for the filter. The filter has to be bound to the table schema.
+    pub fn new(filter: &BoundExpression) -> Self {
+        MetricsEvaluator {
+            expression: filter.clone().rewrite_not(),
+        }
+    }
+    /// Check if the data file of the manifest entry might contain rows matching the filter.
+    pub fn eval(&self, entry: &ManifestEntry) -> Result<bool> {
+        // Files without rows never match. Files with an unknown record count have a negative count.
+        if entry.record_count() == 0 {
+            return Ok(false);
+        }
+        eval_expression(&self.expression, entry)
+    }
+}
+
+fn eval_expression(expression: &BoundExpression, entry: &ManifestEntry) -> Result<bool> {
+    match expression {
+        Expression::True => Ok(true),
+        Expression::False => Ok(false),
+        Expression::And(left, right) => {
+            Ok(eval_expression(left, entry)? && eval_expression(right, entry)?)
+        }
+        Expression::Or(left, right) => {
+            Ok(eval_expression(left, entry)? || eval_expression(right, entry)?)
+        }
+        // Negations were pushed down to the predicates when the evaluator was created
+        Expression::Not(_) => Ok(true),
+        Expression::Predicate(predicate) => might_match(predicate, entry),
+    }
+}
+
+/// Metrics of a single column of a data file
+struct ColumnMetrics {
+    value_count: Option<i64>,
+    null_count: Option<i64>,
+    nan_count: Option<i64>,
+    lower: Option<Value>,
+    upper: Option<Value>,
+}
+
+impl ColumnMetrics {
+    fn new(term: &BoundReference, entry: &ManifestEntry) -> Result<Self> {
+        let id = term.field_id;
+        let count = |counts: &Option<AvroMap<i64>>| {
+            counts.as_ref().and_then(|counts| counts.get(&id).copied())
+        };
+        let bound = |bounds: &Option<AvroMap<ByteBuf>>| {
+            bounds
+                .as_ref()
+                .and_then(|bounds| bounds.get(&id))
+                .map(|bytes| bytes_to_value(bytes, &term.field_type))
+                .transpose()
+        };
+        Ok(ColumnMetrics {
+            value_count: count(entry.value_counts()),
+            null_count: count(entry.null_value_counts()),
+            nan_count: count(entry.nan_value_counts()),
+            lower: bound(entry.lower_bounds())?,
+            upper: bound(entry.upper_bounds())?,
+        })
+    }
+    /// The column only contains null values
+    fn only_nulls(&self) -> bool {
+        matches!((self.value_count, self.null_count), (Some(values), Some(nulls)) if values == nulls)
+    }
+    /// The column only contains NaN values
+    fn only_nans(&self) -> bool {
+        matches!((self.value_count, self.nan_count), (Some(values), Some(nans)) if values == nans)
+    }
+    /// Lower and upper bound of the column. Bounds that are NaN don't order the values and are ignored.
+    fn ordered_bounds(&self) -> (Option<&Value>, Option<&Value>) {
+        (
+            self.lower.as_ref().filter(|bound| !is_nan(bound)),
+            self.upper.as_ref().filter(|bound| !is_nan(bound)),
+        )
+    }
+}
+
+/// Check if a data file with the metrics might contain values matching the predicate.
+fn might_match(predicate: &Predicate<BoundReference>, entry: &ManifestEntry) -> Result<bool> {
+    let metrics = ColumnMetrics::new(predicate.term(), entry)?;
+    match predicate {
+        Predicate::Unary { op, .. } => Ok(match op {
+            UnaryOperator::IsNull => metrics.null_count != Some(0),
+            UnaryOperator::NotNull => !metrics.only_nulls(),
+            UnaryOperator::IsNan => metrics.nan_count != Some(0) && !metrics.only_nulls(),
+            UnaryOperator::NotNan => !metrics.only_nans(),
+        }),
+        // Comparisons never match null or NaN values
+        Predicate::Binary { .. } | Predicate::Set { .. }
+            if (metrics.only_nulls() || metrics.only_nans()) && !is_negated(predicate) =>
+        {
+            Ok(false)
+        }
+        Predicate::Binary { op, literal, .. } => {
+            let (lower, upper) = metrics.ordered_bounds();
+            Ok(match op {
+                BinaryOperator::Lt => lower.is_none_or(|lower| lower < literal),
+                BinaryOperator::LtEq => lower.is_none_or(|lower| lower <= literal),
+                BinaryOperator::Gt => upper.is_none_or(|upper| upper > literal),
+                BinaryOperator::GtEq => upper.is_none_or(|upper| upper >= literal),
+                BinaryOperator::Eq => {
+                    lower.is_none_or(|lower| lower <= literal)
+                        && upper.is_none_or(|upper| upper >= literal)
+                }
+                BinaryOperator::NotEq => true,
+                BinaryOperator::StartsWith => {
+                    lower.is_none_or(|lower| {
+                        compare_prefix(lower, literal) != Some(Ordering::Greater)
+                    }) && upper
+                        .is_none_or(|upper| compare_prefix(upper, literal) != Some(Ordering::Less))
+                }
+                // All values start with the prefix if there are no nulls and both bounds start with it
+                BinaryOperator::NotStartsWith => {
+                    metrics.null_count != Some(0)
+                        || !matches!(
+                            (lower, upper),
+                            (Some(lower), Some(upper))
+                                if compare_prefix(lower, literal) == Some(Ordering::Equal)
+                                    && compare_prefix(upper, literal) == Some(Ordering::Equal)
+                        )
+                }
+            })
+        }
+        Predicate::Set { op, literals, .. } => match op {
+            SetOperator::In => {
+                let (lower, upper) = metrics.ordered_bounds();
+                Ok(literals.iter().any(|literal| {
+                    lower.is_none_or(|lower| lower <= literal)
+                        && upper.is_none_or(|upper| upper >= literal)
+                }))
+            }
+            SetOperator::NotIn => Ok(true),
+        },
+    }
+}
+
+/// Check if the predicate is the negation of a comparison, which matches null and NaN values.
+fn is_negated(predicate: &Predicate<BoundReference>) -> bool {
+    matches!(
+        predicate,
+        Predicate::Binary {
+            op: BinaryOperator::NotEq | BinaryOperator::NotStartsWith,
+            ..
+        } | Predicate::Set {
+            op: SetOperator::NotIn,
+            ..
+        }
+    )
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::collections::HashMap;
+
+    use serde_bytes::ByteBuf;
+
+    use crate::{
+        expr::{Reference, UnboundExpression},
+        model::{
+            bytes::value_to_bytes,
+            manifest::{
+                AvroMap, Content, DataFileV2, FileFormat, ManifestEntry, ManifestEntryV2,
+                PartitionValues, Status,
+            },
+            schema::{AllType, PrimitiveType, SchemaStruct, StructField},
+            types::Value,
+        },
+    };
+
+    use super::MetricsEvaluator;
+
+    fn schema() -> SchemaStruct {
+        SchemaStruct {
+            fields: vec![
+                StructField {
+                    id: 1,
+                    name: "id".to_string(),
+                    required: true,
+                    field_type: AllType::Primitive(PrimitiveType::Int),
+                    doc: None,
+                },
+                StructField {
+                    id: 2,
+                    name: "name".to_string(),
+                    required: false,
+                    field_type: AllType::Primitive(PrimitiveType::String),
+                    doc: None,
+                },
+                StructField {
+                    id: 3,
+                    name: "price".to_string(),
+                    required: false,
+                    field_type: AllType::Primitive(PrimitiveType::Double),
+                    doc: None,
+                },
+            ],
+        }
+    }
+
+    fn bounds(values: Vec<(i32, Value)>) -> Option<AvroMap<ByteBuf>> {
+        Some(AvroMap::from(HashMap::from_iter(values.into_iter().map(
+            |(id, value)| (id, ByteBuf::from(value_to_bytes(&value).unwrap())),
+        ))))
+    }
+
+    /// File with ids between 30 and 79, names between "aaa" and "abc" and only null prices
+    fn entry() -> ManifestEntry {
+        ManifestEntry::V2(ManifestEntryV2 {
+            status: Status::Added,
+            snapshot_id: Some(1),
+            sequence_number: Some(1),
+            data_file: DataFileV2 {
+                content: Content::Data,
+                file_path: "test/data/file1.parquet".to_string(),
+                file_format: FileFormat::Parquet,
+                partition: PartitionValues::from_iter(vec![]),
+                record_count: 50,
+                file_size_in_bytes: 1200,
+                column_sizes: None,
+                value_counts: Some(AvroMap::from(HashMap::from_iter(vec![
+                    (1, 50),
+                    (2, 50),
+                    (3, 50),
+                ]))),
+                null_value_counts: Some(AvroMap::from(HashMap::from_iter(vec![
+                    (1, 0),
+                    (2, 10),
+                    (3, 50),
+                ]))),
+                nan_value_counts: Some(AvroMap::from(HashMap::from_iter(vec![(3, 0)]))),
+                distinct_counts: None,
+                lower_bounds: bounds(vec![
+                    (1, Value::Int(30)),
+                    (2, Value::String("aaa".to_string())),
+                ]),
+                upper_bounds: bounds(vec![
+                    (1, Value::Int(79)),
+                    (2, Value::String("abc".to_string())),
+                ]),
+                key_metadata: None,
+                split_offsets: None,
+                equality_ids: None,
+                sort_order_id: None,
+            },
+        })
+    }
+
+    fn eval(filter: UnboundExpression) -> bool {
+        MetricsEvaluator::new(&filter.bind(&schema()).unwrap())
+            .eval(&entry())
+            .unwrap()
+    }
+
+    #[test]
+    fn test_comparisons() {
+        let id = || Reference::new("id");
+        assert!(!eval(id().less_than(Value::Int(30))));
+        assert!(eval(id().less_than_or_eq(Value::Int(30))));
+        assert!(!eval(id().greater_than(Value::Int(79))));
+        assert!(eval(id().greater_than_or_eq(Value::Int(79))));
+        assert!(!eval(id().equal(Value::Int(80))));
+        assert!(eval(id().equal(Value::Int(50))));
+        assert!(eval(id().not_equal(Value::Int(50))));
+        assert!(!eval(id().is_in(vec![Value::Int(1), Value::Int(90)])));
+        assert!(eval(id().is_in(vec![Value::Int(1), Value::Int(50)])));
+        assert!(!eval(!id().greater_than_or_eq(Value::Int(30))));
+        assert!(eval(
+            id().less_than(Value::Int(30))
+                .or(id().greater_than(Value::Int(70)))
+        ));
+    }
+
+    #[test]
+    fn test_promoted_column() {
+        // The bounds of the file were written before the id column was promoted from int to long
+        let mut schema = schema();
+        schema.fields[0].field_type = AllType::Primitive(PrimitiveType::Long);
+        let eval = |filter: UnboundExpression| {
+            MetricsEvaluator::new(&filter.bind(&schema).unwrap())
+                .eval(&entry())
+                .unwrap()
+        };
+        assert!(!eval(Reference::new("id").less_than(Value::LongInt(30))));
+        assert!(eval(Reference::new("id").equal(Value::LongInt(50))));
+    }
+
+    #[test]
+    fn test_strings() {
+        let name = || Reference::new("name");
+        assert!(eval(name().starts_with("ab")));
+        assert!(eval(name().starts_with("a")));
+        assert!(!eval(name().starts_with("b")));
+        assert!(!eval(name().starts_with("aa0")));
+        // The file contains nulls, which don't start with the prefix
+        assert!(eval(name().not_starts_with("a")));
+    }
+
+    #[test]
+    fn test_nulls_and_nans() {
+        assert!(!eval(Reference::new("id").is_null()));
+        assert!(eval(Reference::new("id").not_null()));
+        assert!(eval(Reference::new("name").is_null()));
+        assert!(eval(Reference::new("price").is_null()));
+        assert!(!eval(Reference::new("price").not_null()));
+        assert!(!eval(Reference::new("price").is_nan()));
+        assert!(eval(Reference::new("price").not_nan()));
+        assert!(!eval(
+            Reference::new("price").greater_than(Value::LongFloat(1.0))
+        ));
+        assert!(eval(
+            Reference::new("price").not_in(vec![Value::LongFloat(1.0), Value::LongFloat(2.0)])
+        ));
+    }
+}
@@ -0,0 +1,706 @@
+/*!
+Defines the [Expression]s used to filter the rows of a table.
+
+Expressions are created with unbound [Reference]s that refer to columns by name. Before they can be evaluated, they
+have to be bound to the schema of the table with [Expression::bind], which resolves the field ids of the columns and
+checks that the literals match the column types.
+
+```rust
+use iceberg_rs::expr::Reference;
+use iceberg_rs::model::types::Value;
+
+let filter = Reference::new("id")
+    .greater_than_or_eq(Value::Int(100))
+    .and(Reference::new("name").starts_with("ice"));
+```
+*/
+
+use std::ops::Not;
+
+use anyhow::{anyhow, Result};
+
+use crate::model::{
+    schema::{AllType, PrimitiveType, SchemaStruct},
+    types::Value,
+};
+
+pub mod manifest_evaluator;
+pub mod metrics_evaluator;
+pub mod projection;
+pub mod residual;
+
+/// Reference to a column by name. Fields of nested structs are referred to by joining the names with a dot.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Reference {
+    name: String,
+}
+
+/// Reference to a column that is resolved against a schema.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BoundReference {
+    /// Id of the referenced field
+    pub field_id: i32,
+    /// Name of the referenced field
+    pub name: String,
+    /// Type of the referenced field
+    pub field_type: PrimitiveType,
+    /// Whether the field can't contain null values. Fields of nested structs are only required if all enclosing
+    /// structs are required.
+    pub required: bool,
+}
+
+/// Operators of predicates that test a column without a literal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnaryOperator {
+    /// Column is null
+    IsNull,
+    /// Column is not null
+    NotNull,
+    /// Column is NaN
+    IsNan,
+    /// Column is not NaN
+    NotNan,
+}
+
+/// Operators of predicates that compare a column to a single literal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinaryOperator {
+    /// Column is less than the literal
+    Lt,
+    /// Column is less than or equal to the literal
+    LtEq,
+    /// Column is greater than the literal
+    Gt,
+    /// Column is greater than or equal to the literal
+    GtEq,
+    /// Column equals the literal
+    Eq,
+    /// Column doesn't equal the literal
+    NotEq,
+    /// Column starts with the literal string
+    StartsWith,
+    /// Column doesn't start with the literal string
+    NotStartsWith,
+}
+
+/// Operators of predicates that compare a column to a set of literals.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SetOperator {
+    /// Column equals one of the literals
+    In,
+    /// Column equals none of the literals
+    NotIn,
+}
+
+/// Predicate on a single column. The column is either a [Reference] or a [BoundReference].
+#[derive(Debug, Clone, PartialEq)]
+pub enum Predicate<T> {
+    /// Test without a literal
+    Unary {
+        /// Operator
+        op: UnaryOperator,
+        /// Column
+        term: T,
+    },
+    /// Comparison with a literal
+    Binary {
+        /// Operator
+        op: BinaryOperator,
+        /// Column
+        term: T,
+        /// Literal
+        literal: Value,
+    },
+    /// Comparison with a set of literals
+    Set {
+        /// Operator
+        op: SetOperator,
+        /// Column
+        term: T,
+        /// Literals
+        literals: Vec<Value>,
+    },
+}
+
+/// Boolean expression over the columns of a table.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expression<T> {
+    /// Always true
+    True,
+    /// Always false
+    False,
+    /// Both expressions are true
+    And(Box<Expression<T>>, Box<Expression<T>>),
+    /// One of the expressions is true
+    Or(Box<Expression<T>>, Box<Expression<T>>),
+    /// The expression is false
+    Not(Box<Expression<T>>),
+    /// Predicate on a column
+    Predicate(Predicate<T>),
+}
+
+/// Expression whose columns are referred to by name
+pub type UnboundExpression = Expression<Reference>;
+/// Expression whose columns are resolved against a schema
+pub type BoundExpression = Expression<BoundReference>;
+
+impl Reference {
+    /// Create a reference to the column with the given name.
+    pub fn new(name: &str) -> Self {
+        Reference {
+            name: name.to_string(),
+        }
+    }
+    /// Get the name of the referenced column.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    fn unary(self, op: UnaryOperator) -> UnboundExpression {
+        Expression::Predicate(Predicate::Unary { op, term: self })
+    }
+    fn binary(self, op: BinaryOperator, literal: Value) -> UnboundExpression {
+        Expression::Predicate(Predicate::Binary {
+            op,
+            term: self,
+            literal,
+        })
+    }
+    /// Column is null
+    pub fn is_null(self) -> UnboundExpression {
+        self.unary(UnaryOperator::IsNull)
+    }
+    /// Column is not null
+    pub fn not_null(self) -> UnboundExpression {
+        self.unary(UnaryOperator::NotNull)
+    }
+    /// Column is NaN
+    pub fn is_nan(self) -> UnboundExpression {
+        self.unary(UnaryOperator::IsNan)
+    }
+    /// Column is not NaN
+    pub fn not_nan(self) -> UnboundExpression {
+        self.unary(UnaryOperator::NotNan)
+    }
+    /// Column is less than the literal
+    pub fn less_than(self, literal: Value) -> UnboundExpression {
+        self.binary(BinaryOperator::Lt, literal)
+    }
+    /// Column is less than or equal to the literal
+    pub fn less_than_or_eq(self, literal: Value) -> UnboundExpression {
+        self.binary(BinaryOperator::LtEq, literal)
+    }
+    /// Column is greater than the literal
+    pub fn greater_than(self, literal: Value) -> UnboundExpression {
+        self.binary(BinaryOperator::Gt, literal)
+    }
+    /// Column is greater than or equal to the literal
+    pub fn greater_than_or_eq(self, literal: Value) -> UnboundExpression {
+        self.binary(BinaryOperator::GtEq, literal)
+    }
+    /// Column equals the literal
+    pub fn equal(self, literal: Value) -> UnboundExpression {
+        self.binary(BinaryOperator::Eq, literal)
+    }
+    /// Column doesn't equal the literal
+    pub fn not_equal(self, literal: Value) -> UnboundExpression {
+        self.binary(BinaryOperator::NotEq, literal)
+    }
+    /// Column starts with the prefix
+    pub fn starts_with(self, prefix: &str) -> UnboundExpression {
+        self.binary(
+            BinaryOperator::StartsWith,
+            Value::String(prefix.to_string()),
+        )
+    }
+    /// Column doesn't start with the prefix
+    pub fn not_starts_with(self, prefix: &str) -> UnboundExpression {
+        self.binary(
+            BinaryOperator::NotStartsWith,
+            Value::String(prefix.to_string()),
+        )
+    }
+    /// Column equals one of the literals
+    pub fn is_in(self, literals: Vec<Value>) -> UnboundExpression {
+        Expression::Predicate(Predicate::Set {
+            op: SetOperator::In,
+            term: self,
+            literals,
+        })
+    }
+    /// Column equals none of the literals
+    pub fn not_in(self, literals: Vec<Value>) -> UnboundExpression {
+        Expression::Predicate(Predicate::Set {
+            op: SetOperator::NotIn,
+            term: self,
+            literals,
+        })
+    }
+}
+
+impl<T> Expression<T> {
+    /// Both expressions are true. Constant expressions are simplified.
+    pub fn and(self, other: Expression<T>) -> Expression<T> {
+        match (self, other) {
+            (Expression::False, _) | (_, Expression::False) => Expression::False,
+            (Expression::True, other) | (other, Expression::True) => other,
+            (left, right) => Expression::And(Box::new(left), Box::new(right)),
+        }
+    }
+    /// One of the expressions is true. Constant expressions are simplified.
+    pub fn or(self, other: Expression<T>) -> Expression<T> {
+        match (self, other) {
+            (Expression::True, _) | (_, Expression::True) => Expression::True,
+            (Expression::False, other) | (other, Expression::False) => other,
+            (left, right) => Expression::Or(Box::new(left), Box::new(right)),
+        }
+    }
+}
+
+impl<T> Expression<T> {
+    /// Push all negations down to the predicates, so that the expression doesn't contain [Expression::Not] anymore.
+    pub fn rewrite_not(self) -> Expression<T> {
+        match self {
+            Expression::And(left, right) => left.rewrite_not().and(right.rewrite_not()),
+            Expression::Or(left, right) => left.rewrite_not().or(right.rewrite_not()),
+            Expression::Not(expression) => match *expression {
+                Expression::True => Expression::False,
+                Expression::False => Expression::True,
+                Expression::And(left, right) => (!*left).rewrite_not().or((!*right).rewrite_not()),
+                Expression::Or(left, right) => (!*left).rewrite_not().and((!*right).rewrite_not()),
+                Expression::Not(expression) => expression.rewrite_not(),
+                Expression::Predicate(predicate) => Expression::Predicate(predicate.negate()),
+            },
+            expression => expression,
+        }
+    }
+}
+
+impl<T> Not for Expression<T> {
+    type Output = Expression<T>;
+
+    /// The expression is false. Constant and double negated expressions are simplified.
+    fn not(self) -> Self::Output {
+        match self {
+            Expression::True => Expression::False,
+            Expression::False => Expression::True,
+            Expression::Not(expression) => *expression,
+            expression => Expression::Not(Box::new(expression)),
+        }
+    }
+}
+
+impl UnboundExpression {
+    /// Bind the expression to the schema. Resolves the field ids of the referenced columns and converts the literals
+    /// to the column types. Fails if a column doesn't exist, isn't a primitive or doesn't match its literals.
+    pub fn bind(self, schema: &SchemaStruct) -> Result<BoundExpression> {
+        self.bind_columns(schema, true)
+    }
+    /// Bind the expression to the schema like [UnboundExpression::bind], but match column names regardless of their
+    /// case.
+    pub fn bind_case_insensitive(self, schema: &SchemaStruct) -> Result<BoundExpression> {
+        self.bind_columns(schema, false)
+    }
+    fn bind_columns(self, schema: &SchemaStruct, case_sensitive: bool) -> Result<BoundExpression> {
+        match self {
+            Expression::True => Ok(Expression::True),
+            Expression::False => Ok(Expression::False),
+            Expression::And(left, right) => Ok(left
+                .bind_columns(schema, case_sensitive)?
+                .and(right.bind_columns(schema, case_sensitive)?)),
+            Expression::Or(left, right) => Ok(left
+                .bind_columns(schema, case_sensitive)?
+                .or(right.bind_columns(schema, case_sensitive)?)),
+            Expression::Not(expression) => Ok(!expression.bind_columns(schema, case_sensitive)?),
+            Expression::Predicate(predicate) => predicate.bind(schema, case_sensitive),
+        }
+    }
+}
+
+impl<T> Predicate<T> {
+    /// Get the column of the predicate.
+    pub fn term(&self) -> &T {
+        match self {
+            Predicate::Unary { term, .. }
+            | Predicate::Binary { term, .. }
+            | Predicate::Set { term, .. } => term,
+        }
+    }
+    /// Get the predicate that is true exactly when this predicate is false.
+    pub fn negate(self) -> Predicate<T> {
+        match self {
+            Predicate::Unary { op, term } => Predicate::Unary {
+                op: match op {
+                    UnaryOperator::IsNull => UnaryOperator::NotNull,
+                    UnaryOperator::NotNull => UnaryOperator::IsNull,
+                    UnaryOperator::IsNan => UnaryOperator::NotNan,
+                    UnaryOperator::NotNan => UnaryOperator::IsNan,
+                },
+                term,
+            },
+            Predicate::Binary { op, term, literal } => Predicate::Binary {
+                op: match op {
+                    BinaryOperator::Lt => BinaryOperator::GtEq,
+                    BinaryOperator::LtEq => BinaryOperator::Gt,
+                    BinaryOperator::Gt => BinaryOperator::LtEq,
+                    BinaryOperator::GtEq => BinaryOperator::Lt,
+                    BinaryOperator::Eq => BinaryOperator::NotEq,
+                    BinaryOperator::NotEq => BinaryOperator::Eq,
+                    BinaryOperator::StartsWith => BinaryOperator::NotStartsWith,
+                    BinaryOperator::NotStartsWith => BinaryOperator::StartsWith,
+                },
+                term,
+                literal,
+            },
+            Predicate::Set { op, term, literals } => Predicate::Set {
+                op: match op {
+                    SetOperator::In => SetOperator::NotIn,
+                    SetOperator::NotIn => SetOperator::In,
+                },
+                term,
+                literals,
+            },
+        }
+    }
+}
+
+impl Predicate<Reference> {
+    /// Bind the predicate to the schema. Predicates that are always true or false for the column are simplified.
+    fn bind(self, schema: &SchemaStruct, case_sensitive: bool) -> Result<BoundExpression> {
+        match self {
+            Predicate::Unary { op, term } => {
+                let term = term.bind(schema, case_sensitive)?;
+                match op {
+                    UnaryOperator::IsNull if term.required => Ok(Expression::False),
+                    UnaryOperator::NotNull if term.required => Ok(Expression::True),
+                    UnaryOperator::IsNan | UnaryOperator::NotNan
+                        if !matches!(
+                            term.field_type,
+                            PrimitiveType::Float | PrimitiveType::Double
+                        ) =>
+                    {
+                        Err(anyhow!(
+                            "Column {} of type {} can't be NaN.",
+                            term.name,
+                            term.field_type
+                        ))
+                    }
+                    op => Ok(Expression::Predicate(Predicate::Unary { op, term })),
+                }
+            }
+            Predicate::Binary { op, term, literal } => {
+                let term = term.bind(schema, case_sensitive)?;
+                if matches!(
+                    op,
+                    BinaryOperator::StartsWith | BinaryOperator::NotStartsWith
+                ) && term.field_type != PrimitiveType::String
+                {
+                    return Err(anyhow!(
+                        "Column {} of type {} can't be compared with a prefix.",
+                        term.name,
+                        term.field_type
+                    ));
+                }
+                let literal = convert_literal(literal, &term)?;
+                Ok(Expression::Predicate(Predicate::Binary {
+                    op,
+                    term,
+                    literal,
+                }))
+            }
+            Predicate::Set { op, term, literals } => {
+                let term = term.bind(schema, case_sensitive)?;
+                let mut converted: Vec<Value> = Vec::with_capacity(literals.len());
+                for literal in literals {
+                    let literal = convert_literal(literal, &term)?;
+                    if !converted.contains(&literal) {
+                        converted.push(literal);
+                    }
+                }
+                match (op, converted.len()) {
+                    (SetOperator::In, 0) => Ok(Expression::False),
+                    (SetOperator::NotIn, 0) => Ok(Expression::True),
+                    (op, 1) => Ok(Expression::Predicate(Predicate::Binary {
+                        op: match op {
+                            SetOperator::In => BinaryOperator::Eq,
+                            SetOperator::NotIn => BinaryOperator::NotEq,
+                        },
+                        term,
+                        literal: converted.remove(0),
+                    })),
+                    (op, _) => Ok(Expression::Predicate(Predicate::Set {
+                        op,
+                        term,
+                        literals: converted,
+                    })),
+                }
+            }
+        }
+    }
+}
+
+impl Reference {
+    /// Resolve the column in the schema.
+    pub(crate) fn bind(
+        self,
+        schema: &SchemaStruct,
+        case_sensitive: bool,
+    ) -> Result<BoundReference> {
+        let mut fields = schema;
+        let mut required = true;
+        let mut remaining = self.name.as_str();
+        loop {
+            // Field names may contain dots themselves, so the longest matching name is used
+            let field = fields
+                .fields
+                .iter()
+                .filter(|field| {
+                    remaining.get(..field.name.len()).is_some_and(|name| {
+                        if case_sensitive {
+                            name == field.name
+                        } else {
+                            name.to_lowercase() == field.name.to_lowercase()
+                        }
+                    }) && (remaining.len() == field.name.len()
+                        || remaining[field.name.len()..].starts_with('.'))
+                })
+                .max_by_key(|field| field.name.len())
+                .ok_or_else(|| anyhow!("Column {} doesn't exist in the schema.", self.name))?;
+            required &= field.required;
+            remaining = remaining[field.name.len()..].trim_start_matches('.');
+            match (&field.field_type, remaining.is_empty()) {
+                (AllType::Primitive(field_type), true) => {
+                    return Ok(BoundReference {
+                        field_id: field.id,
+                        name: self.name,
+                        field_type: field_type.clone(),
+                        required,
+                    })
+                }
+                (AllType::Struct(nested), false) => fields = nested,
+                _ => {
+                    return Err(anyhow!(
+                        "Column {} isn't a primitive column of the schema.",
+                        self.name
+                    ))
+                }
+            }
+        }
+    }
+}
+
+/// Convert the literal to the type of the column. Fails if the literal can't be represented in that type.
+fn convert_literal(literal: Value, term: &BoundReference) -> Result<Value> {
+    match (&term.field_type, literal) {
+        (PrimitiveType::Boolean, literal @ Value::Boolean(_))
+        | (PrimitiveType::Int, literal @ Value::Int(_))
+        | (PrimitiveType::Long, literal @ Value::LongInt(_))
+        | (PrimitiveType::Float, literal @ Value::Double(_))
+        | (PrimitiveType::Double, literal @ Value::LongFloat(_))
+        | (PrimitiveType::Date, literal @ Value::Date(_))
+        | (PrimitiveType::Time, literal @ Value::Time(_))
+        | (PrimitiveType::Timestamp, literal @ Value::Timestamp(_))
+        | (PrimitiveType::Timestampz, literal @ Value::TimestampTZ(_))
+        | (PrimitiveType::String, literal @ Value::String(_))
+        | (PrimitiveType::Uuid, literal @ Value::UUID(_))
+        | (PrimitiveType::Binary, literal @ Value::Binary(_)) => Ok(literal),
+        (PrimitiveType::Long, Value::Int(int)) => Ok(Value::LongInt(int as i64)),
+        (PrimitiveType::Int, Value::LongInt(long)) if i32::try_from(long).is_ok() => {
+            Ok(Value::Int(long as i32))
+        }
+        (PrimitiveType::Double, Value::Double(float)) => Ok(Value::LongFloat(float as f64)),
+        (PrimitiveType::Timestampz, Value::Timestamp(timestamp)) => {
+            Ok(Value::TimestampTZ(timestamp))
+        }
+        (PrimitiveType::Fixed(length), Value::Fixed(_, bytes) | Value::Binary(bytes))
+            if bytes.len() as u64 == *length =>
+        {
+            Ok(Value::Fixed(bytes.len(), bytes))
+        }
+        (PrimitiveType::Decimal { precision, scale }, Value::Decimal(decimal))
+            if decimal.scale() == *scale as u32 && decimal.precision() as i32 <= *precision =>
+        {
+            Ok(Value::Decimal(decimal))
+        }
+        (field_type, literal) => Err(anyhow!(
+            "Literal {:?} can't be compared with column {} of type {}.",
+            literal,
+            term.name,
+            field_type
+        )),
+    }
+}
+
+/// Compare the bound truncated to the length of the prefix with the prefix.
+fn compare_prefix(bound: &Value, prefix: &Value) -> Option<std::cmp::Ordering> {
+    match (bound, prefix) {
+        (Value::String(bound), Value::String(prefix)) => {
+            let bound = bound.as_bytes();
+            Some(bound[..bound.len().min(prefix.len())].cmp(prefix.as_bytes()))
+        }
+        _ => None,
+    }
+}
+
+/// Check if the value is a floating point NaN.
+fn is_nan(value: &Value) -> bool {
+    match value {
+        Value::Double(float) => float.is_nan(),
+        Value::LongFloat(double) => double.is_nan(),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::model::{
+        schema::{AllType, PrimitiveType, SchemaStruct, StructField},
+        types::Value,
+    };
+
+    use super::{BinaryOperator, BoundReference, Expression, Predicate, Reference, SetOperator};
+
+    fn schema() -> SchemaStruct {
+        SchemaStruct {
+            fields: vec![
+                StructField {
+                    id: 1,
+                    name: "id".to_string(),
+                    required: true,
+                    field_type: AllType::Primitive(PrimitiveType::Long),
+                    doc: None,
+                },
+                StructField {
+                    id: 2,
+                    name: "name".to_string(),
+                    required: false,
+                    field_type: AllType::Primitive(PrimitiveType::String),
+                    doc: None,
+                },
+                StructField {
+                    id: 3,
+                    name: "location".to_string(),
+                    required: true,
+                    field_type: AllType::Struct(SchemaStruct {
+                        fields: vec![StructField {
+                            id: 4,
+                            name: "lat".to_string(),
+                            required: false,
+                            field_type: AllType::Primitive(PrimitiveType::Double),
+                            doc: None,
+                        }],
+                    }),
+                    doc: None,
+                },
+            ],
+        }
+    }
+
+    fn id() -> BoundReference {
+        BoundReference {
+            field_id: 1,
+            name: "id".to_string(),
+            field_type: PrimitiveType::Long,
+            required: true,
+        }
+    }
+
+    #[test]
+    fn test_bind() {
+        let bound = Reference::new("id")
+            .greater_than(Value::Int(10))
+            .and(!Reference::new("location.lat").is_nan())
+            .bind(&schema())
+            .unwrap();
+        assert_eq!(
+            bound,
+            Expression::Predicate(Predicate::Binary {
+                op: BinaryOperator::Gt,
+                term: id(),
+                literal: Value::LongInt(10),
+            })
+            .and(!Expression::Predicate(Predicate::Unary {
+                op: super::UnaryOperator::IsNan,
+                term: BoundReference {
+                    field_id: 4,
+                    name: "location.lat".to_string(),
+                    field_type: PrimitiveType::Double,
+                    required: false,
+                },
+            }))
+        );
+    }
+
+    #[test]
+    fn test_bind_errors() {
+        assert!(Reference::new("unknown").is_null().bind(&schema()).is_err());
+        assert!(Reference::new("location")
+            .is_null()
+            .bind(&schema())
+            .is_err());
+        assert!(Reference::new("id")
+            .equal(Value::String("1".to_string()))
+            .bind(&schema())
+            .is_err());
+        assert!(Reference::new("id")
+            .starts_with("1")
+            .bind(&schema())
+            .is_err());
+        assert!(Reference::new("name").is_nan().bind(&schema()).is_err());
+    }
+
+    #[test]
+    fn test_rewrite_not() {
+        let expression = !(Reference::new("id")
+            .less_than(Value::Int(1))
+            .or(!Reference::new("name").is_null()));
+        assert_eq!(
+            expression.rewrite_not(),
+            Reference::new("id")
+                .greater_than_or_eq(Value::Int(1))
+                .and(Reference::new("name").is_null())
+        );
+    }
+
+    #[test]
+    fn test_bind_simplification() {
+        assert_eq!(
+            Reference::new("id").is_null().bind(&schema()).unwrap(),
+            Expression::False
+        );
+        assert_eq!(
+            Reference::new("id")
+                .not_null()
+                .or(Reference::new("name").is_null())
+                .bind(&schema())
+                .unwrap(),
+            Expression::True
+        );
+        assert_eq!(
+            Reference::new("id").is_in(vec![]).bind(&schema()).unwrap(),
+            Expression::False
+        );
+        assert_eq!(
+            Reference::new("id")
+                .not_in(vec![Value::Int(1), Value::LongInt(1)])
+                .bind(&schema())
+                .unwrap(),
+            Expression::Predicate(Predicate::Binary {
+                op: BinaryOperator::NotEq,
+                term: id(),
+                literal: Value::LongInt(1),
+            })
+        );
+        assert_eq!(
+            Reference::new("id")
+                .is_in(vec![Value::Int(1), Value::Int(2)])
+                .bind(&schema())
+                .unwrap(),
+            Expression::Predicate(Predicate::Set {
+                op: SetOperator::In,
+                term: id(),
+                literals: vec![Value::LongInt(1), Value::LongInt(2)],
+            })
+        );
+    }
+}
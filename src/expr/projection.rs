@@ -0,0 +1,575 @@
+/*!
+Projection of row filters to filters on the partition values of a [PartitionSpec](crate::model::partition::PartitionSpec).
+*/
+
+use anyhow::{anyhow, Result};
+use chrono::Duration;
+
+use crate::model::{
+    partition::{PartitionField, Transform},
+    schema::AllType,
+    types::Value,
+};
+
+use super::{
+    BinaryOperator, BoundExpression, BoundReference, Expression, Predicate, SetOperator,
+    UnaryOperator,
+};
+
+/// Project a bound row filter to an expression on the partition values of the spec. The projection is inclusive: it is
+/// true for every partition that might contain rows matching the filter. Predicates that can't be projected through
+/// the partition fields are replaced with [Expression::True].
+pub fn inclusive_projection(
+    filter: &BoundExpression,
+    spec: &[PartitionField],
+) -> Result<BoundExpression> {
+    project(filter.clone().rewrite_not(), spec, false)
+}
+
+/// Project a bound row filter to an expression on the partition values of the spec. The projection is strict: it is
+/// only true for partitions in which all rows match the filter. Predicates that can't be projected through the
+/// partition fields are replaced with [Expression::False].
+pub fn strict_projection(
+    filter: &BoundExpression,
+    spec: &[PartitionField],
+) -> Result<BoundExpression> {
+    project(filter.clone().rewrite_not(), spec, true)
+}
+
+fn project(
+    expression: BoundExpression,
+    spec: &[PartitionField],
+    strict: bool,
+) -> Result<BoundExpression> {
+    match expression {
+        Expression::And(left, right) => {
+            Ok(project(*left, spec, strict)?.and(project(*right, spec, strict)?))
+        }
+        Expression::Or(left, right) => {
+            Ok(project(*left, spec, strict)?.or(project(*right, spec, strict)?))
+        }
+        Expression::Predicate(predicate) => {
+            let mut fields = spec
+                .iter()
+                .filter(|field| field.source_id == predicate.term().field_id);
+            if strict {
+                // Every partition field of the source column can guarantee that all rows match
+                fields.try_fold(Expression::False, |projection, field| {
+                    let term = partition_reference(field, predicate.term())?;
+                    Ok(
+                        projection.or(project_strict(&field.transform, &predicate, term)?
+                            .unwrap_or(Expression::False)),
+                    )
+                })
+            } else {
+                // Every partition field of the source column restricts the partitions that might match
+                fields.try_fold(Expression::True, |projection, field| {
+                    let term = partition_reference(field, predicate.term())?;
+                    Ok(projection.and(
+                        project_inclusive(&field.transform, &predicate, term)?
+                            .unwrap_or(Expression::True),
+                    ))
+                })
+            }
+        }
+        expression => Ok(expression),
+    }
+}
+
+/// Project the predicate inclusively through the transform. Returns None if the transform doesn't allow to restrict
+/// the partition values.
+fn project_inclusive(
+    transform: &Transform,
+    predicate: &Predicate<BoundReference>,
+    term: BoundReference,
+) -> Result<Option<BoundExpression>> {
+    let apply = |literal: &Value| transform.apply(literal);
+    match (transform, predicate) {
+        (Transform::Identity, _) => Ok(Some(Expression::Predicate(with_term(
+            predicate.clone(),
+            term,
+        )))),
+        (Transform::Void, _) => Ok(None),
+        // All other transforms keep null values and drop NaN values
+        (_, Predicate::Unary { op, .. }) => Ok(match op {
+            UnaryOperator::IsNull | UnaryOperator::NotNull => {
+                Some(Expression::Predicate(Predicate::Unary { op: *op, term }))
+            }
+            UnaryOperator::IsNan | UnaryOperator::NotNan => None,
+        }),
+        (_, Predicate::Set { op, literals, .. }) => match op {
+            SetOperator::In => set(SetOperator::In, term, literals, apply),
+            SetOperator::NotIn => Ok(None),
+        },
+        // Buckets don't preserve the order of the values
+        (Transform::Bucket(_), Predicate::Binary { op, literal, .. }) => match op {
+            BinaryOperator::Eq => binary(BinaryOperator::Eq, term, apply(literal)?),
+            _ => Ok(None),
+        },
+        (Transform::Truncate(width), Predicate::Binary { op, literal, .. })
+            if matches!(
+                op,
+                BinaryOperator::StartsWith | BinaryOperator::NotStartsWith
+            ) =>
+        {
+            let length = prefix_length(literal)?;
+            let width = *width as usize;
+            match op {
+                // A prefix that is at least as long as the width determines the partition value
+                BinaryOperator::StartsWith if length >= width => {
+                    binary(BinaryOperator::Eq, term, apply(literal)?)
+                }
+                BinaryOperator::StartsWith => {
+                    binary(BinaryOperator::StartsWith, term, Some(literal.clone()))
+                }
+                // Partitions with longer values can contain values with and without a longer prefix
+                BinaryOperator::NotStartsWith if length > width => Ok(None),
+                BinaryOperator::NotStartsWith if length == width => {
+                    binary(BinaryOperator::NotEq, term, Some(literal.clone()))
+                }
+                _ => binary(BinaryOperator::NotStartsWith, term, Some(literal.clone())),
+            }
+        }
+        // Truncate and the date and time transforms preserve the order of the values
+        (_, Predicate::Binary { op, literal, .. }) => match op {
+            BinaryOperator::Lt => binary(
+                BinaryOperator::LtEq,
+                term,
+                apply(&adjacent(literal, -1).unwrap_or_else(|| literal.clone()))?,
+            ),
+            BinaryOperator::LtEq => binary(BinaryOperator::LtEq, term, apply(literal)?),
+            BinaryOperator::Gt => binary(
+                BinaryOperator::GtEq,
+                term,
+                apply(&adjacent(literal, 1).unwrap_or_else(|| literal.clone()))?,
+            ),
+            BinaryOperator::GtEq => binary(BinaryOperator::GtEq, term, apply(literal)?),
+            BinaryOperator::Eq => binary(BinaryOperator::Eq, term, apply(literal)?),
+            _ => Ok(None),
+        },
+    }
+}
+
+/// Project the predicate strictly through the transform. Returns None if the transform doesn't allow to guarantee
+/// that all rows of a partition match.
+fn project_strict(
+    transform: &Transform,
+    predicate: &Predicate<BoundReference>,
+    term: BoundReference,
+) -> Result<Option<BoundExpression>> {
+    let apply = |literal: &Value| transform.apply(literal);
+    match (transform, predicate) {
+        (Transform::Identity, _) => Ok(Some(Expression::Predicate(with_term(
+            predicate.clone(),
+            term,
+        )))),
+        (Transform::Void, _) => Ok(None),
+        // All other transforms keep null values and drop NaN values
+        (_, Predicate::Unary { op, .. }) => Ok(match op {
+            UnaryOperator::IsNull | UnaryOperator::NotNull => {
+                Some(Expression::Predicate(Predicate::Unary { op: *op, term }))
+            }
+            UnaryOperator::IsNan | UnaryOperator::NotNan => None,
+        }),
+        (_, Predicate::Set { op, literals, .. }) => match op {
+            SetOperator::NotIn => set(SetOperator::NotIn, term, literals, apply),
+            SetOperator::In => Ok(None),
+        },
+        // Buckets don't preserve the order of the values
+        (Transform::Bucket(_), Predicate::Binary { op, literal, .. }) => match op {
+            BinaryOperator::NotEq => binary(BinaryOperator::NotEq, term, apply(literal)?),
+            _ => Ok(None),
+        },
+        (Transform::Truncate(width), Predicate::Binary { op, literal, .. })
+            if matches!(
+                op,
+                BinaryOperator::StartsWith | BinaryOperator::NotStartsWith
+            ) =>
+        {
+            match op {
+                // Partition values are prefixes of the values, they can only guarantee prefixes up to the width
+                BinaryOperator::StartsWith if prefix_length(literal)? <= *width as usize => {
+                    binary(BinaryOperator::StartsWith, term, Some(literal.clone()))
+                }
+                BinaryOperator::StartsWith => Ok(None),
+                _ => binary(BinaryOperator::NotStartsWith, term, apply(literal)?),
+            }
+        }
+        // Truncate and the date and time transforms preserve the order of the values
+        (_, Predicate::Binary { op, literal, .. }) => match op {
+            BinaryOperator::Lt => binary(BinaryOperator::Lt, term, apply(literal)?),
+            BinaryOperator::LtEq => binary(
+                BinaryOperator::Lt,
+                term,
+                apply(&adjacent(literal, 1).unwrap_or_else(|| literal.clone()))?,
+            ),
+            BinaryOperator::Gt => binary(BinaryOperator::Gt, term, apply(literal)?),
+            BinaryOperator::GtEq => binary(
+                BinaryOperator::Gt,
+                term,
+                apply(&adjacent(literal, -1).unwrap_or_else(|| literal.clone()))?,
+            ),
+            BinaryOperator::NotEq => binary(BinaryOperator::NotEq, term, apply(literal)?),
+            _ => Ok(None),
+        },
+    }
+}
+
+/// Create a comparison of the partition field with a partition value.
+fn binary(
+    op: BinaryOperator,
+    term: BoundReference,
+    literal: Option<Value>,
+) -> Result<Option<BoundExpression>> {
+    Ok(literal.map(|literal| Expression::Predicate(Predicate::Binary { op, term, literal })))
+}
+
+/// Create a set predicate on the partition field with the transformed literals. Literals that are mapped to the same
+/// partition value are only kept once.
+fn set(
+    op: SetOperator,
+    term: BoundReference,
+    literals: &[Value],
+    apply: impl Fn(&Value) -> Result<Option<Value>>,
+) -> Result<Option<BoundExpression>> {
+    let mut values: Vec<Value> = Vec::with_capacity(literals.len());
+    for literal in literals {
+        match apply(literal)? {
+            Some(value) => {
+                if !values.contains(&value) {
+                    values.push(value)
+                }
+            }
+            None => return Ok(None),
+        }
+    }
+    Ok(Some(Expression::Predicate(Predicate::Set {
+        op,
+        term,
+        literals: values,
+    })))
+}
+
+/// Number of characters of a string prefix
+fn prefix_length(prefix: &Value) -> Result<usize> {
+    match prefix {
+        Value::String(prefix) => Ok(prefix.chars().count()),
+        _ => Err(anyhow!("Prefix {:?} is not a string.", prefix)),
+    }
+}
+
+/// Get the value that is `step` values away in the order of its type. Returns None for types without discrete values
+/// and if the result overflows. Projections fall back to the value itself, which is always safe but less selective.
+fn adjacent(value: &Value, step: i32) -> Option<Value> {
+    match value {
+        Value::Int(int) => int.checked_add(step).map(Value::Int),
+        Value::LongInt(long) => long.checked_add(step as i64).map(Value::LongInt),
+        Value::Decimal(decimal) => Some(Value::Decimal(decimal.with_value(decimal.value() + step))),
+        Value::Date(date) => date
+            .checked_add_signed(Duration::days(step as i64))
+            .map(Value::Date),
+        Value::Timestamp(timestamp) => timestamp
+            .checked_add_signed(Duration::microseconds(step as i64))
+            .map(Value::Timestamp),
+        Value::TimestampTZ(timestamp) => timestamp
+            .checked_add_signed(Duration::microseconds(step as i64))
+            .map(Value::TimestampTZ),
+        _ => None,
+    }
+}
+
+/// Replace the column of the predicate.
+fn with_term(
+    predicate: Predicate<BoundReference>,
+    term: BoundReference,
+) -> Predicate<BoundReference> {
+    match predicate {
+        Predicate::Unary { op, .. } => Predicate::Unary { op, term },
+        Predicate::Binary { op, literal, .. } => Predicate::Binary { op, term, literal },
+        Predicate::Set { op, literals, .. } => Predicate::Set { op, term, literals },
+    }
+}
+
+/// Get the reference to the partition field that is derived from the source column.
+fn partition_reference(field: &PartitionField, source: &BoundReference) -> Result<BoundReference> {
+    match field
+        .transform
+        .result_type(&AllType::Primitive(source.field_type.clone()))?
+    {
+        AllType::Primitive(field_type) => Ok(BoundReference {
+            field_id: field.field_id,
+            name: field.name.clone(),
+            field_type,
+            required: false,
+        }),
+        _ => Err(anyhow!(
+            "Partition field {} doesn't have a primitive type.",
+            field.name
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use chrono::NaiveDate;
+
+    use crate::{
+        expr::{
+            BinaryOperator, BoundExpression, BoundReference, Expression, Predicate, Reference,
+            UnboundExpression,
+        },
+        model::{
+            partition::{PartitionField, Transform},
+            schema::{AllType, PrimitiveType, SchemaStruct, StructField},
+            types::Value,
+        },
+    };
+
+    use super::{inclusive_projection, strict_projection};
+
+    fn schema() -> SchemaStruct {
+        SchemaStruct {
+            fields: vec![
+                StructField {
+                    id: 1,
+                    name: "id".to_string(),
+                    required: true,
+                    field_type: AllType::Primitive(PrimitiveType::Long),
+                    doc: None,
+                },
+                StructField {
+                    id: 2,
+                    name: "ts".to_string(),
+                    required: false,
+                    field_type: AllType::Primitive(PrimitiveType::Timestamp),
+                    doc: None,
+                },
+                StructField {
+                    id: 3,
+                    name: "name".to_string(),
+                    required: false,
+                    field_type: AllType::Primitive(PrimitiveType::String),
+                    doc: None,
+                },
+            ],
+        }
+    }
+
+    fn spec(source_id: i32, transform: Transform) -> Vec<PartitionField> {
+        vec![PartitionField {
+            source_id,
+            field_id: 1000,
+            name: "partition".to_string(),
+            transform,
+        }]
+    }
+
+    fn project(
+        filter: UnboundExpression,
+        spec: &[PartitionField],
+    ) -> (BoundExpression, BoundExpression) {
+        let filter = filter.bind(&schema()).unwrap();
+        (
+            inclusive_projection(&filter, spec).unwrap(),
+            strict_projection(&filter, spec).unwrap(),
+        )
+    }
+
+    fn partition(op: BinaryOperator, field_type: PrimitiveType, literal: Value) -> BoundExpression {
+        Expression::Predicate(Predicate::Binary {
+            op,
+            term: BoundReference {
+                field_id: 1000,
+                name: "partition".to_string(),
+                field_type,
+                required: false,
+            },
+            literal,
+        })
+    }
+
+    fn timestamp(day: u32, micros: u32) -> Value {
+        Value::Timestamp(
+            NaiveDate::from_ymd_opt(2023, 1, day)
+                .unwrap()
+                .and_hms_micro_opt(0, 0, 0, micros)
+                .unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_day() {
+        let spec = spec(2, Transform::Day);
+        let ts = || Reference::new("ts");
+        let day = |op, day| partition(op, PrimitiveType::Int, Value::Int(day));
+        // 2023-01-01 is day 19358
+        assert_eq!(
+            project(ts().greater_than_or_eq(timestamp(1, 0)), &spec),
+            (
+                day(BinaryOperator::GtEq, 19358),
+                day(BinaryOperator::Gt, 19357)
+            )
+        );
+        assert_eq!(
+            project(ts().greater_than(timestamp(1, 0)), &spec),
+            (
+                day(BinaryOperator::GtEq, 19358),
+                day(BinaryOperator::Gt, 19358)
+            )
+        );
+        assert_eq!(
+            project(ts().less_than(timestamp(2, 0)), &spec),
+            (
+                day(BinaryOperator::LtEq, 19358),
+                day(BinaryOperator::Lt, 19359)
+            )
+        );
+        assert_eq!(
+            project(ts().less_than_or_eq(timestamp(1, 1)), &spec),
+            (
+                day(BinaryOperator::LtEq, 19358),
+                day(BinaryOperator::Lt, 19358)
+            )
+        );
+        assert_eq!(
+            project(ts().equal(timestamp(1, 1)), &spec),
+            (day(BinaryOperator::Eq, 19358), Expression::False)
+        );
+        assert_eq!(
+            project(!ts().equal(timestamp(1, 1)), &spec),
+            (Expression::True, day(BinaryOperator::NotEq, 19358))
+        );
+    }
+
+    #[test]
+    fn test_bucket() {
+        let spec = spec(1, Transform::Bucket(16));
+        let id = || Reference::new("id");
+        let bucket = |op| partition(op, PrimitiveType::Int, Value::Int(3));
+        // Long 34 is in bucket 3 of 16
+        assert_eq!(
+            project(id().equal(Value::LongInt(34)), &spec),
+            (bucket(BinaryOperator::Eq), Expression::False)
+        );
+        assert_eq!(
+            project(id().not_equal(Value::LongInt(34)), &spec),
+            (Expression::True, bucket(BinaryOperator::NotEq))
+        );
+        assert_eq!(
+            project(id().less_than(Value::LongInt(34)), &spec),
+            (Expression::True, Expression::False)
+        );
+    }
+
+    #[test]
+    fn test_truncate_long() {
+        let spec = spec(1, Transform::Truncate(10));
+        let id = || Reference::new("id");
+        let truncated = |op, value| partition(op, PrimitiveType::Long, Value::LongInt(value));
+        assert_eq!(
+            project(id().less_than(Value::LongInt(10)), &spec),
+            (
+                truncated(BinaryOperator::LtEq, 0),
+                truncated(BinaryOperator::Lt, 10)
+            )
+        );
+        assert_eq!(
+            project(id().less_than_or_eq(Value::LongInt(9)), &spec),
+            (
+                truncated(BinaryOperator::LtEq, 0),
+                truncated(BinaryOperator::Lt, 10)
+            )
+        );
+        assert_eq!(
+            project(id().greater_than(Value::LongInt(9)), &spec),
+            (
+                truncated(BinaryOperator::GtEq, 10),
+                truncated(BinaryOperator::Gt, 0)
+            )
+        );
+        assert_eq!(
+            project(id().greater_than_or_eq(Value::LongInt(-10)), &spec),
+            (
+                truncated(BinaryOperator::GtEq, -10),
+                truncated(BinaryOperator::Gt, -20)
+            )
+        );
+    }
+
+    #[test]
+    fn test_truncate_string() {
+        let spec = spec(3, Transform::Truncate(3));
+        let name = || Reference::new("name");
+        let truncated = |op, value: &str| {
+            partition(op, PrimitiveType::String, Value::String(value.to_string()))
+        };
+        assert_eq!(
+            project(name().starts_with("ab"), &spec),
+            (
+                truncated(BinaryOperator::StartsWith, "ab"),
+                truncated(BinaryOperator::StartsWith, "ab")
+            )
+        );
+        assert_eq!(
+            project(name().starts_with("abcd"), &spec),
+            (truncated(BinaryOperator::Eq, "abc"), Expression::False)
+        );
+        assert_eq!(
+            project(name().not_starts_with("ab"), &spec),
+            (
+                truncated(BinaryOperator::NotStartsWith, "ab"),
+                truncated(BinaryOperator::NotStartsWith, "ab")
+            )
+        );
+        assert_eq!(
+            project(name().not_starts_with("abc"), &spec),
+            (
+                truncated(BinaryOperator::NotEq, "abc"),
+                truncated(BinaryOperator::NotStartsWith, "abc")
+            )
+        );
+        assert_eq!(
+            project(name().not_starts_with("abcd"), &spec),
+            (
+                Expression::True,
+                truncated(BinaryOperator::NotStartsWith, "abc")
+            )
+        );
+        assert_eq!(
+            project(name().less_than(Value::String("abcd".to_string())), &spec),
+            (
+                truncated(BinaryOperator::LtEq, "abc"),
+                truncated(BinaryOperator::Lt, "abc")
+            )
+        );
+    }
+
+    #[test]
+    fn test_combinations() {
+        let spec = spec(2, Transform::Day);
+        let filter = Reference::new("ts")
+            .greater_than_or_eq(timestamp(1, 0))
+            .and(Reference::new("id").equal(Value::LongInt(1)));
+        // The unpartitioned column doesn't restrict the partitions and can't be guaranteed
+        assert_eq!(
+            project(filter.clone(), &spec),
+            (
+                partition(BinaryOperator::GtEq, PrimitiveType::Int, Value::Int(19358)),
+                Expression::False
+            )
+        );
+        assert_eq!(
+            project(
+                filter.or(Reference::new("ts").less_than(timestamp(1, 0))),
+                &spec
+            ),
+            (
+                partition(BinaryOperator::GtEq, PrimitiveType::Int, Value::Int(19358)).or(
+                    partition(BinaryOperator::LtEq, PrimitiveType::Int, Value::Int(19357))
+                ),
+                partition(BinaryOperator::Lt, PrimitiveType::Int, Value::Int(19358))
+            )
+        );
+    }
+}
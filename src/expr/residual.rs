@@ -0,0 +1,309 @@
+/*!
+Defines the [ResidualEvaluator] that simplifies a row filter with the partition values of a data file.
+*/
+
+use std::cmp::Ordering;
+
+use anyhow::Result;
+
+use crate::model::{
+    manifest::PartitionValues,
+    partition::PartitionField,
+    schema::PrimitiveType,
+    types::{date_from_epoch, time_from_midnight, timestamp_from_epoch, Value},
+};
+
+use super::{
+    compare_prefix, is_nan,
+    projection::{inclusive_projection, strict_projection},
+    BinaryOperator, BoundExpression, BoundReference, Expression, Predicate, SetOperator,
+    UnaryOperator,
+};
+
+/// Computes the residual of a row filter for the data files of one partition spec. The residual is the part of the
+/// filter that isn't already decided by the partition values of a file and still has to be applied to its rows.
+pub struct ResidualEvaluator {
+    expression: BoundExpression,
+    spec: Vec<PartitionField>,
+}
+
+impl ResidualEvaluator {
+    /// Create an evaluator for data files written with the partition spec. The filter has to be bound to the table
+    /// schema.
+    pub fn new(filter: &BoundExpression, spec: &[PartitionField]) -> Self {
+        ResidualEvaluator {
+            expression: filter.clone().rewrite_not(),
+            spec: spec.to_vec(),
+        }
+    }
+    /// Get the residual filter for a data file with the given partition values. Predicates that are true for all
+    /// rows of the partition are replaced with [Expression::True], predicates that are false for all rows with
+    /// [Expression::False].
+    pub fn residual(&self, partition: &PartitionValues) -> Result<BoundExpression> {
+        self.residual_expression(&self.expression, partition)
+    }
+    fn residual_expression(
+        &self,
+        expression: &BoundExpression,
+        partition: &PartitionValues,
+    ) -> Result<BoundExpression> {
+        match expression {
+            Expression::And(left, right) => Ok(self
+                .residual_expression(left, partition)?
+                .and(self.residual_expression(right, partition)?)),
+            Expression::Or(left, right) => Ok(self
+                .residual_expression(left, partition)?
+                .or(self.residual_expression(right, partition)?)),
+            Expression::Predicate(predicate) => self.residual_predicate(predicate, partition),
+            expression => Ok(expression.clone()),
+        }
+    }
+    fn residual_predicate(
+        &self,
+        predicate: &Predicate<BoundReference>,
+        partition: &PartitionValues,
+    ) -> Result<BoundExpression> {
+        let expression = Expression::Predicate(predicate.clone());
+        for (index, field) in self.spec.iter().enumerate() {
+            if field.source_id != predicate.term().field_id {
+                continue;
+            }
+            let value = match partition.get(index) {
+                Some(value) => value.as_ref(),
+                None => continue,
+            };
+            let field = std::slice::from_ref(field);
+            // All rows of the partition match if the strict projection is true for the partition value
+            if eval(&strict_projection(&expression, field)?, value) == Some(true) {
+                return Ok(Expression::True);
+            }
+            // No row of the partition matches if the inclusive projection is false for the partition value
+            if eval(&inclusive_projection(&expression, field)?, value) == Some(false) {
+                return Ok(Expression::False);
+            }
+        }
+        Ok(expression)
+    }
+}
+
+/// Evaluate an expression on a single partition field for the partition value. Returns None if the result can't be
+/// determined.
+fn eval(expression: &BoundExpression, value: Option<&Value>) -> Option<bool> {
+    match expression {
+        Expression::True => Some(true),
+        Expression::False => Some(false),
+        Expression::And(left, right) => match (eval(left, value), eval(right, value)) {
+            (Some(false), _) | (_, Some(false)) => Some(false),
+            (Some(true), Some(true)) => Some(true),
+            _ => None,
+        },
+        Expression::Or(left, right) => match (eval(left, value), eval(right, value)) {
+            (Some(true), _) | (_, Some(true)) => Some(true),
+            (Some(false), Some(false)) => Some(false),
+            _ => None,
+        },
+        Expression::Not(expression) => eval(expression, value).map(|result| !result),
+        Expression::Predicate(predicate) => match value {
+            Some(value) => {
+                let value = partition_value(value, &predicate.term().field_type)?;
+                test(predicate, Some(&value))
+            }
+            None => test(predicate, None),
+        },
+    }
+}
+
+/// Test if the value satisfies the predicate. Null values only satisfy null checks and negated comparisons.
+fn test(predicate: &Predicate<BoundReference>, value: Option<&Value>) -> Option<bool> {
+    match (predicate, value) {
+        (Predicate::Unary { op, .. }, value) => Some(match op {
+            UnaryOperator::IsNull => value.is_none(),
+            UnaryOperator::NotNull => value.is_some(),
+            UnaryOperator::IsNan => value.is_some_and(is_nan),
+            UnaryOperator::NotNan => !value.is_some_and(is_nan),
+        }),
+        (Predicate::Binary { op, .. }, None) => Some(matches!(
+            op,
+            BinaryOperator::NotEq | BinaryOperator::NotStartsWith
+        )),
+        (Predicate::Set { op, .. }, None) => Some(*op == SetOperator::NotIn),
+        (Predicate::Binary { op, literal, .. }, Some(value)) => match op {
+            BinaryOperator::StartsWith => {
+                compare_prefix(value, literal).map(|ordering| ordering == Ordering::Equal)
+            }
+            BinaryOperator::NotStartsWith => {
+                compare_prefix(value, literal).map(|ordering| ordering != Ordering::Equal)
+            }
+            op => value.partial_cmp(literal).map(|ordering| match op {
+                BinaryOperator::Lt => ordering == Ordering::Less,
+                BinaryOperator::LtEq => ordering != Ordering::Greater,
+                BinaryOperator::Gt => ordering == Ordering::Greater,
+                BinaryOperator::GtEq => ordering != Ordering::Less,
+                BinaryOperator::Eq => ordering == Ordering::Equal,
+                _ => ordering != Ordering::Equal,
+            }),
+        },
+        (Predicate::Set { op, literals, .. }, Some(value)) => {
+            let contained = literals.contains(value);
+            Some(match op {
+                SetOperator::In => contained,
+                SetOperator::NotIn => !contained,
+            })
+        }
+    }
+}
+
+/// Convert a partition value read from a manifest to the type of the partition field. Dates, times and timestamps
+/// are stored as numbers in the manifest. Returns None if the value doesn't fit the type.
+fn partition_value(value: &Value, field_type: &PrimitiveType) -> Option<Value> {
+    match (field_type, value) {
+        (PrimitiveType::Long, Value::Int(int)) => Some(Value::LongInt(*int as i64)),
+        (PrimitiveType::Double, Value::Double(float)) => Some(Value::LongFloat(*float as f64)),
+        (PrimitiveType::Date, Value::Int(days)) => date_from_epoch(*days).map(Value::Date),
+        (PrimitiveType::Time, Value::LongInt(micros)) => {
+            time_from_midnight(*micros).map(Value::Time)
+        }
+        (PrimitiveType::Timestamp, Value::LongInt(micros)) => {
+            timestamp_from_epoch(*micros).map(Value::Timestamp)
+        }
+        (PrimitiveType::Timestampz, Value::LongInt(micros)) => {
+            timestamp_from_epoch(*micros).map(Value::TimestampTZ)
+        }
+        (PrimitiveType::Timestampz, Value::Timestamp(timestamp)) => {
+            Some(Value::TimestampTZ(*timestamp))
+        }
+        (_, value) => Some(value.clone()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::{
+        expr::{BinaryOperator, BoundReference, Expression, Predicate, Reference},
+        model::{
+            manifest::PartitionValues,
+            partition::{PartitionField, Transform},
+            schema::{AllType, PrimitiveType, SchemaStruct, StructField},
+            types::Value,
+        },
+    };
+
+    use super::ResidualEvaluator;
+
+    fn schema() -> SchemaStruct {
+        SchemaStruct {
+            fields: vec![
+                StructField {
+                    id: 1,
+                    name: "id".to_string(),
+                    required: true,
+                    field_type: AllType::Primitive(PrimitiveType::Long),
+                    doc: None,
+                },
+                StructField {
+                    id: 2,
+                    name: "region".to_string(),
+                    required: false,
+                    field_type: AllType::Primitive(PrimitiveType::String),
+                    doc: None,
+                },
+            ],
+        }
+    }
+
+    fn spec() -> Vec<PartitionField> {
+        vec![
+            PartitionField {
+                source_id: 2,
+                field_id: 1000,
+                name: "region".to_string(),
+                transform: Transform::Identity,
+            },
+            PartitionField {
+                source_id: 1,
+                field_id: 1001,
+                name: "id_truncate".to_string(),
+                transform: Transform::Truncate(10),
+            },
+        ]
+    }
+
+    fn partition(region: Option<&str>, id: i64) -> PartitionValues {
+        PartitionValues::from_iter(vec![
+            (
+                "region".to_string(),
+                region.map(|region| Value::String(region.to_string())),
+            ),
+            ("id_truncate".to_string(), Some(Value::LongInt(id))),
+        ])
+    }
+
+    #[test]
+    fn test_residual() {
+        let region = || Reference::new("region");
+        let id = || Reference::new("id");
+        let evaluator = ResidualEvaluator::new(
+            &region()
+                .equal(Value::String("emea".to_string()))
+                .and(id().greater_than_or_eq(Value::LongInt(25)))
+                .bind(&schema())
+                .unwrap(),
+            &spec(),
+        );
+        // The partition decides the region, the ids of partition 20 still have to be filtered
+        assert_eq!(
+            evaluator.residual(&partition(Some("emea"), 20)).unwrap(),
+            Expression::Predicate(Predicate::Binary {
+                op: BinaryOperator::GtEq,
+                term: BoundReference {
+                    field_id: 1,
+                    name: "id".to_string(),
+                    field_type: PrimitiveType::Long,
+                    required: true,
+                },
+                literal: Value::LongInt(25),
+            })
+        );
+        assert_eq!(
+            evaluator.residual(&partition(Some("emea"), 30)).unwrap(),
+            Expression::True
+        );
+        assert_eq!(
+            evaluator.residual(&partition(Some("emea"), 10)).unwrap(),
+            Expression::False
+        );
+        assert_eq!(
+            evaluator.residual(&partition(Some("na"), 30)).unwrap(),
+            Expression::False
+        );
+        assert_eq!(
+            evaluator.residual(&partition(None, 30)).unwrap(),
+            Expression::False
+        );
+    }
+
+    #[test]
+    fn test_nulls() {
+        let evaluator = ResidualEvaluator::new(
+            &Reference::new("region")
+                .is_null()
+                .or(!Reference::new("region").equal(Value::String("emea".to_string())))
+                .bind(&schema())
+                .unwrap(),
+            &spec(),
+        );
+        assert_eq!(
+            evaluator.residual(&partition(None, 30)).unwrap(),
+            Expression::True
+        );
+        assert_eq!(
+            evaluator.residual(&partition(Some("na"), 30)).unwrap(),
+            Expression::True
+        );
+        assert_eq!(
+            evaluator.residual(&partition(Some("emea"), 30)).unwrap(),
+            Expression::False
+        );
+    }
+}
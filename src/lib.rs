@@ -19,6 +19,7 @@ Coming soon:
 */
 pub mod arrow;
 pub mod catalog;
+pub mod expr;
 pub mod model;
 pub mod table;
 pub mod util;
@@ -5,8 +5,15 @@
 use std::any::Any;
 
 use anyhow::{anyhow, Result};
+use num_bigint::BigInt;
+use uuid::Uuid;
 
+use super::decimal::Decimal;
 use super::schema::{AllType, PrimitiveType};
+use super::types::{
+    date_from_epoch, days_from_epoch, micros_from_epoch, micros_from_midnight, time_from_midnight,
+    timestamp_from_epoch, Value,
+};
 
 /// Convert bytes to concrete type and return it as any
 pub fn bytes_to_any(bytes: &[u8], data_type: &AllType) -> Result<Box<dyn Any>> {
@@ -39,3 +46,93 @@ pub fn bytes_to_any(bytes: &[u8], data_type: &AllType) -> Result<Box<dyn Any>> {
         _ => Err(anyhow!("Only primitive types can be stored as bytes.")),
     }
 }
+
+/// Convert the single value serialization of the spec, which is used for bounds and summaries, to a value of the given type.
+pub fn bytes_to_value(bytes: &[u8], data_type: &PrimitiveType) -> Result<Value> {
+    let invalid = || anyhow!("Bytes {:?} are not a valid {} value.", bytes, data_type);
+    match data_type {
+        PrimitiveType::Boolean => Ok(Value::Boolean(bytes.iter().any(|byte| *byte != 0))),
+        PrimitiveType::Int => Ok(Value::Int(i32::from_le_bytes(bytes.try_into()?))),
+        // Values of columns that were promoted from int to long or float to double can still have 4 bytes
+        PrimitiveType::Long if bytes.len() == 4 => {
+            Ok(Value::LongInt(i32::from_le_bytes(bytes.try_into()?) as i64))
+        }
+        PrimitiveType::Long => Ok(Value::LongInt(i64::from_le_bytes(bytes.try_into()?))),
+        PrimitiveType::Float => Ok(Value::Double(f32::from_le_bytes(bytes.try_into()?))),
+        PrimitiveType::Double if bytes.len() == 4 => Ok(Value::LongFloat(f32::from_le_bytes(
+            bytes.try_into()?,
+        ) as f64)),
+        PrimitiveType::Double => Ok(Value::LongFloat(f64::from_le_bytes(bytes.try_into()?))),
+        PrimitiveType::Date => Ok(Value::Date(
+            date_from_epoch(i32::from_le_bytes(bytes.try_into()?)).ok_or_else(invalid)?,
+        )),
+        PrimitiveType::Time => Ok(Value::Time(
+            time_from_midnight(i64::from_le_bytes(bytes.try_into()?)).ok_or_else(invalid)?,
+        )),
+        PrimitiveType::Timestamp => Ok(Value::Timestamp(
+            timestamp_from_epoch(i64::from_le_bytes(bytes.try_into()?)).ok_or_else(invalid)?,
+        )),
+        PrimitiveType::Timestampz => Ok(Value::TimestampTZ(
+            timestamp_from_epoch(i64::from_le_bytes(bytes.try_into()?)).ok_or_else(invalid)?,
+        )),
+        PrimitiveType::String => Ok(Value::String(std::str::from_utf8(bytes)?.to_string())),
+        PrimitiveType::Uuid => Ok(Value::UUID(Uuid::from_slice(bytes)?)),
+        PrimitiveType::Fixed(len) => Ok(Value::Fixed(*len as usize, Vec::from(bytes))),
+        PrimitiveType::Binary => Ok(Value::Binary(Vec::from(bytes))),
+        PrimitiveType::Decimal { precision, scale } => Ok(Value::Decimal(Decimal::from_unscaled(
+            BigInt::from_signed_bytes_be(bytes),
+            u8::try_from(*precision)?,
+            *scale as u32,
+        ))),
+    }
+}
+
+/// Convert a primitive value to the single value serialization of the spec.
+pub fn value_to_bytes(value: &Value) -> Result<Vec<u8>> {
+    match value {
+        Value::Boolean(boolean) => Ok(vec![u8::from(*boolean)]),
+        Value::Int(int) => Ok(int.to_le_bytes().to_vec()),
+        Value::LongInt(long) => Ok(long.to_le_bytes().to_vec()),
+        Value::Double(float) => Ok(float.to_le_bytes().to_vec()),
+        Value::LongFloat(double) => Ok(double.to_le_bytes().to_vec()),
+        Value::Date(date) => Ok(days_from_epoch(date).to_le_bytes().to_vec()),
+        Value::Time(time) => Ok(micros_from_midnight(time).to_le_bytes().to_vec()),
+        Value::Timestamp(timestamp) | Value::TimestampTZ(timestamp) => {
+            Ok(micros_from_epoch(timestamp).to_le_bytes().to_vec())
+        }
+        Value::String(string) => Ok(string.as_bytes().to_vec()),
+        Value::UUID(uuid) => Ok(uuid.as_bytes().to_vec()),
+        Value::Fixed(_, bytes) | Value::Binary(bytes) => Ok(bytes.clone()),
+        Value::Decimal(decimal) => Ok(decimal.value().to_signed_bytes_be()),
+        _ => Err(anyhow!("Only primitive values can be stored as bytes.")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_promoted_long() {
+        assert_eq!(
+            bytes_to_value(&(-42i32).to_le_bytes(), &PrimitiveType::Long).unwrap(),
+            Value::LongInt(-42)
+        );
+        assert_eq!(
+            bytes_to_value(&(1i64 << 40).to_le_bytes(), &PrimitiveType::Long).unwrap(),
+            Value::LongInt(1 << 40)
+        );
+    }
+
+    #[test]
+    fn test_promoted_double() {
+        assert_eq!(
+            bytes_to_value(&1.5f32.to_le_bytes(), &PrimitiveType::Double).unwrap(),
+            Value::LongFloat(1.5)
+        );
+        assert_eq!(
+            bytes_to_value(&0.1f64.to_le_bytes(), &PrimitiveType::Double).unwrap(),
+            Value::LongFloat(0.1)
+        );
+    }
+}
@@ -36,6 +36,34 @@ impl Decimal {
             scale,
         })
     }
+    /// Create a decimal from an unscaled value whose precision and scale are given by the type of a column.
+    pub(crate) fn from_unscaled(value: num_bigint::BigInt, precision: u8, scale: u32) -> Self {
+        Decimal {
+            value,
+            precision,
+            scale,
+        }
+    }
+    /// Get the unscaled value of the decimal.
+    pub fn value(&self) -> &num_bigint::BigInt {
+        &self.value
+    }
+    /// Get the precision of the decimal.
+    pub fn precision(&self) -> u8 {
+        self.precision
+    }
+    /// Get the scale of the decimal.
+    pub fn scale(&self) -> u32 {
+        self.scale
+    }
+    /// Create a decimal with the same precision and scale but a different unscaled value.
+    pub(crate) fn with_value(&self, value: num_bigint::BigInt) -> Self {
+        Decimal {
+            value,
+            precision: self.precision,
+            scale: self.scale,
+        }
+    }
 }
 
 fn max_prec_for_len(len: usize) -> Result<usize> {
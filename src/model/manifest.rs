@@ -229,6 +229,13 @@ impl ManifestEntry {
             ManifestEntry::V2(entry) => entry.data_file.file_size_in_bytes,
         }
     }
+    /// Split offsets for the data file, for example the offsets of the row groups of a Parquet file
+    pub fn split_offsets(&self) -> &Option<Vec<i64>> {
+        match self {
+            ManifestEntry::V1(entry) => &entry.data_file.split_offsets,
+            ManifestEntry::V2(entry) => &entry.data_file.split_offsets,
+        }
+    }
     /// Map from column id to lower bound in the column
     pub fn lower_bounds(&self) -> &Option<AvroMap<ByteBuf>> {
         match self {
@@ -250,6 +257,55 @@ impl ManifestEntry {
             ManifestEntry::V2(entry) => &entry.data_file.null_value_counts,
         }
     }
+    /// Status of the entry, which tracks additions and deletions
+    pub fn status(&self) -> &Status {
+        match self {
+            ManifestEntry::V1(entry) => &entry.status,
+            ManifestEntry::V2(entry) => &entry.status,
+        }
+    }
+    /// Snapshot id where the file was added, or deleted if the status is deleted. Inherited from the manifest when null.
+    pub fn snapshot_id(&self) -> Option<i64> {
+        match self {
+            ManifestEntry::V1(entry) => Some(entry.snapshot_id),
+            ManifestEntry::V2(entry) => entry.snapshot_id,
+        }
+    }
+    /// Sequence number when the file was added. Inherited from the manifest when null, 0 for v1 entries.
+    pub fn sequence_number(&self) -> Option<i64> {
+        match self {
+            ManifestEntry::V1(_) => Some(0),
+            ManifestEntry::V2(entry) => entry.sequence_number,
+        }
+    }
+    /// Type of content stored in the file, v1 entries only track data files
+    pub fn content(&self) -> Content {
+        match self {
+            ManifestEntry::V1(_) => Content::Data,
+            ManifestEntry::V2(entry) => entry.data_file.content.clone(),
+        }
+    }
+    /// Number of records in the file
+    pub fn record_count(&self) -> i64 {
+        match self {
+            ManifestEntry::V1(entry) => entry.data_file.record_count,
+            ManifestEntry::V2(entry) => entry.data_file.record_count,
+        }
+    }
+    /// Map from column id to number of values in the column (including null and NaN values)
+    pub fn value_counts(&self) -> &Option<AvroMap<i64>> {
+        match self {
+            ManifestEntry::V1(entry) => &entry.data_file.value_counts,
+            ManifestEntry::V2(entry) => &entry.data_file.value_counts,
+        }
+    }
+    /// Map from column id to number of NaN values
+    pub fn nan_value_counts(&self) -> &Option<AvroMap<i64>> {
+        match self {
+            ManifestEntry::V1(entry) => &entry.data_file.nan_value_counts,
+            ManifestEntry::V2(entry) => &entry.data_file.nan_value_counts,
+        }
+    }
 }
 
 #[derive(Debug, Serialize_repr, Deserialize_repr, PartialEq, Eq, Clone)]
@@ -360,7 +416,7 @@ impl PartitionValues {
                         + &field.name
                         + r#"", 
                     "type":  ["null",""#
-                        + &format!("{}", &schema_field.field_type)
+                        + &format!("{}", field.transform.result_type(&schema_field.field_type)?)
                         + r#""],
                     "default": null
                 },"#,
@@ -379,6 +435,16 @@ impl PartitionValues {
             .to_owned()
             + r#"]}"#)
     }
+    /// Get the names of the partition fields in the order of the values
+    pub fn names(&self) -> Vec<&str> {
+        let mut names: Vec<(&str, usize)> = self
+            .lookup
+            .iter()
+            .map(|(name, index)| (name.as_str(), *index))
+            .collect();
+        names.sort_by_key(|(_, index)| *index);
+        names.into_iter().map(|(name, _)| name).collect()
+    }
 }
 
 impl Deref for PartitionValues {
@@ -472,6 +538,12 @@ impl<T: Serialize + Clone> core::ops::Deref for AvroMap<T> {
     }
 }
 
+impl<T: Serialize + Clone> From<HashMap<i32, T>> for AvroMap<T> {
+    fn from(map: HashMap<i32, T>) -> Self {
+        AvroMap(map)
+    }
+}
+
 impl<T: Serialize + Clone> Serialize for AvroMap<T> {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -609,6 +681,32 @@ impl From<DataFileV1> for DataFileV2 {
     }
 }
 
+impl From<DataFileV2> for DataFileV1 {
+    fn from(v2: DataFileV2) -> Self {
+        DataFileV1 {
+            file_path: v2.file_path,
+            file_format: v2.file_format,
+            partition: v2.partition,
+            record_count: v2.record_count,
+            file_size_in_bytes: v2.file_size_in_bytes,
+            // Deprecated, v1 writers always write the default of 64 MB
+            block_size_in_bytes: 64 * 1024 * 1024,
+            file_ordinal: None,
+            sort_columns: None,
+            column_sizes: v2.column_sizes,
+            value_counts: v2.value_counts,
+            null_value_counts: v2.null_value_counts,
+            nan_value_counts: v2.nan_value_counts,
+            distinct_counts: v2.distinct_counts,
+            lower_bounds: v2.lower_bounds,
+            upper_bounds: v2.upper_bounds,
+            key_metadata: v2.key_metadata,
+            split_offsets: v2.split_offsets,
+            sort_order_id: v2.sort_order_id,
+        }
+    }
+}
+
 impl DataFileV1 {
     /// Get schema
     pub fn schema(partition_schema: &str) -> String {
@@ -1325,7 +1423,7 @@ mod tests {
                             id: 4,
                             name: "day".to_owned(),
                             required: false,
-                            field_type: AllType::Primitive(PrimitiveType::Int),
+                            field_type: AllType::Primitive(PrimitiveType::Date),
                             doc: None,
                         }],
                     },
@@ -1399,7 +1497,7 @@ mod tests {
                             id: 4,
                             name: "day".to_owned(),
                             required: false,
-                            field_type: AllType::Primitive(PrimitiveType::Int),
+                            field_type: AllType::Primitive(PrimitiveType::Date),
                             doc: None,
                         }],
                     },
@@ -1478,7 +1576,7 @@ mod tests {
                     id: 4,
                     name: "day".to_owned(),
                     required: false,
-                    field_type: AllType::Primitive(PrimitiveType::Int),
+                    field_type: AllType::Primitive(PrimitiveType::Date),
                     doc: None,
                 }],
             },
@@ -1554,7 +1652,7 @@ mod tests {
                     id: 4,
                     name: "day".to_owned(),
                     required: false,
-                    field_type: AllType::Primitive(PrimitiveType::Int),
+                    field_type: AllType::Primitive(PrimitiveType::Date),
                     doc: None,
                 }],
             },
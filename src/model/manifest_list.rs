@@ -402,6 +402,13 @@ impl ManifestFile {
             ManifestFile::V2(file) => &file.manifest_path,
         }
     }
+    /// Length of the manifest file in bytes
+    pub fn manifest_length(&self) -> i64 {
+        match self {
+            ManifestFile::V1(file) => file.manifest_length,
+            ManifestFile::V2(file) => file.manifest_length,
+        }
+    }
     /// ID of a partition spec used to write the manifest; must be listed in table metadata partition-specs
     pub fn partition_spec_id(&self) -> i32 {
         match self {
@@ -416,6 +423,27 @@ impl ManifestFile {
             ManifestFile::V2(file) => &file.partitions,
         }
     }
+    /// The type of files tracked by the manifest, either data or delete files
+    pub fn content(&self) -> Content {
+        match self {
+            ManifestFile::V1(_) => Content::Data,
+            ManifestFile::V2(file) => file.content.clone(),
+        }
+    }
+    /// The sequence number when the manifest was added to the table, 0 for v1 manifests
+    pub fn sequence_number(&self) -> i64 {
+        match self {
+            ManifestFile::V1(_) => 0,
+            ManifestFile::V2(file) => file.sequence_number,
+        }
+    }
+    /// ID of the snapshot where the manifest file was added
+    pub fn added_snapshot_id(&self) -> i64 {
+        match self {
+            ManifestFile::V1(file) => file.added_snapshot_id,
+            ManifestFile::V2(file) => file.added_snapshot_id,
+        }
+    }
     /// Number of entries in the manifest that have status ADDED (1), when null this is assumed to be non-zero
     pub fn added_files_count(&self) -> std::option::Option<i32> {
         match self {
@@ -5,13 +5,19 @@ from the source fields.
 The [PartitionSpec] is composed of multiple [PartitionField] each of which together define how
 the [TableMetadataV2](crate::model::table::TableMetadataV2) is partitioned.
 */
+use anyhow::{anyhow, Result};
+use chrono::{Datelike, NaiveDate};
 use lazy_static::lazy_static;
+use num_bigint::BigInt;
 use regex::Regex;
 use serde::{
     de::{self, IntoDeserializer},
     Deserialize, Deserializer, Serialize,
 };
 
+use super::schema::{AllType, PrimitiveType};
+use super::types::{days_from_epoch, micros_from_epoch, micros_from_midnight, Value};
+
 #[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
 #[serde(rename_all = "lowercase", remote = "Self")]
 /// A Transformation applied to each source column to produce a value.
@@ -64,6 +70,182 @@ impl Serialize for Transform {
     }
 }
 
+impl Transform {
+    /// Get the type of the partition values the transform produces for a source column of the given type. Fails if
+    /// the transform can't be applied to the type.
+    pub fn result_type(&self, source_type: &AllType) -> Result<AllType> {
+        use PrimitiveType::*;
+        let primitive = match source_type {
+            AllType::Primitive(primitive) => Some(primitive),
+            _ => None,
+        };
+        match (self, primitive) {
+            (Transform::Identity, Some(_)) | (Transform::Void, _) => Ok(source_type.clone()),
+            (
+                Transform::Bucket(_),
+                Some(
+                    Int
+                    | Long
+                    | Decimal { .. }
+                    | Date
+                    | Time
+                    | Timestamp
+                    | Timestampz
+                    | String
+                    | Uuid
+                    | Fixed(_)
+                    | Binary,
+                ),
+            )
+            | (
+                Transform::Year | Transform::Month | Transform::Day,
+                Some(Date | Timestamp | Timestampz),
+            )
+            | (Transform::Hour, Some(Timestamp | Timestampz)) => Ok(AllType::Primitive(Int)),
+            (Transform::Truncate(_), Some(Int | Long | Decimal { .. } | String | Binary)) => {
+                Ok(source_type.clone())
+            }
+            _ => Err(anyhow!(
+                "Transform {:?} can't be applied to type {}.",
+                self,
+                source_type
+            )),
+        }
+    }
+    /// Apply the transform to a value to get the partition value. Null values are always transformed to null.
+    pub fn apply(&self, value: &Value) -> Result<Option<Value>> {
+        match (self, value) {
+            (Transform::Void, _) => Ok(None),
+            (Transform::Identity, _) => Ok(Some(value.clone())),
+            (Transform::Bucket(0), _) => Err(anyhow!("The number of buckets must be positive.")),
+            (Transform::Bucket(n), _) => {
+                let n = i32::try_from(*n).map_err(|_| {
+                    anyhow!(
+                        "The number of buckets must not be larger than {}.",
+                        i32::MAX
+                    )
+                })?;
+                Ok(Some(Value::Int((hash(value)? & i32::MAX) % n)))
+            }
+            (Transform::Truncate(0), _) => Err(anyhow!("The truncation width must be positive.")),
+            (Transform::Truncate(width), _) => truncate(value, *width).map(Some),
+            (Transform::Year, Value::Date(date)) => Ok(Some(Value::Int(date.year() - 1970))),
+            (Transform::Year, Value::Timestamp(timestamp) | Value::TimestampTZ(timestamp)) => {
+                Ok(Some(Value::Int(timestamp.year() - 1970)))
+            }
+            (Transform::Month, Value::Date(date)) => Ok(Some(Value::Int(months(date)))),
+            (Transform::Month, Value::Timestamp(timestamp) | Value::TimestampTZ(timestamp)) => {
+                Ok(Some(Value::Int(months(&timestamp.date()))))
+            }
+            (Transform::Day, Value::Date(date)) => Ok(Some(Value::Int(days_from_epoch(date)))),
+            (Transform::Day, Value::Timestamp(timestamp) | Value::TimestampTZ(timestamp)) => {
+                Ok(Some(Value::Int(
+                    micros_from_epoch(timestamp).div_euclid(MICROS_PER_DAY) as i32,
+                )))
+            }
+            (Transform::Hour, Value::Timestamp(timestamp) | Value::TimestampTZ(timestamp)) => {
+                Ok(Some(Value::Int(
+                    micros_from_epoch(timestamp).div_euclid(MICROS_PER_HOUR) as i32,
+                )))
+            }
+            _ => Err(anyhow!(
+                "Transform {:?} can't be applied to value {:?}.",
+                self,
+                value
+            )),
+        }
+    }
+}
+
+const MICROS_PER_HOUR: i64 = 3_600_000_000;
+const MICROS_PER_DAY: i64 = 86_400_000_000;
+
+/// Months from 1970-01-01
+fn months(date: &NaiveDate) -> i32 {
+    (date.year() - 1970) * 12 + date.month0() as i32
+}
+
+/// Hash a value with the 32-bit Murmur3 hash as defined by the [spec](https://iceberg.apache.org/spec/#appendix-b-32-bit-hash-requirements).
+/// Integers, dates and times are hashed like longs, so that promoting the type keeps the hash.
+fn hash(value: &Value) -> Result<i32> {
+    match value {
+        Value::Int(int) => Ok(murmur3_32(&(*int as i64).to_le_bytes())),
+        Value::LongInt(long) => Ok(murmur3_32(&long.to_le_bytes())),
+        Value::Date(date) => Ok(murmur3_32(&(days_from_epoch(date) as i64).to_le_bytes())),
+        Value::Time(time) => Ok(murmur3_32(&micros_from_midnight(time).to_le_bytes())),
+        Value::Timestamp(timestamp) | Value::TimestampTZ(timestamp) => {
+            Ok(murmur3_32(&micros_from_epoch(timestamp).to_le_bytes()))
+        }
+        Value::String(string) => Ok(murmur3_32(string.as_bytes())),
+        Value::UUID(uuid) => Ok(murmur3_32(uuid.as_bytes())),
+        Value::Fixed(_, bytes) | Value::Binary(bytes) => Ok(murmur3_32(bytes)),
+        Value::Decimal(decimal) => Ok(murmur3_32(&decimal.value().to_signed_bytes_be())),
+        _ => Err(anyhow!("Value {:?} can't be hashed for bucketing.", value)),
+    }
+}
+
+/// Truncate a value to the given width.
+fn truncate(value: &Value, width: u32) -> Result<Value> {
+    match value {
+        Value::Int(int) => {
+            let width = i32::try_from(width).map_err(|_| {
+                anyhow!(
+                    "The truncation width of integers must not be larger than {}.",
+                    i32::MAX
+                )
+            })?;
+            Ok(Value::Int(int.wrapping_sub(int.rem_euclid(width))))
+        }
+        Value::LongInt(long) => Ok(Value::LongInt(
+            long.wrapping_sub(long.rem_euclid(width as i64)),
+        )),
+        Value::Decimal(decimal) => {
+            let width = BigInt::from(width);
+            let remainder = ((decimal.value() % &width) + &width) % &width;
+            Ok(Value::Decimal(
+                decimal.with_value(decimal.value() - remainder),
+            ))
+        }
+        Value::String(string) => Ok(Value::String(string.chars().take(width as usize).collect())),
+        Value::Binary(bytes) => Ok(Value::Binary(
+            bytes.iter().take(width as usize).cloned().collect(),
+        )),
+        _ => Err(anyhow!("Value {:?} can't be truncated.", value)),
+    }
+}
+
+/// 32-bit x86 variant of the Murmur3 hash with seed 0
+fn murmur3_32(data: &[u8]) -> i32 {
+    const C1: u32 = 0xcc9e_2d51;
+    const C2: u32 = 0x1b87_3593;
+
+    let mix = |k: u32| k.wrapping_mul(C1).rotate_left(15).wrapping_mul(C2);
+
+    let mut hash: u32 = 0;
+    let chunks = data.chunks_exact(4);
+    let tail = chunks.remainder();
+    for chunk in chunks {
+        hash ^= mix(u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]));
+        hash = hash
+            .rotate_left(13)
+            .wrapping_mul(5)
+            .wrapping_add(0xe654_6b64);
+    }
+    if !tail.is_empty() {
+        hash ^= mix(tail
+            .iter()
+            .enumerate()
+            .fold(0, |k, (i, byte)| k ^ ((*byte as u32) << (8 * i))));
+    }
+    hash ^= data.len() as u32;
+    hash ^= hash >> 16;
+    hash = hash.wrapping_mul(0x85eb_ca6b);
+    hash ^= hash >> 13;
+    hash = hash.wrapping_mul(0xc2b2_ae35);
+    hash ^= hash >> 16;
+    hash as i32
+}
+
 fn deserialize_bucket<'de, D>(deserializer: D) -> Result<Transform, D::Error>
 where
     D: Deserializer<'de>,
@@ -112,7 +294,7 @@ where
     Ok(Transform::Truncate(width))
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
 #[serde(rename_all = "kebab-case")]
 /// Tables are configured with a partition spec that defines how to produce a tuple of partition values from a record.
 pub struct PartitionField {
@@ -127,7 +309,7 @@ pub struct PartitionField {
     pub transform: Transform,
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
 #[serde(rename_all = "kebab-case")]
 /// A definition of how partition values are derived from data fields.
 pub struct PartitionSpec {
@@ -139,6 +321,8 @@ pub struct PartitionSpec {
 
 #[cfg(test)]
 mod tests {
+    use chrono::NaiveTime;
+
     use super::*;
 
     #[test]
@@ -159,6 +343,185 @@ mod tests {
         assert_eq!(Transform::Day, partition_field.transform);
     }
 
+    #[test]
+    fn test_bucket_hash() {
+        // Test vectors from https://iceberg.apache.org/spec/#appendix-b-32-bit-hash-requirements
+        let timestamp = NaiveDate::from_ymd_opt(2017, 11, 16)
+            .unwrap()
+            .and_hms_opt(22, 31, 8)
+            .unwrap();
+        let cases = vec![
+            (Value::Int(34), 2017239379),
+            (Value::LongInt(34), 2017239379),
+            (
+                Value::Decimal(
+                    crate::model::decimal::Decimal::new(BigInt::from(1420), 4, 2).unwrap(),
+                ),
+                -500754589,
+            ),
+            (
+                Value::Date(NaiveDate::from_ymd_opt(2017, 11, 16).unwrap()),
+                -653330422,
+            ),
+            (
+                Value::Time(NaiveTime::from_hms_opt(22, 31, 8).unwrap()),
+                -662762989,
+            ),
+            (Value::Timestamp(timestamp), -2047944441),
+            (Value::TimestampTZ(timestamp), -2047944441),
+            (Value::String("iceberg".to_string()), 1210000089),
+            (
+                Value::UUID(uuid::Uuid::parse_str("f79c3e09-677c-4bbd-a479-3f349cb785e7").unwrap()),
+                1488055340,
+            ),
+            (Value::Fixed(4, vec![0, 1, 2, 3]), -188683207),
+            (Value::Binary(vec![0, 1, 2, 3]), -188683207),
+        ];
+        for (value, expected) in cases {
+            assert_eq!(hash(&value).unwrap(), expected, "{:?}", value);
+        }
+        assert!(hash(&Value::Boolean(true)).is_err());
+    }
+
+    #[test]
+    fn test_bucket() {
+        assert_eq!(
+            Transform::Bucket(16).apply(&Value::Int(34)).unwrap(),
+            Some(Value::Int(2017239379 % 16))
+        );
+        assert_eq!(
+            Transform::Bucket(100)
+                .apply(&Value::Binary(vec![0, 1, 2, 3]))
+                .unwrap(),
+            Some(Value::Int((-188683207 & i32::MAX) % 100))
+        );
+        assert!(Transform::Bucket(0).apply(&Value::Int(34)).is_err());
+        assert!(Transform::Bucket(i32::MAX as u32 + 1)
+            .apply(&Value::Int(34))
+            .is_err());
+    }
+
+    #[test]
+    fn test_truncate() {
+        let transform = Transform::Truncate(10);
+        assert_eq!(
+            transform.apply(&Value::Int(1)).unwrap(),
+            Some(Value::Int(0))
+        );
+        assert_eq!(
+            transform.apply(&Value::Int(-1)).unwrap(),
+            Some(Value::Int(-10))
+        );
+        assert_eq!(
+            transform.apply(&Value::LongInt(-1)).unwrap(),
+            Some(Value::LongInt(-10))
+        );
+        assert_eq!(
+            transform
+                .apply(&Value::Decimal(
+                    crate::model::decimal::Decimal::new(BigInt::from(1065), 4, 2).unwrap()
+                ))
+                .unwrap(),
+            Some(Value::Decimal(
+                crate::model::decimal::Decimal::new(BigInt::from(1060), 4, 2).unwrap()
+            ))
+        );
+        assert_eq!(
+            Transform::Truncate(3)
+                .apply(&Value::String("iceberg".to_string()))
+                .unwrap(),
+            Some(Value::String("ice".to_string()))
+        );
+        assert_eq!(
+            Transform::Truncate(3)
+                .apply(&Value::Binary(vec![0, 1, 2, 3]))
+                .unwrap(),
+            Some(Value::Binary(vec![0, 1, 2]))
+        );
+        assert!(transform.apply(&Value::Boolean(true)).is_err());
+        let transform = Transform::Truncate(i32::MAX as u32 + 1);
+        assert!(transform.apply(&Value::Int(1)).is_err());
+        assert_eq!(
+            transform.apply(&Value::LongInt(-1)).unwrap(),
+            Some(Value::LongInt(-(i32::MAX as i64 + 1)))
+        );
+    }
+
+    #[test]
+    fn test_time_transforms() {
+        let date = NaiveDate::from_ymd_opt(2017, 11, 16).unwrap();
+        let timestamp = date.and_hms_opt(22, 31, 8).unwrap();
+        assert_eq!(
+            Transform::Year.apply(&Value::Date(date)).unwrap(),
+            Some(Value::Int(47))
+        );
+        assert_eq!(
+            Transform::Month
+                .apply(&Value::Timestamp(timestamp))
+                .unwrap(),
+            Some(Value::Int(574))
+        );
+        assert_eq!(
+            Transform::Day.apply(&Value::Date(date)).unwrap(),
+            Some(Value::Int(17486))
+        );
+        assert_eq!(
+            Transform::Day
+                .apply(&Value::TimestampTZ(timestamp))
+                .unwrap(),
+            Some(Value::Int(17486))
+        );
+        assert_eq!(
+            Transform::Hour.apply(&Value::Timestamp(timestamp)).unwrap(),
+            Some(Value::Int(17486 * 24 + 22))
+        );
+        // Values before the epoch are rounded down
+        let before_epoch = NaiveDate::from_ymd_opt(1969, 12, 31)
+            .unwrap()
+            .and_hms_opt(23, 0, 0)
+            .unwrap();
+        assert_eq!(
+            Transform::Day
+                .apply(&Value::Timestamp(before_epoch))
+                .unwrap(),
+            Some(Value::Int(-1))
+        );
+        assert_eq!(
+            Transform::Month
+                .apply(&Value::Timestamp(before_epoch))
+                .unwrap(),
+            Some(Value::Int(-1))
+        );
+        assert_eq!(
+            Transform::Year
+                .apply(&Value::Timestamp(before_epoch))
+                .unwrap(),
+            Some(Value::Int(-1))
+        );
+        assert!(Transform::Hour.apply(&Value::Date(date)).is_err());
+        assert_eq!(Transform::Void.apply(&Value::Date(date)).unwrap(), None);
+    }
+
+    #[test]
+    fn test_result_type() {
+        let int = AllType::Primitive(PrimitiveType::Int);
+        let string = AllType::Primitive(PrimitiveType::String);
+        let timestamp = AllType::Primitive(PrimitiveType::Timestamp);
+        assert_eq!(Transform::Identity.result_type(&string).unwrap(), string);
+        assert_eq!(Transform::Void.result_type(&string).unwrap(), string);
+        assert_eq!(Transform::Bucket(16).result_type(&string).unwrap(), int);
+        assert_eq!(Transform::Truncate(3).result_type(&string).unwrap(), string);
+        assert_eq!(Transform::Day.result_type(&timestamp).unwrap(), int);
+        assert_eq!(Transform::Hour.result_type(&timestamp).unwrap(), int);
+        assert!(Transform::Hour
+            .result_type(&AllType::Primitive(PrimitiveType::Date))
+            .is_err());
+        assert!(Transform::Bucket(16)
+            .result_type(&AllType::Primitive(PrimitiveType::Double))
+            .is_err());
+        assert!(Transform::Year.result_type(&string).is_err());
+    }
+
     #[test]
     fn test_all_transforms() {
         let transforms = vec![
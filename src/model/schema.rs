@@ -173,7 +173,7 @@ where
     Ok(PrimitiveType::Fixed(length))
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
 #[serde(untagged)]
 /// A union type of all allowed Schema types.
 pub enum AllType {
@@ -198,7 +198,7 @@ impl fmt::Display for AllType {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
 #[serde(tag = "type")]
 /// A struct is a tuple of typed values. Each field in the tuple is
 /// named and has an integer id that is unique in the table schema.
@@ -217,7 +217,7 @@ impl SchemaStruct {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
 /// Details of a struct in a field.
 pub struct StructField {
     /// Unique Id
@@ -234,7 +234,7 @@ pub struct StructField {
 }
 
 /// Schema of an iceberg table
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
 #[serde(untagged)]
 pub enum Schema {
     /// Version 2 of the table schema
@@ -253,7 +253,7 @@ impl Schema {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
 #[serde(rename_all = "kebab-case")]
 /// Names and types of fields in a table.
 pub struct SchemaV2 {
@@ -270,7 +270,7 @@ pub struct SchemaV2 {
     pub struct_fields: SchemaStruct,
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
 #[serde(rename_all = "kebab-case")]
 /// Names and types of fields in a table.
 pub struct SchemaV1 {
@@ -298,7 +298,7 @@ impl From<SchemaV1> for SchemaV2 {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
 #[serde(rename_all = "kebab-case", tag = "list")]
 /// A Schema type that contains List  elements.
 pub struct List {
@@ -312,7 +312,7 @@ pub struct List {
     pub element: Box<AllType>,
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
 #[serde(rename_all = "kebab-case", tag = "type")]
 /// A Schema type that contains Map elements.
 /// A map is a collection of key-value pairs with a key type and a value type.
@@ -333,7 +333,7 @@ pub struct Map {
     pub value: Box<AllType>,
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
 /// Tables may also define a property schema.name-mapping.default with a JSON name mapping containing a list of field mapping objects.
 /// These mappings provide fallback field ids to be used when a data file does not contain field id information.
 pub struct NameMappings {
@@ -341,7 +341,7 @@ pub struct NameMappings {
     pub default: Vec<NameMapping>,
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
 #[serde(rename_all = "kebab-case")]
 /// Individual mapping within NameMappings.
 pub struct NameMapping {
@@ -11,7 +11,7 @@ use std::collections::HashMap;
 
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
 #[serde(rename_all = "lowercase")]
 /// The type of operations included in the snapshot, this allows
 /// certain snapshots to be skipped during operation.
@@ -30,7 +30,7 @@ pub enum Operation {
     Delete,
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
 /// Summarises the changes in the snapshot.
 pub struct Summary {
     /// The type of operation in the snapshot
@@ -40,7 +40,7 @@ pub struct Summary {
     pub other: HashMap<String, String>,
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
 #[serde(rename_all = "kebab-case")]
 /// A V2 compliant snapshot.
 pub struct SnapshotV2 {
@@ -64,7 +64,7 @@ pub struct SnapshotV2 {
     pub schema_id: Option<i64>,
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
 #[serde(rename_all = "kebab-case")]
 /// A V1 compliant snapshot.
 pub struct SnapshotV1 {
@@ -104,7 +104,7 @@ impl From<SnapshotV1> for SnapshotV2 {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
 #[serde(rename_all = "kebab-case")]
 /// Iceberg tables keep track of branches and tags using snapshot references.
 pub struct Reference {
@@ -115,7 +115,7 @@ pub struct Reference {
     pub retention: Retention,
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
 #[serde(rename_all = "lowercase", tag = "type")]
 /// Retention policy field, which differ based on it it
 /// is a Branch or Tag Reference
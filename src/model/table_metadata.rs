@@ -5,12 +5,12 @@ The main struct here is [TableMetadataV2] which defines the data for a table.
 
 use anyhow::{anyhow, Ok};
 
-use std::{cmp, collections::HashMap};
+use std::{borrow::Cow, cmp, collections::HashMap};
 
 use crate::model::{
     partition::PartitionSpec,
     schema,
-    snapshot::{Reference, SnapshotV1, SnapshotV2},
+    snapshot::{Operation, Reference, SnapshotV1, SnapshotV2},
     sort,
 };
 
@@ -20,8 +20,11 @@ use uuid::Uuid;
 
 use super::{partition::PartitionField, schema::SchemaStruct};
 
+/// Name of the branch that always points to the current snapshot of a table
+pub static MAIN_BRANCH: &str = "main";
+
 /// Metadata of an iceberg table
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
 #[serde(untagged)]
 pub enum TableMetadata {
     /// Version 2 of the table metadata
@@ -30,7 +33,7 @@ pub enum TableMetadata {
     V1(TableMetadataV1),
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
 #[serde(rename_all = "kebab-case", tag = "format-version")]
 /// Fields for the version 2 of the table metadata.
 pub struct TableMetadataV2 {
@@ -96,7 +99,7 @@ pub struct TableMetadataV2 {
     pub refs: Option<HashMap<String, Reference>>,
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
 #[serde(rename_all = "kebab-case", tag = "format-version")]
 /// Fields for the version 1 of the table metadata.
 pub struct TableMetadataV1 {
@@ -210,7 +213,7 @@ impl From<TableMetadataV1> for TableMetadataV2 {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
 #[serde(rename_all = "kebab-case")]
 /// Encodes changes to the previous metadata files for the table
 pub struct MetadataLog {
@@ -220,7 +223,7 @@ pub struct MetadataLog {
     pub timestamp_ms: i64,
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
 #[serde(rename_all = "kebab-case")]
 /// A log of when each snapshot was made.
 pub struct SnapshotLog {
@@ -291,6 +294,13 @@ impl TableMetadata {
             }
         }
     }
+    /// Get the id of the default partition spec for the table
+    pub fn default_spec_id(&self) -> i32 {
+        match self {
+            TableMetadata::V1(metadata) => metadata.default_spec_id.unwrap_or(0),
+            TableMetadata::V2(metadata) => metadata.default_spec_id,
+        }
+    }
     /// Get the partition spec with thte given spec_id for the table
     pub fn get_spec(&self, id: i32) -> Option<&[PartitionField]> {
         match self {
@@ -306,30 +316,203 @@ impl TableMetadata {
                 .map(|spec| spec.fields.as_slice()),
         }
     }
+    /// Get the id of the current snapshot of the table
+    pub fn current_snapshot_id(&self) -> Option<i64> {
+        match self {
+            TableMetadata::V1(metadata) => metadata.current_snapshot_id,
+            TableMetadata::V2(metadata) => metadata.current_snapshot_id,
+        }
+    }
     /// Get the manifest_list for the current snapshot of the table
     pub fn manifest_list(&self) -> Option<&str> {
+        self.current_snapshot_id()
+            .and_then(|id| self.snapshot_manifest_list(id))
+    }
+    /// Get the manifest_list for the snapshot with the given id
+    pub fn snapshot_manifest_list(&self, snapshot_id: i64) -> Option<&str> {
+        match self {
+            TableMetadata::V1(metadata) => metadata.snapshots.as_ref().and_then(|snapshots| {
+                snapshots
+                    .iter()
+                    .find(|snapshot| snapshot.snapshot_id == snapshot_id)
+                    .and_then(|snapshot| snapshot.manifest_list.as_deref())
+            }),
+            TableMetadata::V2(metadata) => metadata.snapshots.as_ref().and_then(|snapshots| {
+                snapshots
+                    .iter()
+                    .find(|snapshot| snapshot.snapshot_id == snapshot_id)
+                    .map(|snapshot| snapshot.manifest_list.as_str())
+            }),
+        }
+    }
+    /// Get the manifest lists of all snapshots of the table
+    pub fn manifest_lists(&self) -> Vec<&str> {
         match self {
             TableMetadata::V1(metadata) => metadata
                 .snapshots
-                .as_ref()
-                .zip(metadata.current_snapshot_id.as_ref())
-                .and_then(|(snapshots, id)| {
-                    snapshots
-                        .iter()
-                        .find(|snapshot| snapshot.snapshot_id == *id)
-                        .and_then(|snapshot| snapshot.manifest_list.as_deref())
-                }),
+                .iter()
+                .flatten()
+                .filter_map(|snapshot| snapshot.manifest_list.as_deref())
+                .collect(),
             TableMetadata::V2(metadata) => metadata
                 .snapshots
+                .iter()
+                .flatten()
+                .map(|snapshot| snapshot.manifest_list.as_str())
+                .collect(),
+        }
+    }
+    /// Get the log of the previous metadata files of the table
+    pub fn metadata_log(&self) -> &[MetadataLog] {
+        match self {
+            TableMetadata::V1(metadata) => metadata.metadata_log.as_deref().unwrap_or_default(),
+            TableMetadata::V2(metadata) => metadata.metadata_log.as_deref().unwrap_or_default(),
+        }
+    }
+    /// Get the id of the snapshot a branch or tag points to. The main branch points to the current snapshot.
+    pub fn snapshot_id_for_ref(&self, name: &str) -> Option<i64> {
+        match self {
+            TableMetadata::V1(_) => None,
+            TableMetadata::V2(metadata) => metadata
+                .refs
                 .as_ref()
-                .zip(metadata.current_snapshot_id.as_ref())
-                .and_then(|(snapshots, id)| {
-                    snapshots
+                .and_then(|refs| refs.get(name))
+                .map(|reference| reference.snapshot_id),
+        }
+        .or_else(|| {
+            if name == MAIN_BRANCH {
+                self.current_snapshot_id()
+            } else {
+                None
+            }
+        })
+    }
+    /// Get the schema of the table at the time the snapshot was created. Returns the current schema if the snapshot
+    /// doesn't record its schema and None if the snapshot doesn't exist.
+    pub fn snapshot_schema(&self, snapshot_id: i64) -> Option<&SchemaStruct> {
+        match self {
+            TableMetadata::V1(metadata) => {
+                let snapshot = metadata
+                    .snapshots
+                    .as_ref()?
+                    .iter()
+                    .find(|snapshot| snapshot.snapshot_id == snapshot_id)?;
+                snapshot
+                    .schema_id
+                    .and_then(|schema_id| {
+                        metadata.schemas.as_ref().and_then(|schemas| {
+                            schemas
+                                .iter()
+                                .find(|schema| schema.schema_id == Some(schema_id as i32))
+                        })
+                    })
+                    .map(|schema| &schema.struct_fields)
+            }
+            TableMetadata::V2(metadata) => {
+                let snapshot = metadata
+                    .snapshots
+                    .as_ref()?
+                    .iter()
+                    .find(|snapshot| snapshot.snapshot_id == snapshot_id)?;
+                snapshot
+                    .schema_id
+                    .and_then(|schema_id| {
+                        metadata
+                            .schemas
+                            .iter()
+                            .find(|schema| schema.schema_id as i64 == schema_id)
+                    })
+                    .map(|schema| &schema.struct_fields)
+            }
+        }
+        .or_else(|| Some(self.current_schema()))
+    }
+    /// Get the snapshot with the given id. Version 1 snapshots are converted to version 2 snapshots with a sequence
+    /// number of 0.
+    pub fn snapshot(&self, snapshot_id: i64) -> Option<Cow<'_, SnapshotV2>> {
+        match self {
+            TableMetadata::V1(metadata) => metadata
+                .snapshots
+                .as_ref()?
+                .iter()
+                .find(|snapshot| snapshot.snapshot_id == snapshot_id)
+                .map(|snapshot| Cow::Owned(snapshot.clone().into())),
+            TableMetadata::V2(metadata) => metadata
+                .snapshots
+                .as_ref()?
+                .iter()
+                .find(|snapshot| snapshot.snapshot_id == snapshot_id)
+                .map(Cow::Borrowed),
+        }
+    }
+    /// Get the id of the parent of the snapshot. Returns None if the snapshot has no parent or doesn't exist.
+    pub fn parent_snapshot_id(&self, snapshot_id: i64) -> Option<i64> {
+        self.snapshot(snapshot_id)?.parent_snapshot_id
+    }
+    /// Get the operation recorded in the summary of the snapshot. Returns None if the snapshot doesn't record its
+    /// operation or doesn't exist.
+    pub fn snapshot_operation(&self, snapshot_id: i64) -> Option<Operation> {
+        self.snapshot(snapshot_id)?.summary.operation.clone()
+    }
+    /// Get the snapshot and its ancestors, from the snapshot to the oldest ancestor that still exists in the table.
+    /// Returns an empty list if the snapshot doesn't exist.
+    pub fn ancestors_of(&self, snapshot_id: i64) -> Vec<Cow<'_, SnapshotV2>> {
+        let mut ancestors: Vec<Cow<'_, SnapshotV2>> = Vec::new();
+        let mut current = self.snapshot(snapshot_id);
+        while let Some(snapshot) = current {
+            let parent_id = snapshot.parent_snapshot_id;
+            ancestors.push(snapshot);
+            current = parent_id
+                // Guard against cycles in corrupt metadata, including snapshots that are their own parent
+                .filter(|parent_id| {
+                    !ancestors
                         .iter()
-                        .find(|snapshot| snapshot.snapshot_id == *id)
-                        .map(|snapshot| snapshot.manifest_list.as_str())
-                }),
+                        .any(|ancestor| ancestor.snapshot_id == *parent_id)
+                })
+                .and_then(|parent_id| self.snapshot(parent_id));
         }
+        ancestors
+    }
+    /// Check if the snapshot with the id `ancestor_id` is the snapshot with the id `snapshot_id` or one of its
+    /// ancestors.
+    pub fn is_ancestor_of(&self, snapshot_id: i64, ancestor_id: i64) -> bool {
+        self.ancestors_of(snapshot_id)
+            .iter()
+            .any(|snapshot| snapshot.snapshot_id == ancestor_id)
+    }
+    /// Get the current snapshot and its ancestors, from the newest to the oldest. The current snapshot is the snapshot
+    /// the main branch points to.
+    pub fn current_ancestors(&self) -> Vec<Cow<'_, SnapshotV2>> {
+        self.snapshot_id_for_ref(MAIN_BRANCH)
+            .map(|snapshot_id| self.ancestors_of(snapshot_id))
+            .unwrap_or_default()
+    }
+    /// Get the oldest ancestor of the current snapshot that still exists in the table.
+    pub fn oldest_ancestor(&self) -> Option<Cow<'_, SnapshotV2>> {
+        self.current_ancestors().pop()
+    }
+    /// Get the ancestor of the current snapshot that was current at the given time in milliseconds from the unix
+    /// epoch. Snapshots that were rolled back are never returned.
+    pub fn snapshot_as_of_time(&self, timestamp_ms: i64) -> Option<Cow<'_, SnapshotV2>> {
+        self.current_ancestors()
+            .into_iter()
+            .find(|snapshot| snapshot.timestamp_ms <= timestamp_ms)
+    }
+    /// Get the snapshots after the snapshot with the id `from_snapshot_id` (exclusive) up to the snapshot with the id
+    /// `to_snapshot_id` (inclusive), from the oldest to the newest. Returns None if the start snapshot isn't an ancestor
+    /// of the end snapshot.
+    pub fn snapshots_between(
+        &self,
+        from_snapshot_id: i64,
+        to_snapshot_id: i64,
+    ) -> Option<Vec<Cow<'_, SnapshotV2>>> {
+        let mut ancestors = self.ancestors_of(to_snapshot_id);
+        let position = ancestors
+            .iter()
+            .position(|snapshot| snapshot.snapshot_id == from_snapshot_id)?;
+        ancestors.truncate(position);
+        ancestors.reverse();
+        Some(ancestors)
     }
     /// Get the base location of the table
     pub fn location(&self) -> &str {
@@ -356,6 +539,13 @@ impl TableMetadata {
             TableMetadata::V2(metadata) => metadata.last_updated_ms,
         }
     }
+    /// Get the properties of the table
+    pub fn properties(&self) -> Option<&HashMap<String, String>> {
+        match self {
+            TableMetadata::V1(metadata) => metadata.properties.as_ref(),
+            TableMetadata::V2(metadata) => metadata.properties.as_ref(),
+        }
+    }
     /// Get the format version of the table
     pub fn format_version(&self) -> FormatVersion {
         match self {
@@ -368,9 +558,14 @@ impl TableMetadata {
 #[cfg(test)]
 mod tests {
 
+    use std::borrow::Cow;
+
     use anyhow::Result;
 
-    use crate::model::table_metadata::TableMetadata;
+    use crate::model::{
+        snapshot::{Operation, SnapshotV2},
+        table_metadata::TableMetadata,
+    };
 
     use super::TableMetadataV2;
 
@@ -596,6 +791,249 @@ mod tests {
         assert!(serde_json::from_str::<TableMetadataV2>(&data).is_err());
         Ok(())
     }
+    #[test]
+    fn test_time_travel() -> Result<()> {
+        let data = r#"
+            {
+                "format-version" : 2,
+                "table-uuid": "fb072c92-a02b-11e9-ae9c-1bb7bc9eca94",
+                "location": "s3://b/wh/data.db/table",
+                "last-sequence-number" : 2,
+                "last-updated-ms": 1515100955770,
+                "last-column-id": 1,
+                "schemas": [
+                    {
+                        "schema-id" : 1,
+                        "type" : "struct",
+                        "fields" :[
+                            {
+                                "id": 1,
+                                "name": "one",
+                                "required": true,
+                                "type": "long"
+                            }
+                        ]
+                    },
+                    {
+                        "schema-id" : 2,
+                        "type" : "struct",
+                        "fields" :[
+                            {
+                                "id": 1,
+                                "name": "one",
+                                "required": true,
+                                "type": "long"
+                            },
+                            {
+                                "id": 2,
+                                "name": "two",
+                                "required": false,
+                                "type": "string"
+                            }
+                        ]
+                    }
+                ],
+                "current-schema-id" : 2,
+                "partition-specs": [
+                    {
+                        "spec-id": 1,
+                        "fields": []
+                    }
+                ],
+                "default-spec-id": 1,
+                "last-partition-id": 1,
+                "current-snapshot-id": 2,
+                "snapshots": [
+                    {
+                        "snapshot-id": 1,
+                        "sequence-number": 1,
+                        "timestamp-ms": 1000,
+                        "manifest-list": "s3://b/wh/data.db/table/metadata/snap-1.avro",
+                        "summary": { "operation": "append" },
+                        "schema-id": 1
+                    },
+                    {
+                        "snapshot-id": 2,
+                        "parent-snapshot-id": 1,
+                        "sequence-number": 2,
+                        "timestamp-ms": 2000,
+                        "manifest-list": "s3://b/wh/data.db/table/metadata/snap-2.avro",
+                        "summary": { "operation": "append" }
+                    }
+                ],
+                "refs": {
+                    "audit": { "snapshot-id": 1, "type": "tag", "max-ref-age-ms": 1000 }
+                },
+                "sort-orders": [],
+                "default-sort-order-id": 0
+            }
+        "#;
+        let metadata = serde_json::from_str::<TableMetadata>(data)?;
+        let as_of = |timestamp_ms| {
+            metadata
+                .snapshot_as_of_time(timestamp_ms)
+                .map(|snapshot| snapshot.snapshot_id)
+        };
+        assert_eq!(as_of(999), None);
+        assert_eq!(as_of(1000), Some(1));
+        assert_eq!(as_of(1999), Some(1));
+        assert_eq!(as_of(5000), Some(2));
+        assert_eq!(metadata.snapshot_id_for_ref("audit"), Some(1));
+        assert_eq!(metadata.snapshot_id_for_ref("main"), Some(2));
+        assert_eq!(metadata.snapshot_id_for_ref("dev"), None);
+        assert_eq!(
+            metadata
+                .snapshot_schema(1)
+                .map(|schema| schema.fields.len()),
+            Some(1)
+        );
+        assert_eq!(
+            metadata
+                .snapshot_schema(2)
+                .map(|schema| schema.fields.len()),
+            Some(2)
+        );
+        assert!(metadata.snapshot_schema(3).is_none());
+        Ok(())
+    }
+    #[test]
+    fn test_ancestry() -> Result<()> {
+        // Snapshot 3 was rolled back, the current snapshot 2 is a child of snapshot 1
+        let data = r#"
+            {
+                "format-version" : 1,
+                "location": "s3://b/wh/data.db/table",
+                "last-updated-ms": 1515100955770,
+                "last-column-id": 1,
+                "schema": {
+                    "type" : "struct",
+                    "fields" :[
+                        {
+                            "id": 1,
+                            "name": "one",
+                            "required": true,
+                            "type": "long"
+                        }
+                    ]
+                },
+                "partition-spec": [],
+                "current-snapshot-id": 2,
+                "snapshots": [
+                    {
+                        "snapshot-id": 1,
+                        "timestamp-ms": 1000,
+                        "manifest-list": "s3://b/wh/data.db/table/metadata/snap-1.avro",
+                        "summary": { "operation": "append" }
+                    },
+                    {
+                        "snapshot-id": 3,
+                        "parent-snapshot-id": 1,
+                        "timestamp-ms": 1500,
+                        "manifest-list": "s3://b/wh/data.db/table/metadata/snap-3.avro",
+                        "summary": { "operation": "overwrite" }
+                    },
+                    {
+                        "snapshot-id": 2,
+                        "parent-snapshot-id": 1,
+                        "timestamp-ms": 2000,
+                        "manifest-list": "s3://b/wh/data.db/table/metadata/snap-2.avro"
+                    }
+                ],
+                "sort-orders": [],
+                "default-sort-order-id": 0
+            }
+        "#;
+        let metadata = serde_json::from_str::<TableMetadata>(data)?;
+        let ids = |snapshots: Vec<Cow<'_, SnapshotV2>>| -> Vec<i64> {
+            snapshots
+                .iter()
+                .map(|snapshot| snapshot.snapshot_id)
+                .collect()
+        };
+        assert_eq!(
+            metadata.snapshot(3).map(|snapshot| snapshot.timestamp_ms),
+            Some(1500)
+        );
+        assert!(metadata.snapshot(4).is_none());
+        assert_eq!(metadata.snapshot_operation(3), Some(Operation::Overwrite));
+        assert_eq!(metadata.snapshot_operation(2), None);
+        assert_eq!(ids(metadata.ancestors_of(3)), vec![3, 1]);
+        assert_eq!(ids(metadata.current_ancestors()), vec![2, 1]);
+        assert!(metadata.is_ancestor_of(2, 1));
+        assert!(metadata.is_ancestor_of(2, 2));
+        assert!(!metadata.is_ancestor_of(2, 3));
+        assert_eq!(
+            metadata
+                .oldest_ancestor()
+                .map(|snapshot| snapshot.snapshot_id),
+            Some(1)
+        );
+        // Rolled back snapshots were current at some time, but are no ancestors of the current snapshot
+        assert_eq!(
+            metadata
+                .snapshot_as_of_time(1700)
+                .map(|snapshot| snapshot.snapshot_id),
+            Some(1)
+        );
+        assert!(metadata.snapshot_as_of_time(999).is_none());
+        assert_eq!(metadata.snapshots_between(1, 2).map(ids), Some(vec![2]));
+        assert_eq!(metadata.snapshots_between(2, 2).map(ids), Some(vec![]));
+        assert!(metadata.snapshots_between(3, 2).is_none());
+        Ok(())
+    }
+    #[test]
+    fn test_ancestry_cycle() -> Result<()> {
+        // Corrupt metadata with a snapshot that is its own parent
+        let data = r#"
+            {
+                "format-version" : 1,
+                "location": "s3://b/wh/data.db/table",
+                "last-updated-ms": 1515100955770,
+                "last-column-id": 1,
+                "schema": {
+                    "type" : "struct",
+                    "fields" :[
+                        {
+                            "id": 1,
+                            "name": "one",
+                            "required": true,
+                            "type": "long"
+                        }
+                    ]
+                },
+                "partition-spec": [],
+                "current-snapshot-id": 2,
+                "snapshots": [
+                    {
+                        "snapshot-id": 1,
+                        "parent-snapshot-id": 1,
+                        "timestamp-ms": 1000,
+                        "manifest-list": "s3://b/wh/data.db/table/metadata/snap-1.avro"
+                    },
+                    {
+                        "snapshot-id": 2,
+                        "parent-snapshot-id": 1,
+                        "timestamp-ms": 2000,
+                        "manifest-list": "s3://b/wh/data.db/table/metadata/snap-2.avro"
+                    }
+                ],
+                "sort-orders": [],
+                "default-sort-order-id": 0
+            }
+        "#;
+        let metadata = serde_json::from_str::<TableMetadata>(data)?;
+        let ids = |snapshots: Vec<Cow<'_, SnapshotV2>>| -> Vec<i64> {
+            snapshots
+                .iter()
+                .map(|snapshot| snapshot.snapshot_id)
+                .collect()
+        };
+        assert_eq!(ids(metadata.ancestors_of(1)), vec![1]);
+        assert_eq!(ids(metadata.current_ancestors()), vec![2, 1]);
+        assert_eq!(metadata.snapshots_between(1, 2).map(ids), Some(vec![2]));
+        Ok(())
+    }
+
     #[test]
     fn test_deserialize_table_data_v2_invalid_format_version() -> Result<()> {
         let data = r#"
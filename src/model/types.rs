@@ -3,10 +3,12 @@
  */
 
 use std::{
+    cmp::Ordering,
     collections::{BTreeMap, HashMap},
     ops::Index,
 };
 
+use chrono::{Datelike, NaiveDate, NaiveDateTime, NaiveTime, Timelike};
 use serde::{Deserialize, Serialize};
 
 use super::decimal::Decimal;
@@ -62,6 +64,71 @@ pub enum Value {
     Map(HashMap<String, Field>),
 }
 
+/// Values of the same primitive type are ordered like the spec orders them for bounds. Values of different types,
+/// nested values and decimals with different scales are not comparable.
+impl PartialOrd for Value {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        match (self, other) {
+            (Value::Boolean(left), Value::Boolean(right)) => left.partial_cmp(right),
+            (Value::Int(left), Value::Int(right)) => left.partial_cmp(right),
+            (Value::LongInt(left), Value::LongInt(right)) => left.partial_cmp(right),
+            (Value::Double(left), Value::Double(right)) => left.partial_cmp(right),
+            (Value::LongFloat(left), Value::LongFloat(right)) => left.partial_cmp(right),
+            (Value::Date(left), Value::Date(right)) => left.partial_cmp(right),
+            (Value::Time(left), Value::Time(right)) => left.partial_cmp(right),
+            (Value::Timestamp(left), Value::Timestamp(right))
+            | (Value::TimestampTZ(left), Value::TimestampTZ(right)) => left.partial_cmp(right),
+            (Value::String(left), Value::String(right)) => left.partial_cmp(right),
+            (Value::UUID(left), Value::UUID(right)) => left.partial_cmp(right),
+            (Value::Fixed(_, left), Value::Fixed(_, right))
+            | (Value::Binary(left), Value::Binary(right)) => left.partial_cmp(right),
+            (Value::Decimal(left), Value::Decimal(right)) if left.scale() == right.scale() => {
+                left.value().partial_cmp(right.value())
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Days from 0001-01-01 to 1970-01-01
+const UNIX_EPOCH_DAYS_FROM_CE: i32 = 719_163;
+
+/// Days from 1970-01-01
+pub(crate) fn days_from_epoch(date: &NaiveDate) -> i32 {
+    date.num_days_from_ce() - UNIX_EPOCH_DAYS_FROM_CE
+}
+
+/// Date from the days since 1970-01-01
+pub(crate) fn date_from_epoch(days: i32) -> Option<NaiveDate> {
+    NaiveDate::from_num_days_from_ce_opt(days.checked_add(UNIX_EPOCH_DAYS_FROM_CE)?)
+}
+
+/// Microseconds from 1970-01-01 00:00:00
+pub(crate) fn micros_from_epoch(timestamp: &NaiveDateTime) -> i64 {
+    timestamp.timestamp() * 1_000_000 + timestamp.timestamp_subsec_micros() as i64
+}
+
+/// Timestamp from the microseconds since 1970-01-01 00:00:00
+pub(crate) fn timestamp_from_epoch(micros: i64) -> Option<NaiveDateTime> {
+    NaiveDateTime::from_timestamp_opt(
+        micros.div_euclid(1_000_000),
+        micros.rem_euclid(1_000_000) as u32 * 1_000,
+    )
+}
+
+/// Microseconds from midnight
+pub(crate) fn micros_from_midnight(time: &NaiveTime) -> i64 {
+    time.num_seconds_from_midnight() as i64 * 1_000_000 + time.nanosecond() as i64 / 1_000
+}
+
+/// Time from the microseconds since midnight
+pub(crate) fn time_from_midnight(micros: i64) -> Option<NaiveTime> {
+    NaiveTime::from_num_seconds_from_midnight_opt(
+        u32::try_from(micros.div_euclid(1_000_000)).ok()?,
+        micros.rem_euclid(1_000_000) as u32 * 1_000,
+    )
+}
+
 /// Optional or required value
 #[derive(Debug, Clone, PartialEq)]
 pub enum Field {
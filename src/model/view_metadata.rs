@@ -11,7 +11,7 @@ use crate::model::schema::Schema;
 use super::schema::SchemaStruct;
 
 /// Metadata of an iceberg view
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
 #[serde(untagged)]
 pub enum ViewMetadata {
     /// Version 1 of the table metadata
@@ -67,7 +67,7 @@ impl ViewMetadata {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
 #[serde(rename_all = "kebab-case")]
 /// Fields for the version 1 of the view metadata.
 pub struct ViewMetadataV1 {
@@ -89,7 +89,7 @@ pub struct ViewMetadataV1 {
     pub current_schema_id: Option<i32>,
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
 #[serde(rename_all = "kebab-case")]
 /// Fields for the version 2 of the view metadata.
 pub struct Version {
@@ -103,7 +103,7 @@ pub struct Version {
     pub representations: Vec<Representation>,
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
 #[serde(rename_all = "kebab-case")]
 /// Fields for the version 2 of the view metadata.
 pub struct VersionLogStruct {
@@ -155,7 +155,7 @@ impl<'de> Deserialize<'de> for Operation {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
 #[serde(rename_all = "kebab-case")]
 /// Fields for the version 2 of the view metadata.
 pub struct Summary {
@@ -165,7 +165,7 @@ pub struct Summary {
     pub engine_version: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
 #[serde(rename_all = "kebab-case", tag = "type")]
 /// Fields for the version 2 of the view metadata.
 pub enum Representation {
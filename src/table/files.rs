@@ -1,14 +1,20 @@
 /*!
  * Helper for iterating over files in a table.
 */
-use std::{io::Cursor, iter::repeat, sync::Arc};
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    io::Cursor,
+    iter::repeat,
+    sync::Arc,
+};
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use apache_avro::types::Value as AvroValue;
 use futures::{stream, StreamExt, TryFutureExt, TryStreamExt};
-use object_store::path::Path;
+use object_store::{path::Path, ObjectStore};
 
 use crate::{
+    expr::{manifest_evaluator::ManifestEvaluator, UnboundExpression},
     model::{
         manifest::{ManifestEntry, ManifestEntryV1, ManifestEntryV2},
         manifest_list::ManifestFile,
@@ -43,24 +49,57 @@ impl Table {
         };
         // Collect a vector of data files by creating a stream over the manifst files, fetch their content and return a flatten stream over their entries.
         stream::iter(iter)
-            .map(|file| async move {
-                let object_store = Arc::clone(&self.object_store());
-                let path: Path = util::strip_prefix(file.manifest_path()).into();
-                let bytes = Cursor::new(Vec::from(
-                    object_store
-                        .get(&path)
-                        .and_then(|file| file.bytes())
-                        .await?,
-                ));
-                let reader = apache_avro::Reader::new(bytes)?;
-                Ok(stream::iter(reader.map(|record| {
-                    avro_value_to_manifest_entry(record, &self.metadata().format_version())
-                })))
-            })
-            .flat_map(|reader| reader.try_flatten_stream())
+            .then(|file| read_manifest(self.object_store(), file, self.metadata().format_version()))
+            .map_ok(|entries| stream::iter(entries.into_iter().map(Ok)))
+            .try_flatten()
             .try_collect()
             .await
     }
+    /// Get the filter vector for [Table::files] that only includes the manifest files that might contain rows matching
+    /// the row filter. The filter is bound to the current schema and evaluated against the partition summaries of the
+    /// manifest list, so that the manifest files don't have to be read.
+    pub fn manifest_filter(&self, filter: UnboundExpression) -> Result<Vec<bool>> {
+        let filter = filter.bind(self.schema())?;
+        let mut evaluators: HashMap<i32, ManifestEvaluator> = HashMap::new();
+        self.manifests()
+            .iter()
+            .map(|manifest| {
+                let spec_id = manifest.partition_spec_id();
+                let evaluator = match evaluators.entry(spec_id) {
+                    Entry::Occupied(entry) => entry.into_mut(),
+                    Entry::Vacant(entry) => {
+                        let spec = self.metadata().get_spec(spec_id).ok_or_else(|| {
+                            anyhow!(
+                                "Partition spec {} doesn't exist in the table metadata.",
+                                spec_id
+                            )
+                        })?;
+                        entry.insert(ManifestEvaluator::new(&filter, spec)?)
+                    }
+                };
+                evaluator.eval(manifest)
+            })
+            .collect()
+    }
+}
+
+/// Read the entries of a manifest file.
+pub(crate) async fn read_manifest(
+    object_store: Arc<dyn ObjectStore>,
+    manifest: &ManifestFile,
+    format_version: FormatVersion,
+) -> Result<Vec<ManifestEntry>> {
+    let path: Path = util::strip_prefix(manifest.manifest_path()).into();
+    let bytes = Cursor::new(Vec::from(
+        object_store
+            .get(&path)
+            .and_then(|file| file.bytes())
+            .await?,
+    ));
+    let reader = apache_avro::Reader::new(bytes)?;
+    reader
+        .map(|record| avro_value_to_manifest_entry(record, &format_version))
+        .collect()
 }
 
 // Filter manifest files according to predicate. Returns Some(&ManifestFile) of the predicate is true and None if it is false.
@@ -96,7 +135,11 @@ mod tests {
     use std::sync::Arc;
 
     use crate::{
-        model::schema::{AllType, PrimitiveType, SchemaStruct, SchemaV2, StructField},
+        expr::Reference,
+        model::{
+            schema::{AllType, PrimitiveType, SchemaStruct, SchemaV2, StructField},
+            types::Value,
+        },
         table::table_builder::TableBuilder,
     };
 
@@ -151,6 +194,16 @@ mod tests {
             .commit()
             .await
             .unwrap();
+        // The partition summaries of the manifests don't exclude any manifest
+        assert_eq!(
+            table
+                .manifest_filter(Reference::new("one").equal(Value::String("a".to_string())))
+                .unwrap(),
+            vec![true, true]
+        );
+        assert!(table
+            .manifest_filter(Reference::new("three").is_null())
+            .is_err());
         let mut files = table
             .files(None)
             .await
@@ -10,24 +10,26 @@ use futures::StreamExt;
 use object_store::{path::Path, ObjectStore};
 
 use crate::{
-    catalog::{identifier::Identifier, Catalog},
+    catalog::{identifier::Identifier, relation::Relation, Catalog},
     model::{
         manifest_list::{ManifestFile, ManifestFileV1, ManifestFileV2},
         schema::SchemaStruct,
-        snapshot::{SnapshotV1, SnapshotV2, Summary},
-        table_metadata::{FormatVersion, TableMetadata},
+        snapshot::{Operation, SnapshotV1, SnapshotV2, Summary},
+        table_metadata::{FormatVersion, SnapshotLog, TableMetadata},
     },
     table::transaction::TableTransaction,
     util,
 };
 
 pub mod files;
+pub mod scan;
 pub mod table_builder;
 pub mod transaction;
 
 /// Tables can be either one of following types:
 /// - FileSystem(https://iceberg.apache.org/spec/#file-system-tables)
 /// - Metastore(https://iceberg.apache.org/spec/#metastore-tables)
+#[derive(Clone)]
 pub enum TableType {
     /// Filesystem table
     FileSystem(Arc<dyn ObjectStore>),
@@ -36,6 +38,7 @@ pub enum TableType {
 }
 
 /// Iceberg table
+#[derive(Clone)]
 pub struct Table {
     table_type: TableType,
     metadata: TableMetadata,
@@ -133,6 +136,12 @@ impl Table {
             TableType::Metastore(_, catalog) => Some(catalog),
         }
     }
+    /// Associate a metastore table with another catalog, for example a catalog that wraps the one it was loaded from.
+    pub(crate) fn set_catalog(&mut self, catalog: Arc<dyn Catalog>) {
+        if let TableType::Metastore(_, table_catalog) = &mut self.table_type {
+            *table_catalog = catalog;
+        }
+    }
     /// Get the object_store associated to the table
     pub fn object_store(&self) -> Arc<dyn ObjectStore> {
         match &self.table_type {
@@ -160,6 +169,27 @@ impl Table {
     pub fn new_transaction(&mut self) -> TableTransaction {
         TableTransaction::new(self)
     }
+    /// Reload the table from its catalog or object store to get the current state.
+    pub async fn refresh(&mut self) -> Result<()> {
+        let table = match &self.table_type {
+            TableType::Metastore(identifier, catalog) => {
+                match catalog.clone().load_table(identifier).await? {
+                    Relation::Table(table) => table,
+                    Relation::View(_) => {
+                        return Err(anyhow!(
+                            "Refreshing the table {} didn't return a table.",
+                            identifier
+                        ))
+                    }
+                }
+            }
+            TableType::FileSystem(object_store) => {
+                Table::load_file_system_table(self.metadata.location(), object_store).await?
+            }
+        };
+        *self = table;
+        Ok(())
+    }
 }
 
 /// Private interface of the table.
@@ -174,6 +204,41 @@ impl Table {
         }
     }
 
+    /// Record the operation in the summary of the current snapshot. A snapshot that already records a different
+    /// operation both adds and removes files and is recorded as an overwrite.
+    pub(crate) fn set_snapshot_operation(&mut self, operation: Operation) {
+        match &mut self.metadata {
+            TableMetadata::V1(metadata) => {
+                let current_snapshot_id = metadata.current_snapshot_id;
+                if let Some(snapshot) = metadata.snapshots.as_mut().and_then(|snapshots| {
+                    snapshots
+                        .iter_mut()
+                        .find(|snapshot| Some(snapshot.snapshot_id) == current_snapshot_id)
+                }) {
+                    let summary = snapshot.summary.get_or_insert_with(|| Summary {
+                        operation: None,
+                        other: HashMap::new(),
+                    });
+                    summary.operation =
+                        Some(combine_operations(summary.operation.take(), operation));
+                }
+            }
+            TableMetadata::V2(metadata) => {
+                let current_snapshot_id = metadata.current_snapshot_id;
+                if let Some(snapshot) = metadata.snapshots.as_mut().and_then(|snapshots| {
+                    snapshots
+                        .iter_mut()
+                        .find(|snapshot| Some(snapshot.snapshot_id) == current_snapshot_id)
+                }) {
+                    snapshot.summary.operation = Some(combine_operations(
+                        snapshot.summary.operation.take(),
+                        operation,
+                    ));
+                }
+            }
+        }
+    }
+
     /// Create a new table snapshot based on the manifest_list file of the previous snapshot.
     pub(crate) async fn new_snapshot(&mut self) -> Result<()> {
         let mut bytes: [u8; 8] = [0u8; 8];
@@ -181,6 +246,10 @@ impl Table {
         let snapshot_id = i64::from_le_bytes(bytes);
         let object_store = self.object_store();
         let old_manifest_list_location = self.metadata.manifest_list().map(|st| st.to_string());
+        let timestamp_ms = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap()
+            .as_millis() as i64;
         match &mut self.metadata {
             TableMetadata::V1(metadata) => {
                 let new_manifest_list_location = metadata.location.to_string()
@@ -210,10 +279,7 @@ impl Table {
                 let snapshot = SnapshotV1 {
                     snapshot_id,
                     parent_snapshot_id: metadata.current_snapshot_id,
-                    timestamp_ms: SystemTime::now()
-                        .duration_since(SystemTime::UNIX_EPOCH)
-                        .unwrap()
-                        .as_millis() as i64,
+                    timestamp_ms,
                     manifest_list: Some(new_manifest_list_location),
                     manifests: None,
                     summary: None,
@@ -226,6 +292,13 @@ impl Table {
                     metadata.snapshots = Some(vec![snapshot]);
                     metadata.current_snapshot_id = Some(snapshot_id)
                 };
+                metadata
+                    .snapshot_log
+                    .get_or_insert_with(Vec::new)
+                    .push(SnapshotLog {
+                        snapshot_id,
+                        timestamp_ms,
+                    });
                 Ok(())
             }
             TableMetadata::V2(metadata) => {
@@ -257,10 +330,7 @@ impl Table {
                     snapshot_id,
                     parent_snapshot_id: metadata.current_snapshot_id,
                     sequence_number: metadata.last_sequence_number + 1,
-                    timestamp_ms: SystemTime::now()
-                        .duration_since(SystemTime::UNIX_EPOCH)
-                        .unwrap()
-                        .as_millis() as i64,
+                    timestamp_ms,
                     manifest_list: new_manifest_list_location,
                     summary: Summary {
                         operation: None,
@@ -275,12 +345,27 @@ impl Table {
                     metadata.snapshots = Some(vec![snapshot]);
                     metadata.current_snapshot_id = Some(snapshot_id)
                 };
+                metadata
+                    .snapshot_log
+                    .get_or_insert_with(Vec::new)
+                    .push(SnapshotLog {
+                        snapshot_id,
+                        timestamp_ms,
+                    });
                 Ok(())
             }
         }
     }
 }
 
+/// Combine an operation with the operation that was already recorded for a snapshot.
+fn combine_operations(recorded: Option<Operation>, operation: Operation) -> Operation {
+    match recorded {
+        Some(recorded) if recorded != operation => Operation::Overwrite,
+        _ => operation,
+    }
+}
+
 // Return all manifest files associated to the latest table snapshot. Reads the related manifest_list file and returns its entries.
 // If the manifest list file is empty returns an empty vector.
 pub(crate) async fn get_manifests(
@@ -289,29 +374,38 @@ pub(crate) async fn get_manifests(
 ) -> Result<Vec<ManifestFile>> {
     match metadata.manifest_list() {
         Some(manifest_list) => {
-            let bytes: Cursor<Vec<u8>> = Cursor::new(
-                object_store
-                    .get(&util::strip_prefix(&manifest_list).into())
-                    .await
-                    .map_err(anyhow::Error::msg)?
-                    .bytes()
-                    .await?
-                    .into(),
-            );
-            // Read the file content only if the bytes are not empty otherwise return an empty vector
-            if !bytes.get_ref().is_empty() {
-                let reader = apache_avro::Reader::new(bytes)?;
-                reader
-                    .map(|record| avro_value_to_manifest_file(record, metadata.format_version()))
-                    .collect()
-            } else {
-                Ok(Vec::new())
-            }
+            read_manifest_list(manifest_list, metadata.format_version(), object_store).await
         }
         None => Ok(Vec::new()),
     }
 }
 
+// Read the entries of a manifest_list file. If the manifest list file is empty returns an empty vector.
+pub(crate) async fn read_manifest_list(
+    manifest_list: &str,
+    format_version: FormatVersion,
+    object_store: Arc<dyn ObjectStore>,
+) -> Result<Vec<ManifestFile>> {
+    let bytes: Cursor<Vec<u8>> = Cursor::new(
+        object_store
+            .get(&util::strip_prefix(manifest_list).into())
+            .await
+            .map_err(anyhow::Error::msg)?
+            .bytes()
+            .await?
+            .into(),
+    );
+    // Read the file content only if the bytes are not empty otherwise return an empty vector
+    if !bytes.get_ref().is_empty() {
+        let reader = apache_avro::Reader::new(bytes)?;
+        reader
+            .map(|record| avro_value_to_manifest_file(record, format_version.clone()))
+            .collect()
+    } else {
+        Ok(Vec::new())
+    }
+}
+
 /// Convert an avro value to a [ManifestFile] according to the provided format version
 fn avro_value_to_manifest_file(
     entry: Result<AvroValue, apache_avro::Error>,
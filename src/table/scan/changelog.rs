@@ -0,0 +1,535 @@
+/*!
+Defines the [ChangelogScan] builder that plans the row level changes of a table between two snapshots.
+*/
+
+use std::fmt;
+
+use anyhow::Result;
+use arrow::{datatypes::Schema as ArrowSchema, record_batch::RecordBatch};
+
+use crate::{
+    arrow::changelog::{add_change_columns, changelog_schema},
+    expr::{BoundExpression, UnboundExpression},
+    model::{
+        manifest::{ManifestEntry, Status},
+        schema::SchemaStruct,
+        snapshot::Operation,
+    },
+    table::Table,
+};
+
+use super::{snapshot_range, FileScanTask, ScannedFile, TableScan};
+
+/// Type of a change in the changelog
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChangeType {
+    /// Rows were added to the table
+    Insert,
+    /// Rows were removed from the table
+    Delete,
+}
+
+impl fmt::Display for ChangeType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChangeType::Insert => write!(f, "INSERT"),
+            ChangeType::Delete => write!(f, "DELETE"),
+        }
+    }
+}
+
+/// Rows of a data file that changed in one snapshot of the changelog. The change ordinal is the position of the
+/// snapshot in the changelog, starting with 0 for the oldest snapshot.
+#[derive(Debug, Clone)]
+pub enum ChangelogScanTask {
+    /// The data file was added in the snapshot. The delete files of the task remove the rows that were deleted in the
+    /// same snapshot.
+    AddedRows {
+        /// Position of the snapshot in the changelog
+        change_ordinal: i32,
+        /// Id of the snapshot that added the data file
+        commit_snapshot_id: i64,
+        /// Task that reads the inserted rows
+        task: FileScanTask,
+    },
+    /// The data file was removed in the snapshot. The delete files of the task remove the rows that were deleted
+    /// before.
+    DeletedDataFile {
+        /// Position of the snapshot in the changelog
+        change_ordinal: i32,
+        /// Id of the snapshot that removed the data file
+        commit_snapshot_id: i64,
+        /// Task that reads the deleted rows
+        task: FileScanTask,
+    },
+    /// Rows of the data file were deleted by delete files added in the snapshot. The delete files of the task remove
+    /// the rows that were deleted before.
+    DeletedRows {
+        /// Position of the snapshot in the changelog
+        change_ordinal: i32,
+        /// Id of the snapshot that added the delete files
+        commit_snapshot_id: i64,
+        /// Task that reads the rows of the data file that weren't deleted before
+        task: FileScanTask,
+        /// Delete files added in the snapshot, the rows they delete are the deleted rows
+        added_delete_files: Vec<ManifestEntry>,
+    },
+}
+
+impl ChangelogScanTask {
+    /// Get the type of the change
+    pub fn change_type(&self) -> ChangeType {
+        match self {
+            ChangelogScanTask::AddedRows { .. } => ChangeType::Insert,
+            ChangelogScanTask::DeletedDataFile { .. } | ChangelogScanTask::DeletedRows { .. } => {
+                ChangeType::Delete
+            }
+        }
+    }
+    /// Get the position of the snapshot of the change in the changelog
+    pub fn change_ordinal(&self) -> i32 {
+        match self {
+            ChangelogScanTask::AddedRows { change_ordinal, .. }
+            | ChangelogScanTask::DeletedDataFile { change_ordinal, .. }
+            | ChangelogScanTask::DeletedRows { change_ordinal, .. } => *change_ordinal,
+        }
+    }
+    /// Get the id of the snapshot that committed the change
+    pub fn commit_snapshot_id(&self) -> i64 {
+        match self {
+            ChangelogScanTask::AddedRows {
+                commit_snapshot_id, ..
+            }
+            | ChangelogScanTask::DeletedDataFile {
+                commit_snapshot_id, ..
+            }
+            | ChangelogScanTask::DeletedRows {
+                commit_snapshot_id, ..
+            } => *commit_snapshot_id,
+        }
+    }
+    /// Get the task that reads the rows of the data file
+    pub fn task(&self) -> &FileScanTask {
+        match self {
+            ChangelogScanTask::AddedRows { task, .. }
+            | ChangelogScanTask::DeletedDataFile { task, .. }
+            | ChangelogScanTask::DeletedRows { task, .. } => task,
+        }
+    }
+    /// Add the `_change_type`, `_change_ordinal` and `_commit_snapshot_id` metadata columns to the changed rows that
+    /// were read for the task.
+    pub fn to_record_batch(&self, rows: &RecordBatch) -> Result<RecordBatch> {
+        add_change_columns(
+            rows,
+            &self.change_type(),
+            self.change_ordinal(),
+            self.commit_snapshot_id(),
+        )
+    }
+}
+
+/// Builder for a scan of the changes of a table between two snapshots. Changes are read from the snapshots between the
+/// start snapshot (exclusive) and the end snapshot (inclusive). Replace snapshots don't change the rows of the table
+/// and are skipped.
+pub struct ChangelogScan<'table> {
+    scan: TableScan<'table>,
+    from_snapshot: Option<i64>,
+    to_snapshot: Option<i64>,
+}
+
+impl<'table> ChangelogScan<'table> {
+    /// Create a scan of the changes of the table up to its current snapshot
+    pub fn new(table: &'table Table) -> Self {
+        ChangelogScan {
+            scan: TableScan::new(table),
+            from_snapshot: None,
+            to_snapshot: None,
+        }
+    }
+    /// Only read the given columns. Nested columns are selected by their dotted name.
+    pub fn select(mut self, columns: &[&str]) -> Self {
+        self.scan = self.scan.select(columns);
+        self
+    }
+    /// Only read the changed rows matching the filter. Multiple filters are combined, so that rows have to match all of
+    /// them.
+    pub fn filter(mut self, filter: UnboundExpression) -> Self {
+        self.scan = self.scan.filter(filter);
+        self
+    }
+    /// Whether column names of the selection and the filter are matched case sensitively. Defaults to true.
+    pub fn case_sensitive(mut self, case_sensitive: bool) -> Self {
+        self.scan = self.scan.case_sensitive(case_sensitive);
+        self
+    }
+    /// Only read changes after the snapshot with the given id. The snapshot has to be an ancestor of the end snapshot.
+    /// Defaults to the first snapshot of the table, including its changes.
+    pub fn from_snapshot_exclusive(mut self, snapshot_id: i64) -> Self {
+        self.from_snapshot = Some(snapshot_id);
+        self
+    }
+    /// Read the changes up to and including the snapshot with the given id. Defaults to the current snapshot.
+    pub fn to_snapshot(mut self, snapshot_id: i64) -> Self {
+        self.to_snapshot = Some(snapshot_id);
+        self
+    }
+    /// Get the schema of the changed rows, which only contains the selected columns and the structs they are nested in.
+    pub fn schema(&self) -> Result<SchemaStruct> {
+        self.scan.schema()
+    }
+    /// Get the arrow schema of the record batches of the changelog, which contains the selected columns followed by the
+    /// `_change_type`, `_change_ordinal` and `_commit_snapshot_id` metadata columns.
+    pub fn arrow_schema(&self) -> Result<ArrowSchema> {
+        changelog_schema(&self.schema()?)
+    }
+    /// Get the ids of the snapshots of the changelog, from the oldest to the newest. The position of a snapshot is its
+    /// change ordinal.
+    pub fn snapshots(&self) -> Result<Vec<i64>> {
+        let metadata = self.scan.table.metadata();
+        Ok(
+            snapshot_range(metadata, self.from_snapshot, self.to_snapshot)?
+                .into_iter()
+                .filter(|snapshot_id| {
+                    metadata.snapshot_operation(*snapshot_id) != Some(Operation::Replace)
+                })
+                .collect(),
+        )
+    }
+    /// Plan the changes of the snapshots of the changelog. The tasks of a snapshot are ordered by inserts, deleted data
+    /// files and deleted rows.
+    pub async fn plan_files(&self) -> Result<Vec<ChangelogScanTask>> {
+        let filter = self.scan.bound_filter()?;
+        let mut tasks = Vec::new();
+        for (change_ordinal, snapshot_id) in self.snapshots()?.into_iter().enumerate() {
+            let manifests = self.scan.snapshot_manifests(snapshot_id).await?;
+            // Entries that were removed in older snapshots are no longer part of the table
+            let (data_files, delete_files) = self
+                .scan
+                .scan_manifests(&manifests, &filter, |manifest, entry| {
+                    *entry.status() != Status::Deleted
+                        || entry
+                            .snapshot_id()
+                            .unwrap_or_else(|| manifest.added_snapshot_id())
+                            == snapshot_id
+                })
+                .await?;
+            tasks.extend(snapshot_changes(
+                change_ordinal as i32,
+                snapshot_id,
+                data_files,
+                delete_files,
+            ));
+        }
+        Ok(tasks)
+    }
+}
+
+/// Get the changes of a snapshot from the files of the snapshot and the files it removed.
+fn snapshot_changes(
+    change_ordinal: i32,
+    snapshot_id: i64,
+    data_files: Vec<(ScannedFile, BoundExpression)>,
+    delete_files: Vec<ScannedFile>,
+) -> Vec<ChangelogScanTask> {
+    // Delete files that were removed in the snapshot still applied to the rows before
+    let (added_deletes, existing_deletes): (Vec<_>, Vec<_>) = delete_files
+        .into_iter()
+        .partition(|file| file.snapshot_id == snapshot_id && *file.entry.status() == Status::Added);
+    let applying = |delete_files: &[ScannedFile], data_file: &ScannedFile| -> Vec<ManifestEntry> {
+        delete_files
+            .iter()
+            .filter(|delete_file| delete_file.applies_to(data_file))
+            .map(|delete_file| delete_file.entry.clone())
+            .collect()
+    };
+    let mut inserts = Vec::new();
+    let mut deleted_files = Vec::new();
+    let mut deleted_rows = Vec::new();
+    for (file, residual) in data_files {
+        let changed = file.snapshot_id == snapshot_id;
+        match file.entry.status() {
+            Status::Added if changed => {
+                let delete_files = applying(&added_deletes, &file);
+                inserts.push(ChangelogScanTask::AddedRows {
+                    change_ordinal,
+                    commit_snapshot_id: snapshot_id,
+                    task: file.into_task(delete_files, residual),
+                })
+            }
+            Status::Deleted => {
+                let delete_files = applying(&existing_deletes, &file);
+                deleted_files.push(ChangelogScanTask::DeletedDataFile {
+                    change_ordinal,
+                    commit_snapshot_id: snapshot_id,
+                    task: file.into_task(delete_files, residual),
+                })
+            }
+            _ => {
+                let added_delete_files = applying(&added_deletes, &file);
+                if !added_delete_files.is_empty() {
+                    let delete_files = applying(&existing_deletes, &file);
+                    deleted_rows.push(ChangelogScanTask::DeletedRows {
+                        change_ordinal,
+                        commit_snapshot_id: snapshot_id,
+                        task: file.into_task(delete_files, residual),
+                        added_delete_files,
+                    })
+                }
+            }
+        }
+    }
+    inserts
+        .into_iter()
+        .chain(deleted_files)
+        .chain(deleted_rows)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::sync::Arc;
+
+    use arrow::{
+        array::{Int32Array, Int64Array, StringArray},
+        datatypes::{DataType, Field, Schema as ArrowSchema},
+        record_batch::RecordBatch,
+    };
+    use object_store::{memory::InMemory, ObjectStore};
+
+    use crate::{
+        expr::Expression,
+        model::{
+            manifest::PartitionValues,
+            manifest::{Content, DataFileV2, FileFormat, ManifestEntry, ManifestEntryV2, Status},
+            schema::{AllType, PrimitiveType, SchemaStruct, SchemaV2, StructField},
+        },
+        table::{scan::ScannedFile, table_builder::TableBuilder},
+    };
+
+    use super::{snapshot_changes, ChangeType, ChangelogScanTask};
+
+    fn file(path: &str, content: Content, status: Status, snapshot_id: i64) -> ScannedFile {
+        ScannedFile {
+            entry: ManifestEntry::V2(ManifestEntryV2 {
+                status,
+                snapshot_id: Some(snapshot_id),
+                sequence_number: Some(snapshot_id),
+                data_file: DataFileV2 {
+                    content,
+                    file_path: path.to_string(),
+                    file_format: FileFormat::Parquet,
+                    partition: PartitionValues::from_iter(vec![]),
+                    record_count: 4,
+                    file_size_in_bytes: 1200,
+                    column_sizes: None,
+                    value_counts: None,
+                    null_value_counts: None,
+                    nan_value_counts: None,
+                    distinct_counts: None,
+                    lower_bounds: None,
+                    upper_bounds: None,
+                    key_metadata: None,
+                    split_offsets: None,
+                    equality_ids: None,
+                    sort_order_id: None,
+                },
+            }),
+            snapshot_id,
+            spec_id: 0,
+            sequence_number: snapshot_id,
+            unpartitioned: true,
+        }
+    }
+
+    fn paths(files: &[ManifestEntry]) -> Vec<&str> {
+        files.iter().map(|file| file.file_path()).collect()
+    }
+
+    fn describe(task: &ChangelogScanTask) -> (ChangeType, &str, Vec<&str>, Vec<&str>) {
+        let added = match task {
+            ChangelogScanTask::DeletedRows {
+                added_delete_files, ..
+            } => paths(added_delete_files),
+            _ => vec![],
+        };
+        (
+            task.change_type(),
+            task.task().data_file().file_path(),
+            paths(task.task().delete_files()),
+            added,
+        )
+    }
+
+    #[test]
+    fn test_snapshot_changes() {
+        // Removed files keep the sequence number of the snapshot that added them
+        let mut removed = file("removed", Content::Data, Status::Deleted, 3);
+        removed.sequence_number = 1;
+        let data_files = vec![
+            (
+                file("old", Content::Data, Status::Existing, 1),
+                Expression::True,
+            ),
+            (
+                file("new", Content::Data, Status::Added, 3),
+                Expression::True,
+            ),
+            (removed, Expression::True),
+            // Files added by older snapshots are carried over in their manifests
+            (
+                file("appended", Content::Data, Status::Added, 2),
+                Expression::True,
+            ),
+        ];
+        let delete_files = vec![
+            file("old-deletes", Content::PositionDeletes, Status::Existing, 2),
+            file("new-deletes", Content::PositionDeletes, Status::Added, 3),
+        ];
+        let tasks = snapshot_changes(1, 3, data_files, delete_files);
+        assert!(tasks
+            .iter()
+            .all(|task| task.change_ordinal() == 1 && task.commit_snapshot_id() == 3));
+        assert_eq!(
+            tasks.iter().map(describe).collect::<Vec<_>>(),
+            vec![
+                (ChangeType::Insert, "new", vec!["new-deletes"], vec![]),
+                (ChangeType::Delete, "removed", vec!["old-deletes"], vec![]),
+                (
+                    ChangeType::Delete,
+                    "old",
+                    vec!["old-deletes"],
+                    vec!["new-deletes"]
+                ),
+                (
+                    ChangeType::Delete,
+                    "appended",
+                    vec!["old-deletes"],
+                    vec!["new-deletes"]
+                ),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_changelog_scan() {
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let schema = SchemaV2 {
+            schema_id: 1,
+            identifier_field_ids: Some(vec![1]),
+            name_mapping: None,
+            struct_fields: SchemaStruct {
+                fields: vec![StructField {
+                    id: 1,
+                    name: "id".to_string(),
+                    required: true,
+                    field_type: AllType::Primitive(PrimitiveType::Long),
+                    doc: None,
+                }],
+            },
+        };
+        let mut table = TableBuilder::new_filesystem_table("test/changelog", schema, object_store)
+            .unwrap()
+            .commit()
+            .await
+            .unwrap();
+        for file in ["file1", "file2"] {
+            table
+                .new_transaction()
+                .fast_append(vec![format!("test/changelog/data/{}.parquet", file)])
+                .commit()
+                .await
+                .unwrap();
+        }
+        let snapshot_ids = table.changelog_scan().snapshots().unwrap();
+        assert_eq!(snapshot_ids.len(), 2);
+
+        let tasks = table.changelog_scan().plan_files().await.unwrap();
+        assert_eq!(
+            tasks
+                .iter()
+                .map(|task| (
+                    task.change_type(),
+                    task.change_ordinal(),
+                    task.commit_snapshot_id(),
+                    task.task().data_file().file_path()
+                ))
+                .collect::<Vec<_>>(),
+            vec![
+                (
+                    ChangeType::Insert,
+                    0,
+                    snapshot_ids[0],
+                    "test/changelog/data/file1.parquet"
+                ),
+                (
+                    ChangeType::Insert,
+                    1,
+                    snapshot_ids[1],
+                    "test/changelog/data/file2.parquet"
+                ),
+            ]
+        );
+        let tasks = table
+            .changelog_scan()
+            .from_snapshot_exclusive(snapshot_ids[0])
+            .plan_files()
+            .await
+            .unwrap();
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(tasks[0].change_ordinal(), 0);
+
+        // The metadata columns follow the columns of the rows read for the task
+        let rows = RecordBatch::try_new(
+            Arc::new(ArrowSchema::new(vec![Field::new(
+                "id",
+                DataType::Int64,
+                false,
+            )])),
+            vec![Arc::new(Int64Array::from(vec![1, 2]))],
+        )
+        .unwrap();
+        let batch = tasks[0].to_record_batch(&rows).unwrap();
+        let arrow_schema = table.changelog_scan().arrow_schema().unwrap();
+        assert_eq!(
+            batch
+                .schema()
+                .fields()
+                .iter()
+                .map(|field| field.name().as_str())
+                .collect::<Vec<_>>(),
+            arrow_schema
+                .fields()
+                .iter()
+                .map(|field| field.name().as_str())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            batch
+                .column(1)
+                .as_any()
+                .downcast_ref::<StringArray>()
+                .unwrap()
+                .value(1),
+            "INSERT"
+        );
+        assert_eq!(
+            batch
+                .column(2)
+                .as_any()
+                .downcast_ref::<Int32Array>()
+                .unwrap()
+                .value(0),
+            0
+        );
+        assert_eq!(
+            batch
+                .column(3)
+                .as_any()
+                .downcast_ref::<Int64Array>()
+                .unwrap()
+                .value(0),
+            snapshot_ids[1]
+        );
+    }
+}
@@ -0,0 +1,1217 @@
+/*!
+Defines the [TableScan] builder that plans which files have to be read to scan a table.
+*/
+
+use std::collections::{hash_map::Entry, HashMap, HashSet, VecDeque};
+
+use anyhow::{anyhow, Result};
+
+use crate::{
+    expr::{
+        manifest_evaluator::ManifestEvaluator, metrics_evaluator::MetricsEvaluator,
+        residual::ResidualEvaluator, BoundExpression, Expression, Reference, UnboundExpression,
+    },
+    model::{
+        manifest::{Content, ManifestEntry, Status},
+        manifest_list::ManifestFile,
+        partition::PartitionField,
+        schema::{AllType, SchemaStruct},
+        snapshot::Operation,
+        table_metadata::TableMetadata,
+    },
+};
+
+use self::changelog::ChangelogScan;
+
+use super::{files::read_manifest, read_manifest_list, transaction::table_property, Table};
+
+pub mod changelog;
+
+/// Table property for the target size in bytes of the combined tasks of a scan.
+pub static SPLIT_SIZE: &str = "read.split.target-size";
+/// Table property for the estimated cost in bytes of opening a file, used as the minimum weight of a task when
+/// combining tasks.
+pub static SPLIT_OPEN_FILE_COST: &str = "read.split.open-file-cost";
+/// Table property for the number of combined tasks that are considered when adding a task.
+pub static SPLIT_LOOKBACK: &str = "read.split.planning-lookback";
+
+/// Builder for a scan of a table. The scan reads the current snapshot of the table unless another snapshot is chosen.
+pub struct TableScan<'table> {
+    table: &'table Table,
+    columns: Option<Vec<String>>,
+    filter: UnboundExpression,
+    snapshot: ScanSnapshot,
+    case_sensitive: bool,
+}
+
+/// Builder for a scan of the data files appended to a table between two snapshots. Files are read from the append
+/// snapshots between the start snapshot (exclusive) and the end snapshot (inclusive) of the range.
+pub struct IncrementalAppendScan<'table> {
+    scan: TableScan<'table>,
+    from_snapshot: Option<i64>,
+    to_snapshot: Option<i64>,
+    skip_non_append: bool,
+}
+
+/// The snapshot a scan reads
+#[derive(Debug, Clone)]
+enum ScanSnapshot {
+    Current,
+    Id(i64),
+    AsOfTimestamp(i64),
+    Ref(String),
+}
+
+/// A data file or a byte range of it that has to be read for a scan, together with the delete files that apply to its
+/// rows.
+#[derive(Debug, Clone)]
+pub struct FileScanTask {
+    data_file: ManifestEntry,
+    delete_files: Vec<ManifestEntry>,
+    residual: BoundExpression,
+    start: i64,
+    length: i64,
+}
+
+/// Group of [FileScanTask]s that are read together, for example by one worker of a distributed reader.
+#[derive(Debug, Clone)]
+pub struct CombinedScanTask {
+    tasks: Vec<FileScanTask>,
+}
+
+impl FileScanTask {
+    /// Get the manifest entry of the data file to read
+    pub fn data_file(&self) -> &ManifestEntry {
+        &self.data_file
+    }
+    /// Get the manifest entries of the delete files whose deletes have to be applied to the rows of the data file
+    pub fn delete_files(&self) -> &[ManifestEntry] {
+        &self.delete_files
+    }
+    /// Get the part of the scan filter that isn't guaranteed by the partition of the data file and still has to be
+    /// applied to its rows
+    pub fn residual(&self) -> &BoundExpression {
+        &self.residual
+    }
+    /// Get the offset in bytes of the range of the data file to read
+    pub fn start(&self) -> i64 {
+        self.start
+    }
+    /// Get the length in bytes of the range of the data file to read
+    pub fn length(&self) -> i64 {
+        self.length
+    }
+    /// Split the task into tasks that read parts of the data file with about the target size. The split offsets of the
+    /// data file are used if they are valid, so that a task never starts in the middle of a row group.
+    pub fn split(self, target_size: i64) -> Vec<FileScanTask> {
+        if self.length <= target_size {
+            return vec![self];
+        }
+        let end = self.start + self.length;
+        let offsets = match self.data_file.split_offsets() {
+            Some(offsets)
+                if !offsets.is_empty()
+                    && offsets.windows(2).all(|pair| pair[0] < pair[1])
+                    && offsets[0] >= self.start
+                    && offsets[offsets.len() - 1] < end =>
+            {
+                // Adjacent ranges are combined as long as they don't exceed the target size
+                let mut boundaries = vec![self.start];
+                let mut size = 0;
+                for (offset, next) in offsets.iter().zip(offsets.iter().skip(1).chain([&end])) {
+                    if size > 0 && size + next - offset > target_size {
+                        boundaries.push(*offset);
+                        size = 0;
+                    }
+                    size += next - offset;
+                }
+                boundaries
+            }
+            _ => (self.start..end)
+                .step_by(target_size.max(1) as usize)
+                .collect(),
+        };
+        let ends = offsets.iter().skip(1).copied().chain([end]);
+        offsets
+            .iter()
+            .zip(ends)
+            .map(|(start, end)| FileScanTask {
+                start: *start,
+                length: end - start,
+                ..self.clone()
+            })
+            .collect()
+    }
+    /// Estimated number of bytes that have to be read for the task, including its delete files
+    fn size_in_bytes(&self) -> i64 {
+        self.length
+            + self
+                .delete_files
+                .iter()
+                .map(|file| file.file_size_in_bytes())
+                .sum::<i64>()
+    }
+}
+
+impl CombinedScanTask {
+    /// Get the tasks that are read together
+    pub fn tasks(&self) -> &[FileScanTask] {
+        &self.tasks
+    }
+}
+
+impl Table {
+    /// Create a scan of the table
+    pub fn scan(&self) -> TableScan<'_> {
+        TableScan::new(self)
+    }
+    /// Create a scan of the data files that were appended to the table between two snapshots
+    pub fn incremental_append_scan(&self) -> IncrementalAppendScan<'_> {
+        IncrementalAppendScan::new(self)
+    }
+    /// Create a scan of the rows that were inserted into and deleted from the table between two snapshots
+    pub fn changelog_scan(&self) -> ChangelogScan<'_> {
+        ChangelogScan::new(self)
+    }
+}
+
+impl<'table> TableScan<'table> {
+    /// Create a scan of all columns and rows of the current snapshot of the table
+    pub fn new(table: &'table Table) -> Self {
+        TableScan {
+            table,
+            columns: None,
+            filter: Expression::True,
+            snapshot: ScanSnapshot::Current,
+            case_sensitive: true,
+        }
+    }
+    /// Only read the given columns. Nested columns are selected by their dotted name.
+    pub fn select(mut self, columns: &[&str]) -> Self {
+        self.columns = Some(columns.iter().map(|column| column.to_string()).collect());
+        self
+    }
+    /// Only read the rows matching the filter. Multiple filters are combined, so that rows have to match all of them.
+    pub fn filter(mut self, filter: UnboundExpression) -> Self {
+        self.filter = self.filter.and(filter);
+        self
+    }
+    /// Read the snapshot with the given id instead of the current snapshot
+    pub fn snapshot_id(mut self, snapshot_id: i64) -> Self {
+        self.snapshot = ScanSnapshot::Id(snapshot_id);
+        self
+    }
+    /// Read the snapshot that was current at the given time in milliseconds from the unix epoch. Snapshots that were
+    /// rolled back are never read.
+    pub fn as_of_timestamp(mut self, timestamp_ms: i64) -> Self {
+        self.snapshot = ScanSnapshot::AsOfTimestamp(timestamp_ms);
+        self
+    }
+    /// Read the snapshot the branch or tag with the given name points to
+    pub fn use_ref(mut self, name: &str) -> Self {
+        self.snapshot = ScanSnapshot::Ref(name.to_string());
+        self
+    }
+    /// Whether column names of the selection and the filter are matched case sensitively. Defaults to true.
+    pub fn case_sensitive(mut self, case_sensitive: bool) -> Self {
+        self.case_sensitive = case_sensitive;
+        self
+    }
+    /// Get the id of the snapshot the scan reads. Returns None if the scan reads the current snapshot of a table
+    /// without snapshots. Fails if the chosen snapshot doesn't exist.
+    pub fn snapshot(&self) -> Result<Option<i64>> {
+        let metadata = self.table.metadata();
+        let snapshot_id = match &self.snapshot {
+            ScanSnapshot::Current => return Ok(metadata.current_snapshot_id()),
+            ScanSnapshot::Id(snapshot_id) => {
+                if metadata.snapshot(*snapshot_id).is_none() {
+                    return Err(anyhow!(
+                        "Snapshot {} doesn't exist in the table.",
+                        snapshot_id
+                    ));
+                }
+                *snapshot_id
+            }
+            ScanSnapshot::AsOfTimestamp(timestamp_ms) => metadata
+                .snapshot_as_of_time(*timestamp_ms)
+                .map(|snapshot| snapshot.snapshot_id)
+                .ok_or_else(|| anyhow!("The table had no snapshot at {} ms.", timestamp_ms))?,
+            ScanSnapshot::Ref(name) => metadata
+                .snapshot_id_for_ref(name)
+                .ok_or_else(|| anyhow!("The table has no branch or tag named {}.", name))?,
+        };
+        Ok(Some(snapshot_id))
+    }
+    /// Get the schema of the table for the scan. Scans of an older snapshot use the schema of that snapshot.
+    fn table_schema(&self) -> Result<&'table SchemaStruct> {
+        match (&self.snapshot, self.snapshot()?) {
+            (ScanSnapshot::Current, _) | (_, None) => Ok(self.table.schema()),
+            (_, Some(snapshot_id)) => self
+                .table
+                .metadata()
+                .snapshot_schema(snapshot_id)
+                .ok_or_else(|| anyhow!("Snapshot {} doesn't exist in the table.", snapshot_id)),
+        }
+    }
+    /// Get the schema of the scanned rows, which only contains the selected columns and the structs they are nested in.
+    pub fn schema(&self) -> Result<SchemaStruct> {
+        let schema = self.table_schema()?;
+        match &self.columns {
+            None => Ok(schema.clone()),
+            Some(columns) => {
+                let ids = columns
+                    .iter()
+                    .map(|column| {
+                        Reference::new(column)
+                            .bind(schema, self.case_sensitive)
+                            .map(|reference| reference.field_id)
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                Ok(select_fields(schema, &ids))
+            }
+        }
+    }
+    /// Plan the files that have to be read for the scan. Manifests and data files that can't contain rows matching the
+    /// filter are skipped based on their partition summaries, partition values and column metrics.
+    pub async fn plan_files(&self) -> Result<Vec<FileScanTask>> {
+        let filter = self.bound_filter()?;
+        match self.snapshot()? {
+            None => Ok(Vec::new()),
+            Some(snapshot_id) => {
+                let manifests = self.snapshot_manifests(snapshot_id).await?;
+                self.plan_manifests(&manifests, &filter, |_, _| true).await
+            }
+        }
+    }
+    /// Plan the files of the scan and combine them into tasks of about the target split size. Large files are split
+    /// and small files are packed together. The sizes are configured with the `read.split.*` table properties.
+    pub async fn plan_tasks(&self) -> Result<Vec<CombinedScanTask>> {
+        self.combine_tasks(self.plan_files().await?)
+    }
+    /// Bind the filter to the schema of the scan. Also fails if the selected columns don't exist.
+    fn bound_filter(&self) -> Result<BoundExpression> {
+        let schema = self.table_schema()?;
+        self.schema()?;
+        if self.case_sensitive {
+            self.filter.clone().bind(schema)
+        } else {
+            self.filter.clone().bind_case_insensitive(schema)
+        }
+    }
+    /// Get the manifests of the snapshot with the given id.
+    async fn snapshot_manifests(&self, snapshot_id: i64) -> Result<Vec<ManifestFile>> {
+        let metadata = self.table.metadata();
+        if Some(snapshot_id) == metadata.current_snapshot_id() {
+            return Ok(self.table.manifests().to_vec());
+        }
+        let manifest_list = metadata
+            .snapshot_manifest_list(snapshot_id)
+            .ok_or_else(|| anyhow!("Snapshot {} doesn't exist in the table.", snapshot_id))?;
+        read_manifest_list(
+            manifest_list,
+            metadata.format_version(),
+            self.table.object_store(),
+        )
+        .await
+    }
+    /// Plan the files of the manifests that match the filter. Only the live entries for which `include` returns true
+    /// are considered.
+    async fn plan_manifests(
+        &self,
+        manifests: &[ManifestFile],
+        filter: &BoundExpression,
+        include: impl Fn(&ManifestFile, &ManifestEntry) -> bool,
+    ) -> Result<Vec<FileScanTask>> {
+        let (data_files, delete_files) = self
+            .scan_manifests(manifests, filter, |manifest, entry| {
+                *entry.status() != Status::Deleted && include(manifest, entry)
+            })
+            .await?;
+        Ok(data_files
+            .into_iter()
+            .map(|(file, residual)| {
+                let delete_files = delete_files
+                    .iter()
+                    .filter(|delete_file| delete_file.applies_to(&file))
+                    .map(|delete_file| delete_file.entry.clone())
+                    .collect();
+                file.into_task(delete_files, residual)
+            })
+            .collect())
+    }
+    /// Read the entries of the manifests for which `include` returns true and that might contain rows matching the
+    /// filter. Returns the data files together with their residual filter and the delete files.
+    async fn scan_manifests(
+        &self,
+        manifests: &[ManifestFile],
+        filter: &BoundExpression,
+        include: impl Fn(&ManifestFile, &ManifestEntry) -> bool,
+    ) -> Result<(Vec<(ScannedFile, BoundExpression)>, Vec<ScannedFile>)> {
+        let metadata = self.table.metadata();
+        let metrics_evaluator = MetricsEvaluator::new(filter);
+        let mut evaluators: HashMap<i32, (ManifestEvaluator, ResidualEvaluator)> = HashMap::new();
+        let mut data_files: Vec<(ScannedFile, BoundExpression)> = Vec::new();
+        let mut delete_files: Vec<ScannedFile> = Vec::new();
+        for manifest in manifests {
+            let spec_id = manifest.partition_spec_id();
+            let (manifest_evaluator, residual_evaluator) = match evaluators.entry(spec_id) {
+                Entry::Occupied(entry) => entry.into_mut(),
+                Entry::Vacant(entry) => {
+                    let spec = metadata.get_spec(spec_id).ok_or_else(|| {
+                        anyhow!(
+                            "Partition spec {} doesn't exist in the table metadata.",
+                            spec_id
+                        )
+                    })?;
+                    entry.insert((
+                        ManifestEvaluator::new(filter, spec)?,
+                        ResidualEvaluator::new(filter, spec),
+                    ))
+                }
+            };
+            if !manifest_evaluator.eval(manifest)? {
+                continue;
+            }
+            let entries = read_manifest(
+                self.table.object_store(),
+                manifest,
+                metadata.format_version(),
+            )
+            .await?;
+            for entry in entries {
+                if !include(manifest, &entry) {
+                    continue;
+                }
+                let residual = residual_evaluator.residual(entry.partition_values())?;
+                if residual == Expression::False {
+                    continue;
+                }
+                let file = ScannedFile::new(entry, manifest, metadata.get_spec(spec_id));
+                match file.entry.content() {
+                    Content::Data => {
+                        if metrics_evaluator.eval(&file.entry)? {
+                            data_files.push((file, residual))
+                        }
+                    }
+                    Content::PositionDeletes | Content::EqualityDeletes => delete_files.push(file),
+                }
+            }
+        }
+
+        Ok((data_files, delete_files))
+    }
+    /// Split and pack the planned files into tasks of about the target split size of the table.
+    fn combine_tasks(&self, files: Vec<FileScanTask>) -> Result<Vec<CombinedScanTask>> {
+        let properties = self.table.metadata().properties();
+        let split_size = split_property(properties, SPLIT_SIZE, 128 * 1024 * 1024)?;
+        let open_file_cost = split_property(properties, SPLIT_OPEN_FILE_COST, 4 * 1024 * 1024)?;
+        let lookback = split_property(properties, SPLIT_LOOKBACK, 10)?.max(1) as usize;
+        let tasks = files.into_iter().flat_map(|task| task.split(split_size));
+        Ok(pack(tasks, split_size, lookback, |task| {
+            task.size_in_bytes().max(open_file_cost)
+        })
+        .into_iter()
+        .map(|tasks| CombinedScanTask { tasks })
+        .collect())
+    }
+}
+
+impl<'table> IncrementalAppendScan<'table> {
+    /// Create a scan of the files appended to the table up to its current snapshot
+    pub fn new(table: &'table Table) -> Self {
+        IncrementalAppendScan {
+            scan: TableScan::new(table),
+            from_snapshot: None,
+            to_snapshot: None,
+            skip_non_append: false,
+        }
+    }
+    /// Only read the given columns. Nested columns are selected by their dotted name.
+    pub fn select(mut self, columns: &[&str]) -> Self {
+        self.scan = self.scan.select(columns);
+        self
+    }
+    /// Only read the rows matching the filter. Multiple filters are combined, so that rows have to match all of them.
+    pub fn filter(mut self, filter: UnboundExpression) -> Self {
+        self.scan = self.scan.filter(filter);
+        self
+    }
+    /// Whether column names of the selection and the filter are matched case sensitively. Defaults to true.
+    pub fn case_sensitive(mut self, case_sensitive: bool) -> Self {
+        self.scan = self.scan.case_sensitive(case_sensitive);
+        self
+    }
+    /// Only read files appended after the snapshot with the given id. The snapshot has to be an ancestor of the end
+    /// snapshot. Defaults to the first snapshot of the table, including its files.
+    pub fn from_snapshot_exclusive(mut self, snapshot_id: i64) -> Self {
+        self.from_snapshot = Some(snapshot_id);
+        self
+    }
+    /// Read the files appended up to and including the snapshot with the given id. Defaults to the current snapshot.
+    pub fn to_snapshot(mut self, snapshot_id: i64) -> Self {
+        self.to_snapshot = Some(snapshot_id);
+        self
+    }
+    /// Whether overwrite and delete snapshots in the range are skipped. By default the scan fails for them, because
+    /// the files they add don't only contain new rows. Replace snapshots are always skipped and snapshots without an
+    /// operation are always read. Defaults to false.
+    pub fn skip_non_append(mut self, skip_non_append: bool) -> Self {
+        self.skip_non_append = skip_non_append;
+        self
+    }
+    /// Get the schema of the scanned rows, which only contains the selected columns and the structs they are nested in.
+    pub fn schema(&self) -> Result<SchemaStruct> {
+        self.scan.schema()
+    }
+    /// Get the ids of the snapshots whose added files the scan reads, from the oldest to the newest. These are the
+    /// append snapshots and the snapshots without an operation in the range. Replace snapshots are skipped, because
+    /// the files they add contain rows that were already appended. Fails if the start snapshot isn't an ancestor of
+    /// the end snapshot or if the range contains an overwrite or delete snapshot that isn't skipped.
+    pub fn snapshots(&self) -> Result<Vec<i64>> {
+        let metadata = self.scan.table.metadata();
+        let mut snapshot_ids = Vec::new();
+        for snapshot_id in snapshot_range(metadata, self.from_snapshot, self.to_snapshot)? {
+            match metadata.snapshot_operation(snapshot_id) {
+                // Transactions that only change the metadata, like the schema or the partition spec, don't record an
+                // operation and add no files. Other writers may omit the operation of appends.
+                Some(Operation::Append) | None => snapshot_ids.push(snapshot_id),
+                // Replaced files contain rows that were already appended
+                Some(Operation::Replace) => (),
+                Some(_) if self.skip_non_append => (),
+                Some(operation) => {
+                    return Err(anyhow!(
+                        "Snapshot {} has the operation {:?}, an incremental append scan can only read append snapshots.",
+                        snapshot_id,
+                        operation
+                    ))
+                }
+            }
+        }
+        Ok(snapshot_ids)
+    }
+    /// Plan the data files that were appended in the range of snapshots and might contain rows matching the filter.
+    pub async fn plan_files(&self) -> Result<Vec<FileScanTask>> {
+        let filter = self.scan.bound_filter()?;
+        let snapshots = self.snapshots()?;
+        let snapshot_ids: HashSet<i64> = snapshots.iter().copied().collect();
+        // Files are only added by manifests of the snapshot that added them. Every snapshot lists the manifests of its
+        // ancestors as well, so the manifests are deduplicated by their path.
+        let mut manifest_paths = HashSet::new();
+        let mut manifests: Vec<ManifestFile> = Vec::new();
+        for snapshot_id in &snapshots {
+            for manifest in self.scan.snapshot_manifests(*snapshot_id).await? {
+                if snapshot_ids.contains(&manifest.added_snapshot_id())
+                    && manifest_paths.insert(manifest.manifest_path().to_string())
+                {
+                    manifests.push(manifest);
+                }
+            }
+        }
+        self.scan
+            .plan_manifests(&manifests, &filter, |manifest, entry| {
+                *entry.status() == Status::Added
+                    && entry.content() == Content::Data
+                    && snapshot_ids.contains(
+                        &entry
+                            .snapshot_id()
+                            .unwrap_or_else(|| manifest.added_snapshot_id()),
+                    )
+            })
+            .await
+    }
+    /// Plan the appended files and combine them into tasks of about the target split size, like
+    /// [TableScan::plan_tasks].
+    pub async fn plan_tasks(&self) -> Result<Vec<CombinedScanTask>> {
+        self.scan.combine_tasks(self.plan_files().await?)
+    }
+}
+
+/// Get the ids of the snapshots after the start snapshot (exclusive) up to the end snapshot (inclusive), from the
+/// oldest to the newest. Without a start snapshot the range begins with the first ancestor of the end snapshot, the end
+/// snapshot defaults to the current snapshot. Fails if the start snapshot isn't an ancestor of the end snapshot.
+fn snapshot_range(
+    metadata: &TableMetadata,
+    from_snapshot: Option<i64>,
+    to_snapshot: Option<i64>,
+) -> Result<Vec<i64>> {
+    for snapshot_id in from_snapshot.iter().chain(to_snapshot.iter()) {
+        if metadata.snapshot(*snapshot_id).is_none() {
+            return Err(anyhow!(
+                "Snapshot {} doesn't exist in the table.",
+                snapshot_id
+            ));
+        }
+    }
+    let to_snapshot = match to_snapshot.or_else(|| metadata.current_snapshot_id()) {
+        Some(snapshot_id) => snapshot_id,
+        None => return Ok(Vec::new()),
+    };
+    let snapshots = match from_snapshot {
+        Some(from_snapshot) => metadata
+            .snapshots_between(from_snapshot, to_snapshot)
+            .ok_or_else(|| {
+                anyhow!(
+                    "Snapshot {} is not an ancestor of snapshot {}.",
+                    from_snapshot,
+                    to_snapshot
+                )
+            })?,
+        None => metadata
+            .ancestors_of(to_snapshot)
+            .into_iter()
+            .rev()
+            .collect(),
+    };
+    Ok(snapshots
+        .iter()
+        .map(|snapshot| snapshot.snapshot_id)
+        .collect())
+}
+
+/// Read a numeric split planning property of the table, which has to be positive.
+fn split_property(
+    properties: Option<&HashMap<String, String>>,
+    key: &str,
+    default: i64,
+) -> Result<i64> {
+    match table_property(properties, key, default)? {
+        value if value > 0 => Ok(value),
+        value => Err(anyhow!(
+            "Table property {} has the invalid value {}.",
+            key,
+            value
+        )),
+    }
+}
+
+/// Pack the items into bins of the target weight. An item is added to the first of the last `lookback` bins it fits
+/// into, otherwise it starts a new bin. Items that are heavier than the target weight get a bin of their own.
+fn pack<T>(
+    items: impl IntoIterator<Item = T>,
+    target_weight: i64,
+    lookback: usize,
+    weight: impl Fn(&T) -> i64,
+) -> Vec<Vec<T>> {
+    let mut packed = Vec::new();
+    let mut open: VecDeque<(i64, Vec<T>)> = VecDeque::with_capacity(lookback + 1);
+    for item in items {
+        let item_weight = weight(&item);
+        match open
+            .iter_mut()
+            .find(|(bin_weight, _)| bin_weight + item_weight <= target_weight)
+        {
+            Some((bin_weight, bin)) => {
+                *bin_weight += item_weight;
+                bin.push(item);
+            }
+            None => {
+                open.push_back((item_weight, vec![item]));
+                if open.len() > lookback {
+                    packed.extend(open.pop_front().map(|(_, bin)| bin));
+                }
+            }
+        }
+    }
+    packed.extend(open.into_iter().map(|(_, bin)| bin));
+    packed
+}
+
+/// A manifest entry with the information inherited from its manifest
+struct ScannedFile {
+    entry: ManifestEntry,
+    snapshot_id: i64,
+    spec_id: i32,
+    sequence_number: i64,
+    unpartitioned: bool,
+}
+
+impl ScannedFile {
+    fn new(entry: ManifestEntry, manifest: &ManifestFile, spec: Option<&[PartitionField]>) -> Self {
+        ScannedFile {
+            snapshot_id: entry
+                .snapshot_id()
+                .unwrap_or_else(|| manifest.added_snapshot_id()),
+            sequence_number: entry
+                .sequence_number()
+                .unwrap_or_else(|| manifest.sequence_number()),
+            spec_id: manifest.partition_spec_id(),
+            unpartitioned: spec.is_none_or(|spec| spec.is_empty()),
+            entry,
+        }
+    }
+    /// Create a task that reads the whole data file
+    fn into_task(
+        self,
+        delete_files: Vec<ManifestEntry>,
+        residual: BoundExpression,
+    ) -> FileScanTask {
+        FileScanTask {
+            start: 0,
+            length: self.entry.file_size_in_bytes(),
+            data_file: self.entry,
+            delete_files,
+            residual,
+        }
+    }
+    /// Check if the deletes of this delete file apply to the rows of the data file. Position deletes apply to data
+    /// files of the same or an older sequence number, equality deletes only to data files that were written before.
+    /// Delete files of an unpartitioned spec apply to all partitions.
+    fn applies_to(&self, data_file: &ScannedFile) -> bool {
+        let same_partition = self.unpartitioned
+            || (self.spec_id == data_file.spec_id
+                && self.entry.partition_values() == data_file.entry.partition_values());
+        same_partition
+            && match self.entry.content() {
+                Content::PositionDeletes => self.sequence_number >= data_file.sequence_number,
+                Content::EqualityDeletes => self.sequence_number > data_file.sequence_number,
+                Content::Data => false,
+            }
+    }
+}
+
+/// Only keep the fields with the given ids and the structs that contain them.
+fn select_fields(schema: &SchemaStruct, ids: &[i32]) -> SchemaStruct {
+    SchemaStruct {
+        fields: schema
+            .fields
+            .iter()
+            .filter_map(|field| match &field.field_type {
+                _ if ids.contains(&field.id) => Some(field.clone()),
+                AllType::Struct(nested) => {
+                    let nested = select_fields(nested, ids);
+                    if nested.fields.is_empty() {
+                        None
+                    } else {
+                        let mut field = field.clone();
+                        field.field_type = AllType::Struct(nested);
+                        Some(field)
+                    }
+                }
+                _ => None,
+            })
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::{collections::HashMap, sync::Arc};
+
+    use object_store::{memory::InMemory, ObjectStore};
+
+    use crate::{
+        expr::{Expression, Reference as ColumnReference},
+        model::{
+            manifest::{
+                Content, DataFileV2, FileFormat, ManifestEntry, ManifestEntryV2, PartitionValues,
+                Status,
+            },
+            schema::{AllType, PrimitiveType, SchemaStruct, SchemaV2, StructField},
+            snapshot::{Operation, Reference, Retention},
+            table_metadata::TableMetadata,
+            types::Value,
+        },
+        table::{table_builder::TableBuilder, Table},
+    };
+
+    use super::{pack, FileScanTask, ScannedFile, SPLIT_OPEN_FILE_COST, SPLIT_SIZE};
+
+    async fn table(object_store: Arc<dyn ObjectStore>, properties: &[(&str, &str)]) -> Table {
+        let schema = SchemaV2 {
+            schema_id: 1,
+            identifier_field_ids: Some(vec![1, 2]),
+            name_mapping: None,
+            struct_fields: SchemaStruct {
+                fields: vec![
+                    StructField {
+                        id: 1,
+                        name: "one".to_string(),
+                        required: false,
+                        field_type: AllType::Primitive(PrimitiveType::String),
+                        doc: None,
+                    },
+                    StructField {
+                        id: 2,
+                        name: "two".to_string(),
+                        required: false,
+                        field_type: AllType::Primitive(PrimitiveType::String),
+                        doc: None,
+                    },
+                ],
+            },
+        };
+        properties
+            .iter()
+            .fold(
+                TableBuilder::new_filesystem_table("test/scan", schema, object_store).unwrap(),
+                |builder, (key, value)| builder.with_property(key, value),
+            )
+            .commit()
+            .await
+            .unwrap()
+    }
+
+    fn paths(tasks: &[FileScanTask]) -> Vec<&str> {
+        tasks
+            .iter()
+            .map(|task| task.data_file().file_path())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_plan_files() {
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let mut table = table(object_store, &[]).await;
+        table
+            .new_transaction()
+            .fast_append(vec![
+                "test/scan/data/file1.parquet".to_string(),
+                "test/scan/data/file2.parquet".to_string(),
+            ])
+            .commit()
+            .await
+            .unwrap();
+        let first_snapshot_id = table.metadata().current_snapshot_id().unwrap();
+        table
+            .new_transaction()
+            .fast_append(vec!["test/scan/data/file3.parquet".to_string()])
+            .commit()
+            .await
+            .unwrap();
+
+        let tasks = table.scan().plan_files().await.unwrap();
+        assert_eq!(
+            paths(&tasks),
+            vec![
+                "test/scan/data/file1.parquet",
+                "test/scan/data/file2.parquet",
+                "test/scan/data/file3.parquet"
+            ]
+        );
+        assert!(tasks
+            .iter()
+            .all(|task| task.delete_files().is_empty() && *task.residual() == Expression::True));
+
+        // Older snapshots only contain the files that were appended before them
+        let tasks = table
+            .scan()
+            .snapshot_id(first_snapshot_id)
+            .plan_files()
+            .await
+            .unwrap();
+        assert_eq!(
+            paths(&tasks),
+            vec![
+                "test/scan/data/file1.parquet",
+                "test/scan/data/file2.parquet"
+            ]
+        );
+        assert!(table.scan().snapshot_id(1).plan_files().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_filter_and_select() {
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let mut table = table(object_store, &[]).await;
+        table
+            .new_transaction()
+            .fast_append(vec!["test/scan/data/file1.parquet".to_string()])
+            .commit()
+            .await
+            .unwrap();
+
+        // The table isn't partitioned, so the whole filter remains for the rows of the file
+        let filter = || ColumnReference::new("ONE").equal(Value::String("a".to_string()));
+        assert!(table.scan().filter(filter()).plan_files().await.is_err());
+        let tasks = table
+            .scan()
+            .case_sensitive(false)
+            .filter(filter())
+            .plan_files()
+            .await
+            .unwrap();
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(
+            *tasks[0].residual(),
+            filter().bind_case_insensitive(table.schema()).unwrap()
+        );
+
+        let scan = table.scan().select(&["two"]);
+        let schema = scan.schema().unwrap();
+        assert_eq!(schema.fields.len(), 1);
+        assert_eq!(schema.fields[0].name, "two");
+        assert!(table.scan().select(&["three"]).plan_files().await.is_err());
+    }
+
+    fn file(content: Content, sequence_number: i64, partition: &str) -> ScannedFile {
+        ScannedFile {
+            entry: ManifestEntry::V2(ManifestEntryV2 {
+                status: Status::Added,
+                snapshot_id: Some(1),
+                sequence_number: Some(sequence_number),
+                data_file: DataFileV2 {
+                    content,
+                    file_path: "test/scan/data/file.parquet".to_string(),
+                    file_format: FileFormat::Parquet,
+                    partition: PartitionValues::from_iter(vec![(
+                        "one".to_string(),
+                        Some(Value::String(partition.to_string())),
+                    )]),
+                    record_count: 4,
+                    file_size_in_bytes: 1200,
+                    column_sizes: None,
+                    value_counts: None,
+                    null_value_counts: None,
+                    nan_value_counts: None,
+                    distinct_counts: None,
+                    lower_bounds: None,
+                    upper_bounds: None,
+                    key_metadata: None,
+                    split_offsets: None,
+                    equality_ids: None,
+                    sort_order_id: None,
+                },
+            }),
+            snapshot_id: 1,
+            spec_id: 1,
+            sequence_number,
+            unpartitioned: false,
+        }
+    }
+
+    #[test]
+    fn test_delete_files() {
+        let data_file = file(Content::Data, 2, "a");
+        assert!(file(Content::PositionDeletes, 2, "a").applies_to(&data_file));
+        assert!(!file(Content::PositionDeletes, 1, "a").applies_to(&data_file));
+        assert!(!file(Content::PositionDeletes, 3, "b").applies_to(&data_file));
+        assert!(file(Content::EqualityDeletes, 3, "a").applies_to(&data_file));
+        assert!(!file(Content::EqualityDeletes, 2, "a").applies_to(&data_file));
+        let mut global = file(Content::EqualityDeletes, 3, "b");
+        global.unpartitioned = true;
+        assert!(global.applies_to(&data_file));
+    }
+
+    fn task(split_offsets: Option<Vec<i64>>) -> FileScanTask {
+        let mut entry = file(Content::Data, 1, "a").entry;
+        if let ManifestEntry::V2(entry) = &mut entry {
+            entry.data_file.file_size_in_bytes = 100;
+            entry.data_file.split_offsets = split_offsets;
+        }
+        FileScanTask {
+            data_file: entry,
+            delete_files: vec![],
+            residual: Expression::True,
+            start: 0,
+            length: 100,
+        }
+    }
+
+    fn ranges(tasks: Vec<FileScanTask>) -> Vec<(i64, i64)> {
+        tasks
+            .iter()
+            .map(|task| (task.start(), task.length()))
+            .collect()
+    }
+
+    #[test]
+    fn test_split() {
+        // Row groups are combined up to the target size
+        assert_eq!(
+            ranges(task(Some(vec![4, 30, 60, 90])).split(50)),
+            vec![(0, 30), (30, 30), (60, 40)]
+        );
+        assert_eq!(
+            ranges(task(None).split(40)),
+            vec![(0, 40), (40, 40), (80, 20)]
+        );
+        // Invalid offsets are ignored
+        assert_eq!(
+            ranges(task(Some(vec![60, 30])).split(40)),
+            vec![(0, 40), (40, 40), (80, 20)]
+        );
+        assert_eq!(ranges(task(Some(vec![4])).split(200)), vec![(0, 100)]);
+    }
+
+    #[test]
+    fn test_pack() {
+        assert_eq!(
+            pack(vec![60, 50, 30], 100, 2, |weight| *weight),
+            vec![vec![60, 30], vec![50]]
+        );
+        // With a lookback of one only the last bin can take more items
+        assert_eq!(
+            pack(vec![60, 50, 30], 100, 1, |weight| *weight),
+            vec![vec![60], vec![50, 30]]
+        );
+        assert_eq!(
+            pack(vec![150, 20], 100, 1, |weight| *weight),
+            vec![vec![150], vec![20]]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_plan_tasks() {
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let mut table = table(
+            object_store,
+            &[(SPLIT_SIZE, "1000"), (SPLIT_OPEN_FILE_COST, "100")],
+        )
+        .await;
+        table
+            .new_transaction()
+            .fast_append(vec![
+                "test/scan/data/file1.parquet".to_string(),
+                "test/scan/data/file2.parquet".to_string(),
+                "test/scan/data/file3.parquet".to_string(),
+            ])
+            .commit()
+            .await
+            .unwrap();
+
+        // Every file of 1200 bytes is split in two, the small remainders are combined
+        let tasks = table.scan().plan_tasks().await.unwrap();
+        let tasks: Vec<Vec<(&str, i64)>> = tasks
+            .iter()
+            .map(|combined| {
+                combined
+                    .tasks()
+                    .iter()
+                    .map(|task| (task.data_file().file_path(), task.start()))
+                    .collect()
+            })
+            .collect();
+        assert_eq!(
+            tasks,
+            vec![
+                vec![("test/scan/data/file1.parquet", 0)],
+                vec![
+                    ("test/scan/data/file1.parquet", 1000),
+                    ("test/scan/data/file2.parquet", 1000),
+                    ("test/scan/data/file3.parquet", 1000)
+                ],
+                vec![("test/scan/data/file2.parquet", 0)],
+                vec![("test/scan/data/file3.parquet", 0)],
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_time_travel() {
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let mut table = table(object_store, &[]).await;
+        assert!(table.scan().plan_files().await.unwrap().is_empty());
+        table
+            .new_transaction()
+            .fast_append(vec!["test/scan/data/file1.parquet".to_string()])
+            .commit()
+            .await
+            .unwrap();
+        let first_snapshot_id = table.metadata().current_snapshot_id().unwrap();
+        table
+            .new_transaction()
+            .fast_append(vec!["test/scan/data/file2.parquet".to_string()])
+            .commit()
+            .await
+            .unwrap();
+        // The snapshots were created at 1000 and 2000 ms
+        if let TableMetadata::V2(metadata) = &mut table.metadata {
+            for (index, snapshot) in metadata.snapshots.iter_mut().flatten().enumerate() {
+                snapshot.timestamp_ms = 1000 * (index as i64 + 1);
+            }
+            for (index, entry) in metadata.snapshot_log.iter_mut().flatten().enumerate() {
+                entry.timestamp_ms = 1000 * (index as i64 + 1);
+            }
+        }
+
+        let files = |tasks: Vec<FileScanTask>| tasks.len();
+        assert_eq!(
+            files(
+                table
+                    .scan()
+                    .as_of_timestamp(1000)
+                    .plan_files()
+                    .await
+                    .unwrap()
+            ),
+            1
+        );
+        assert_eq!(
+            files(
+                table
+                    .scan()
+                    .as_of_timestamp(1999)
+                    .plan_files()
+                    .await
+                    .unwrap()
+            ),
+            1
+        );
+        assert_eq!(
+            files(
+                table
+                    .scan()
+                    .as_of_timestamp(2000)
+                    .plan_files()
+                    .await
+                    .unwrap()
+            ),
+            2
+        );
+        assert!(table
+            .scan()
+            .as_of_timestamp(999)
+            .plan_files()
+            .await
+            .is_err());
+        assert_eq!(
+            files(table.scan().use_ref("main").plan_files().await.unwrap()),
+            2
+        );
+        assert!(table.scan().use_ref("audit").plan_files().await.is_err());
+
+        // Tags point to older snapshots
+        if let TableMetadata::V2(metadata) = &mut table.metadata {
+            metadata.refs = Some(HashMap::from_iter(vec![(
+                "audit".to_string(),
+                Reference {
+                    snapshot_id: first_snapshot_id,
+                    retention: Retention::Tag {
+                        max_ref_age_ms: 1000,
+                    },
+                },
+            )]));
+        }
+        let scan = table.scan().use_ref("audit");
+        assert_eq!(scan.snapshot().unwrap(), Some(first_snapshot_id));
+        assert_eq!(files(scan.plan_files().await.unwrap()), 1);
+    }
+
+    #[tokio::test]
+    async fn test_incremental_append_scan() {
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let mut table = table(object_store, &[]).await;
+        assert!(table
+            .incremental_append_scan()
+            .plan_files()
+            .await
+            .unwrap()
+            .is_empty());
+        let mut snapshot_ids = Vec::new();
+        for file in ["file1", "file2", "file3"] {
+            table
+                .new_transaction()
+                .fast_append(vec![format!("test/scan/data/{}.parquet", file)])
+                .commit()
+                .await
+                .unwrap();
+            snapshot_ids.push(table.metadata().current_snapshot_id().unwrap());
+        }
+        assert_eq!(
+            table.metadata().snapshot_operation(snapshot_ids[0]),
+            Some(Operation::Append)
+        );
+
+        let scan = table
+            .incremental_append_scan()
+            .from_snapshot_exclusive(snapshot_ids[0]);
+        assert_eq!(scan.snapshots().unwrap(), snapshot_ids[1..].to_vec());
+        assert_eq!(
+            paths(&scan.plan_files().await.unwrap()),
+            vec![
+                "test/scan/data/file2.parquet",
+                "test/scan/data/file3.parquet"
+            ]
+        );
+        let tasks = table
+            .incremental_append_scan()
+            .from_snapshot_exclusive(snapshot_ids[0])
+            .to_snapshot(snapshot_ids[1])
+            .plan_files()
+            .await
+            .unwrap();
+        assert_eq!(paths(&tasks), vec!["test/scan/data/file2.parquet"]);
+        assert_eq!(
+            table
+                .incremental_append_scan()
+                .plan_files()
+                .await
+                .unwrap()
+                .len(),
+            3
+        );
+        // The start snapshot has to be an ancestor of the end snapshot
+        assert!(table
+            .incremental_append_scan()
+            .from_snapshot_exclusive(snapshot_ids[2])
+            .to_snapshot(snapshot_ids[0])
+            .snapshots()
+            .is_err());
+
+        // Overwrite snapshots fail the scan unless they are skipped
+        if let TableMetadata::V2(metadata) = &mut table.metadata {
+            for snapshot in metadata.snapshots.as_mut().unwrap() {
+                if snapshot.snapshot_id == snapshot_ids[1] {
+                    snapshot.summary.operation = Some(Operation::Overwrite);
+                }
+            }
+        }
+        assert!(table
+            .incremental_append_scan()
+            .from_snapshot_exclusive(snapshot_ids[0])
+            .plan_files()
+            .await
+            .is_err());
+        let tasks = table
+            .incremental_append_scan()
+            .from_snapshot_exclusive(snapshot_ids[0])
+            .skip_non_append(true)
+            .plan_files()
+            .await
+            .unwrap();
+        assert_eq!(paths(&tasks), vec!["test/scan/data/file3.parquet"]);
+    }
+
+    #[tokio::test]
+    async fn test_incremental_append_scan_schema_change() {
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let mut table = table(object_store, &[]).await;
+        let schema = SchemaV2 {
+            schema_id: 1,
+            identifier_field_ids: Some(vec![1, 2]),
+            name_mapping: None,
+            struct_fields: table.schema().clone(),
+        };
+        let mut snapshot_ids = Vec::new();
+        for transaction in ["file1", "schema", "file2"] {
+            let transaction = match transaction {
+                "schema" => table.new_transaction().update_schema(schema.clone()),
+                file => table
+                    .new_transaction()
+                    .fast_append(vec![format!("test/scan/data/{}.parquet", file)]),
+            };
+            transaction.commit().await.unwrap();
+            snapshot_ids.push(table.metadata().current_snapshot_id().unwrap());
+        }
+        // The schema change is recorded as a snapshot without an operation that adds no files
+        assert_eq!(table.metadata().snapshot_operation(snapshot_ids[1]), None);
+
+        let scan = table
+            .incremental_append_scan()
+            .from_snapshot_exclusive(snapshot_ids[0]);
+        assert_eq!(scan.snapshots().unwrap(), snapshot_ids[1..].to_vec());
+        assert_eq!(
+            paths(&scan.plan_files().await.unwrap()),
+            vec!["test/scan/data/file2.parquet"]
+        );
+        assert!(table
+            .incremental_append_scan()
+            .from_snapshot_exclusive(snapshot_ids[0])
+            .to_snapshot(snapshot_ids[1])
+            .plan_files()
+            .await
+            .unwrap()
+            .is_empty());
+    }
+}
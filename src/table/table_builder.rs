@@ -2,6 +2,7 @@
 Defining the [TableBuilder] struct for creating catalog tables and starting create/replace transactions
 */
 
+use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::SystemTime;
 
@@ -10,7 +11,6 @@ use object_store::ObjectStore;
 use uuid::Uuid;
 
 use crate::catalog::identifier::Identifier;
-use crate::catalog::relation::Relation;
 use crate::model::partition::{PartitionField, Transform};
 use crate::model::sort::{NullOrder, SortDirection, SortField, SortOrder};
 use crate::model::{partition::PartitionSpec, schema::SchemaV2, table_metadata::TableMetadataV2};
@@ -22,7 +22,11 @@ use super::{Catalog, TableType};
 ///Builder pattern to create a table
 pub struct TableBuilder {
     table_type: TableType,
-    metadata: TableMetadataV2,
+    location: String,
+    schema: SchemaV2,
+    partition_spec: PartitionSpec,
+    sort_order: SortOrder,
+    properties: HashMap<String, String>,
 }
 
 impl TableBuilder {
@@ -33,51 +37,12 @@ impl TableBuilder {
         identifier: Identifier,
         catalog: Arc<dyn Catalog>,
     ) -> Result<Self> {
-        let partition_spec = PartitionSpec {
-            spec_id: 1,
-            fields: vec![PartitionField {
-                name: "default".to_string(),
-                field_id: 1,
-                source_id: 1,
-                transform: Transform::Void,
-            }],
-        };
-        let sort_order = SortOrder {
-            order_id: 1,
-            fields: vec![SortField {
-                source_id: 1,
-                transform: Transform::Void,
-                direction: SortDirection::Descending,
-                null_order: NullOrder::Last,
-            }],
-        };
-        let metadata = TableMetadataV2 {
-            table_uuid: Uuid::new_v4(),
-            location: base_path.to_owned() + &identifier.to_string().replace(".", "/"),
-            last_sequence_number: 1,
-            last_updated_ms: SystemTime::now()
-                .duration_since(SystemTime::UNIX_EPOCH)
-                .map_err(|err| anyhow!(err.to_string()))?
-                .as_millis() as i64,
-            last_column_id: schema.struct_fields.fields.len() as i32,
-            schemas: vec![schema],
-            current_schema_id: 1,
-            partition_specs: vec![partition_spec],
-            default_spec_id: 1,
-            last_partition_id: 1,
-            properties: None,
-            current_snapshot_id: None,
-            snapshots: None,
-            snapshot_log: None,
-            metadata_log: None,
-            sort_orders: vec![sort_order],
-            default_sort_order_id: 0,
-            refs: None,
-        };
-        Ok(TableBuilder {
-            metadata,
-            table_type: TableType::Metastore(identifier, catalog),
-        })
+        let location = base_path.to_owned() + &identifier.to_string().replace(".", "/");
+        Ok(TableBuilder::new(
+            location,
+            schema,
+            TableType::Metastore(identifier, catalog),
+        ))
     }
     /// Creates a new [TableBuilder] to create a FileSystem Table with some default metadata entries already set.
     pub fn new_filesystem_table(
@@ -85,6 +50,13 @@ impl TableBuilder {
         schema: SchemaV2,
         object_store: Arc<dyn ObjectStore>,
     ) -> Result<Self> {
+        Ok(TableBuilder::new(
+            location.to_string(),
+            schema,
+            TableType::FileSystem(object_store),
+        ))
+    }
+    fn new(location: String, schema: SchemaV2, table_type: TableType) -> Self {
         let partition_spec = PartitionSpec {
             spec_id: 1,
             fields: vec![PartitionField {
@@ -103,69 +75,43 @@ impl TableBuilder {
                 null_order: NullOrder::Last,
             }],
         };
-        let metadata = TableMetadataV2 {
-            table_uuid: Uuid::new_v4(),
-            location: location.to_string(),
-            last_sequence_number: 1,
-            last_updated_ms: SystemTime::now()
-                .duration_since(SystemTime::UNIX_EPOCH)
-                .map_err(|err| anyhow!(err.to_string()))?
-                .as_millis() as i64,
-            last_column_id: schema.struct_fields.fields.len() as i32,
-            schemas: vec![schema],
-            current_schema_id: 1,
-            partition_specs: vec![partition_spec],
-            default_spec_id: 1,
-            last_partition_id: 1,
-            properties: None,
-            current_snapshot_id: None,
-            snapshots: None,
-            snapshot_log: None,
-            metadata_log: None,
-            sort_orders: vec![sort_order],
-            default_sort_order_id: 0,
-            refs: None,
-        };
-        Ok(TableBuilder {
-            metadata,
-            table_type: TableType::FileSystem(object_store),
-        })
+        TableBuilder {
+            table_type,
+            location,
+            schema,
+            partition_spec,
+            sort_order,
+            properties: HashMap::new(),
+        }
     }
     /// Building a table writes the metadata file and commits the table to either the metastore or the filesystem
     pub async fn commit(self) -> Result<Table> {
         match self.table_type {
             TableType::Metastore(identifier, catalog) => {
-                let object_store = catalog.object_store();
-                let location = &self.metadata.location;
-                let uuid = Uuid::new_v4();
-                let version = &self.metadata.last_sequence_number;
-                let metadata_json = serde_json::to_string(&self.metadata)
-                    .map_err(|err| anyhow!(err.to_string()))?;
-                let path: Path = (location.to_string()
-                    + "/metadata/"
-                    + &version.to_string()
-                    + "-"
-                    + &uuid.to_string()
-                    + ".metadata.json")
-                    .into();
-                object_store
-                    .put(&path, metadata_json.into())
+                catalog
+                    .create_table(
+                        identifier,
+                        self.schema,
+                        Some(self.partition_spec),
+                        Some(self.sort_order),
+                        Some(&self.location),
+                        self.properties,
+                    )
                     .await
-                    .map_err(|err| anyhow!(err.to_string()))?;
-                if let Relation::Table(table) =
-                    catalog.register_table(identifier, path.as_ref()).await?
-                {
-                    Ok(table)
-                } else {
-                    Err(anyhow!("Building the table failed because registering the table in the catalog didn't return a table."))
-                }
             }
             TableType::FileSystem(object_store) => {
-                let location = &self.metadata.location;
+                let metadata = new_table_metadata(
+                    &self.location,
+                    self.schema,
+                    Some(self.partition_spec),
+                    Some(self.sort_order),
+                    self.properties,
+                )?;
+                let location = &metadata.location;
                 let uuid = Uuid::new_v4();
-                let version = &self.metadata.last_sequence_number;
-                let metadata_json = serde_json::to_string(&self.metadata)
-                    .map_err(|err| anyhow!(err.to_string()))?;
+                let version = &metadata.last_sequence_number;
+                let metadata_json =
+                    serde_json::to_string(&metadata).map_err(|err| anyhow!(err.to_string()))?;
                 let temp_path: Path =
                     (location.to_string() + "/metadata/" + &uuid.to_string() + ".metadata.json")
                         .into();
@@ -193,7 +139,68 @@ impl TableBuilder {
     }
     /// Sets a partition spec for the table.
     pub fn with_partition_spec(mut self, partition_spec: PartitionSpec) -> Self {
-        self.metadata.partition_specs.push(partition_spec);
+        self.partition_spec = partition_spec;
+        self
+    }
+    /// Sets a sort order for the table.
+    pub fn with_sort_order(mut self, sort_order: SortOrder) -> Self {
+        self.sort_order = sort_order;
         self
     }
+    /// Sets a table property.
+    pub fn with_property(mut self, key: &str, value: &str) -> Self {
+        self.properties.insert(key.to_string(), value.to_string());
+        self
+    }
+}
+
+/// Create the metadata of a new table. Without a partition spec or sort order the table is unpartitioned and unsorted.
+pub(crate) fn new_table_metadata(
+    location: &str,
+    schema: SchemaV2,
+    partition_spec: Option<PartitionSpec>,
+    sort_order: Option<SortOrder>,
+    properties: HashMap<String, String>,
+) -> Result<TableMetadataV2> {
+    let partition_spec = partition_spec.unwrap_or(PartitionSpec {
+        spec_id: 0,
+        fields: vec![],
+    });
+    let sort_order = sort_order.unwrap_or(SortOrder {
+        order_id: 0,
+        fields: vec![],
+    });
+    Ok(TableMetadataV2 {
+        table_uuid: Uuid::new_v4(),
+        location: location.to_string(),
+        last_sequence_number: 1,
+        last_updated_ms: SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map_err(|err| anyhow!(err.to_string()))?
+            .as_millis() as i64,
+        last_column_id: schema.struct_fields.fields.len() as i32,
+        current_schema_id: schema.schema_id,
+        schemas: vec![schema],
+        default_spec_id: partition_spec.spec_id,
+        // Partition field ids start at 1000 for unpartitioned tables
+        last_partition_id: partition_spec
+            .fields
+            .iter()
+            .map(|field| field.field_id)
+            .max()
+            .unwrap_or(999),
+        partition_specs: vec![partition_spec],
+        properties: if properties.is_empty() {
+            None
+        } else {
+            Some(properties)
+        },
+        current_snapshot_id: None,
+        snapshots: None,
+        snapshot_log: None,
+        metadata_log: None,
+        default_sort_order_id: sort_order.order_id as i64,
+        sort_orders: vec![sort_order],
+        refs: None,
+    })
 }
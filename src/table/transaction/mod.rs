@@ -2,17 +2,40 @@
  * Defines the [Transaction] type that performs multiple [Operation]s with ACID properties.
 */
 
-use futures::StreamExt;
+use std::{collections::HashMap, str::FromStr, time::Duration};
+
 use object_store::path::Path;
 use uuid::Uuid;
 
-use crate::{catalog::relation::Relation, model::schema::SchemaV2, table::Table};
+use crate::{
+    catalog::{
+        relation::{write_metadata, Relation},
+        CommitConflict,
+    },
+    expr::UnboundExpression,
+    model::{manifest::DataFileV2, schema::SchemaV2},
+    table::{read_manifest_list, Table},
+    util,
+};
 use anyhow::{anyhow, Result};
 
-use self::operation::Operation;
+use self::operation::{DeleteFiles, Operation};
 
 mod operation;
 
+/// Table property for the number of times a commit is retried after a concurrent commit.
+pub static COMMIT_NUM_RETRIES: &str = "commit.retry.num-retries";
+/// Table property for the minimum time in milliseconds to wait before retrying a commit.
+pub static COMMIT_MIN_RETRY_WAIT_MS: &str = "commit.retry.min-wait-ms";
+/// Table property for the maximum time in milliseconds to wait before retrying a commit.
+pub static COMMIT_MAX_RETRY_WAIT_MS: &str = "commit.retry.max-wait-ms";
+/// Table property to enable merging manifests on merge appends.
+pub static MANIFEST_MERGE_ENABLED: &str = "commit.manifest-merge.enabled";
+/// Table property for the minimum number of manifests of a partition spec before they are merged.
+pub static MANIFEST_MIN_MERGE_COUNT: &str = "commit.manifest.min-count-to-merge";
+/// Table property for the target size in bytes of merged manifests.
+pub static MANIFEST_TARGET_SIZE_BYTES: &str = "commit.manifest.target-size-bytes";
+
 /// Transactions let you perform a sequence of [Operation]s that can be committed to be performed with ACID guarantees.
 pub struct TableTransaction<'table> {
     table: &'table mut Table,
@@ -37,98 +60,360 @@ impl<'table> TableTransaction<'table> {
         self.operations.push(Operation::UpdateSpec(spec_id));
         self
     }
+    /// Append data files to the table. The manifest entries of the files are written with the partition values,
+    /// metrics, format and size of the given descriptors.
+    pub fn append(mut self, files: Vec<DataFileV2>) -> Self {
+        self.operations.push(Operation::NewAppend(files));
+        self
+    }
+    /// Append data files to the table like [TableTransaction::append] and merge the manifests of the table into
+    /// fewer, larger manifests. The merging is configured with the `commit.manifest*` table properties.
+    pub fn merge_append(mut self, files: Vec<DataFileV2>) -> Self {
+        self.operations.push(Operation::MergeAppend(files));
+        self
+    }
     /// Quickly append files to the table
     pub fn fast_append(mut self, files: Vec<String>) -> Self {
         self.operations.push(Operation::NewFastAppend(files));
         self
     }
+    /// Delete the data files with the given paths from the table. Only the metadata is changed, the data files are
+    /// kept.
+    pub fn delete_files(mut self, paths: Vec<String>) -> Self {
+        self.operations
+            .push(Operation::NewDelete(DeleteFiles::Paths(paths)));
+        self
+    }
+    /// Delete the rows matching the filter by removing whole data files from the table. The commit fails if the
+    /// partition values of a file that might contain matching rows don't prove that all of its rows match.
+    pub fn delete_from_row_filter(mut self, filter: UnboundExpression) -> Self {
+        self.operations
+            .push(Operation::NewDelete(DeleteFiles::RowFilter(filter)));
+        self
+    }
     /// Commit the transaction to perform the [Operation]s with ACID guarantees.
+    ///
+    /// If another writer committed to the table in the meantime, the files written for the failed attempt are deleted,
+    /// the table is refreshed and the operations are applied again to the new state, as long as they are still valid.
+    /// Other errors are returned immediately. The number of retries and the exponential backoff between them are
+    /// configured with the `commit.retry.*` table properties.
     pub async fn commit(self) -> Result<()> {
+        let properties = self.table.metadata().properties();
+        let num_retries: u64 = table_property(properties, COMMIT_NUM_RETRIES, 4)?;
+        let min_wait_ms: u64 = table_property(properties, COMMIT_MIN_RETRY_WAIT_MS, 100)?;
+        let max_wait_ms: u64 = table_property(properties, COMMIT_MAX_RETRY_WAIT_MS, 60000)?;
+        let base = self.table.clone();
+        let mut attempt = 0;
+        loop {
+            let mut table = self.apply().await?;
+            match swap_metadata(&mut table).await {
+                Ok(()) => {
+                    *self.table = table;
+                    return Ok(());
+                }
+                Err(err) if !err.is::<CommitConflict>() => return Err(err),
+                Err(err) => {
+                    // The files of the failed attempt are unreferenced, failing to delete them doesn't affect the
+                    // retry
+                    delete_uncommitted_files(&table).await.ok();
+                    if attempt >= num_retries {
+                        return Err(err);
+                    }
+                    let wait_ms = min_wait_ms
+                        .saturating_mul(2u64.saturating_pow(attempt as u32))
+                        .min(max_wait_ms);
+                    tokio::time::sleep(Duration::from_millis(wait_ms)).await;
+                    attempt += 1;
+                    self.table.refresh().await?;
+                    for operation in &self.operations {
+                        operation.validate(&base, self.table).await?;
+                    }
+                }
+            }
+        }
+    }
+    /// Perform the operations on a copy of the table, so that a failed commit leaves the table unchanged.
+    pub(crate) async fn apply(&self) -> Result<Table> {
+        let mut table = self.table.clone();
         // Before executing the transactions operations, update the metadata for a new snapshot
-        self.table.increment_sequence_number();
-        self.table.new_snapshot().await?;
-        // Execute the table operations
-        let table = futures::stream::iter(self.operations)
-            .fold(
-                Ok::<&mut Table, anyhow::Error>(self.table),
-                |table, op| async move {
-                    let table = table?;
-                    op.execute(table).await?;
-                    Ok(table)
-                },
+        table.increment_sequence_number();
+        table.new_snapshot().await?;
+        for operation in self.operations.iter().cloned() {
+            operation.execute(&mut table).await?;
+        }
+        Ok(table)
+    }
+    /// Get the table the transaction operates on.
+    pub(crate) fn table(&self) -> &Table {
+        self.table
+    }
+    /// Replace the table with its committed state.
+    pub(crate) fn finish(self, table: Table) {
+        *self.table = table;
+    }
+}
+
+/// Read a property of the table, or the default if the property isn't set.
+pub(crate) fn table_property<T: FromStr>(
+    properties: Option<&HashMap<String, String>>,
+    key: &str,
+    default: T,
+) -> Result<T> {
+    match properties.and_then(|properties| properties.get(key)) {
+        Some(value) => value
+            .parse()
+            .map_err(|_| anyhow!("Table property {} has the invalid value {}.", key, value)),
+        None => Ok(default),
+    }
+}
+
+/// Delete the manifest list and the manifests that were written for the new snapshot of a commit that failed.
+pub(crate) async fn delete_uncommitted_files(table: &Table) -> Result<()> {
+    let metadata = table.metadata();
+    let object_store = table.object_store();
+    if let (Some(snapshot_id), Some(manifest_list)) =
+        (metadata.current_snapshot_id(), metadata.manifest_list())
+    {
+        for manifest in read_manifest_list(
+            manifest_list,
+            metadata.format_version(),
+            object_store.clone(),
+        )
+        .await?
+        {
+            if manifest.added_snapshot_id() == snapshot_id {
+                object_store
+                    .delete(&util::strip_prefix(manifest.manifest_path()).into())
+                    .await?;
+            }
+        }
+        object_store
+            .delete(&util::strip_prefix(manifest_list).into())
+            .await?;
+    }
+    Ok(())
+}
+
+/// Write the metadata of the table and atomically make it the current metadata. Fails with a [CommitConflict] if
+/// another commit changed the table in the meantime; the metadata file written for the commit is deleted in that case.
+async fn swap_metadata(table: &mut Table) -> Result<()> {
+    // Write the new state to the object store
+    match (table.catalog(), table.identifier()) {
+        // In case of a metastore table, write the metadata to object srorage and use the catalog to perform the atomic swap
+        (Some(catalog), Some(identifier)) => {
+            let metadata_file_location = write_metadata(
+                catalog.object_store(),
+                table.metadata().location(),
+                table.metadata().last_sequence_number(),
+                table.metadata(),
             )
             .await?;
-        // Write the new state to the object store
-        match (table.catalog(), table.identifier()) {
-            // In case of a metastore table, write the metadata to object srorage and use the catalog to perform the atomic swap
-            (Some(catalog), Some(identifier)) => {
-                let object_store = catalog.object_store();
-                let location = &table.metadata().location();
-                let transaction_uuid = Uuid::new_v4();
-                let version = &table.metadata().last_sequence_number();
-                let metadata_json = serde_json::to_string(&table.metadata())
-                    .map_err(|err| anyhow!(err.to_string()))?;
-                let metadata_file_location: Path = (location.to_string()
-                    + "/metadata/"
-                    + &version.to_string()
-                    + "-"
-                    + &transaction_uuid.to_string()
-                    + ".metadata.json")
-                    .into();
-                object_store
-                    .put(&metadata_file_location, metadata_json.into())
-                    .await
-                    .map_err(|err| anyhow!(err.to_string()))?;
-                let previous_metadata_file_location = table.metadata_location();
-                if let Relation::Table(new_table) = catalog
-                    .clone()
-                    .update_table(
-                        identifier.clone(),
-                        metadata_file_location.as_ref(),
-                        previous_metadata_file_location,
-                    )
-                    .await?
-                {
-                    *table = new_table;
-                    Ok(())
-                } else {
-                    Err(anyhow!(
-                        "Updating the table for the transaction didn't return a table."
-                    ))
+            let previous_metadata_file_location = table.metadata_location();
+            let relation = match catalog
+                .clone()
+                .update_table(
+                    identifier.clone(),
+                    &metadata_file_location,
+                    previous_metadata_file_location,
+                )
+                .await
+            {
+                Err(err) if err.is::<CommitConflict>() => {
+                    catalog
+                        .object_store()
+                        .delete(&util::strip_prefix(&metadata_file_location).into())
+                        .await
+                        .ok();
+                    return Err(err);
                 }
-            }
-            // In case of a filesystem table, write the metadata to the object storage and perform the atomic swap of the metadata file
-            (_, _) => {
-                let object_store = table.object_store();
-                let location = &table.metadata().location();
-                let uuid = Uuid::new_v4();
-                let version = &table.metadata().last_sequence_number();
-                let metadata_json = serde_json::to_string(&table.metadata())
-                    .map_err(|err| anyhow!(err.to_string()))?;
-                let temp_path: Path =
-                    (location.to_string() + "/metadata/" + &uuid.to_string() + ".metadata.json")
-                        .into();
-                let final_path: Path = (location.to_string()
-                    + "/metadata/v"
-                    + &version.to_string()
-                    + ".metadata.json")
-                    .into();
-                object_store
-                    .put(&temp_path, metadata_json.into())
-                    .await
-                    .map_err(|err| anyhow!(err.to_string()))?;
-                object_store
-                    .copy_if_not_exists(&temp_path, &final_path)
-                    .await
-                    .map_err(|err| anyhow!(err.to_string()))?;
-                object_store
-                    .delete(&temp_path)
-                    .await
-                    .map_err(|err| anyhow!(err.to_string()))?;
-                let new_table = Table::load_file_system_table(location, &object_store).await?;
+                relation => relation?,
+            };
+            if let Relation::Table(new_table) = relation {
                 *table = new_table;
                 Ok(())
+            } else {
+                Err(anyhow!(
+                    "Updating the table for the transaction didn't return a table."
+                ))
             }
         }
+        // In case of a filesystem table, write the metadata to the object storage and perform the atomic swap of the metadata file
+        (_, _) => {
+            let object_store = table.object_store();
+            let location = &table.metadata().location();
+            let uuid = Uuid::new_v4();
+            let version = &table.metadata().last_sequence_number();
+            let metadata_json =
+                serde_json::to_string(&table.metadata()).map_err(|err| anyhow!(err.to_string()))?;
+            let temp_path: Path =
+                (location.to_string() + "/metadata/" + &uuid.to_string() + ".metadata.json").into();
+            let final_path: Path =
+                (location.to_string() + "/metadata/v" + &version.to_string() + ".metadata.json")
+                    .into();
+            object_store
+                .put(&temp_path, metadata_json.into())
+                .await
+                .map_err(|err| anyhow!(err.to_string()))?;
+            // The temporary file is removed in any case, because it would be mistaken for a metadata version
+            let copied = object_store
+                .copy_if_not_exists(&temp_path, &final_path)
+                .await
+                .map_err(|err| match err {
+                    object_store::Error::AlreadyExists { .. } => CommitConflict(format!(
+                        "Failed to commit version {} of table {}: it was created by a concurrent commit.",
+                        version, location
+                    ))
+                    .into(),
+                    err => anyhow!(err.to_string()),
+                });
+            object_store.delete(&temp_path).await.ok();
+            copied?;
+            let new_table = Table::load_file_system_table(location, &object_store).await?;
+            *table = new_table;
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::sync::Arc;
+
+    use futures::StreamExt;
+    use object_store::{memory::InMemory, ObjectStore};
+
+    use crate::{
+        catalog::{identifier::Identifier, memory::MemoryCatalog, relation::Relation, Catalog},
+        table::{
+            table_builder::TableBuilder,
+            transaction::{COMMIT_MIN_RETRY_WAIT_MS, COMMIT_NUM_RETRIES},
+            Table,
+        },
+    };
+
+    use crate::catalog::tests::{schema, UndeletableStore};
+
+    async fn load(catalog: &Arc<dyn Catalog>, identifier: &Identifier) -> Table {
+        match catalog.clone().load_table(identifier).await.unwrap() {
+            Relation::Table(table) => table,
+            Relation::View(_) => panic!("Relation is not a table."),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_appends() {
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let catalog: Arc<dyn Catalog> = Arc::new(MemoryCatalog::new(Arc::clone(&object_store)));
+        let identifier = Identifier::parse("retry.table1").unwrap();
+        TableBuilder::new_metastore_table(
+            "test/",
+            schema(),
+            identifier.clone(),
+            Arc::clone(&catalog),
+        )
+        .unwrap()
+        .with_property(COMMIT_MIN_RETRY_WAIT_MS, "1")
+        .commit()
+        .await
+        .unwrap();
+        let mut first = load(&catalog, &identifier).await;
+        let mut second = load(&catalog, &identifier).await;
+
+        first
+            .new_transaction()
+            .fast_append(vec!["test/retry/table1/data/file1.parquet".to_string()])
+            .commit()
+            .await
+            .unwrap();
+        // The second writer is based on an outdated state and has to re-apply its append to the new state
+        second
+            .new_transaction()
+            .fast_append(vec!["test/retry/table1/data/file2.parquet".to_string()])
+            .commit()
+            .await
+            .unwrap();
+        assert_eq!(second.manifests().len(), 2);
+        assert_eq!(
+            load(&catalog, &identifier).await.metadata_location(),
+            second.metadata_location()
+        );
+        // The metadata file, manifest list and manifest of the failed attempt were deleted
+        let files: Vec<String> = object_store
+            .list(None)
+            .await
+            .unwrap()
+            .map(|meta| meta.unwrap().location.to_string())
+            .collect()
+            .await;
+        let count = |suffix: &str| files.iter().filter(|file| file.ends_with(suffix)).count();
+        assert_eq!(count(".metadata.json"), 3);
+        assert_eq!(count("-m0.avro"), 2);
+        assert_eq!(count(".avro"), 4);
+    }
+
+    #[tokio::test]
+    async fn test_failed_cleanup() {
+        let object_store: Arc<dyn ObjectStore> = Arc::new(UndeletableStore(InMemory::new()));
+        let catalog: Arc<dyn Catalog> = Arc::new(MemoryCatalog::new(object_store));
+        let identifier = Identifier::parse("retry.table1").unwrap();
+        TableBuilder::new_metastore_table(
+            "test/",
+            schema(),
+            identifier.clone(),
+            Arc::clone(&catalog),
+        )
+        .unwrap()
+        .with_property(COMMIT_MIN_RETRY_WAIT_MS, "1")
+        .commit()
+        .await
+        .unwrap();
+        let mut first = load(&catalog, &identifier).await;
+        let mut second = load(&catalog, &identifier).await;
+
+        first
+            .new_transaction()
+            .fast_append(vec!["test/retry/table1/data/file1.parquet".to_string()])
+            .commit()
+            .await
+            .unwrap();
+        // The files of the failed attempt can't be deleted, which doesn't prevent the retry
+        second
+            .new_transaction()
+            .fast_append(vec!["test/retry/table1/data/file2.parquet".to_string()])
+            .commit()
+            .await
+            .unwrap();
+        assert_eq!(second.manifests().len(), 2);
+        assert_eq!(
+            load(&catalog, &identifier).await.metadata_location(),
+            second.metadata_location()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_no_retries() {
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let catalog: Arc<dyn Catalog> = Arc::new(MemoryCatalog::new(object_store));
+        let identifier = Identifier::parse("retry.table1").unwrap();
+        TableBuilder::new_metastore_table(
+            "test/",
+            schema(),
+            identifier.clone(),
+            Arc::clone(&catalog),
+        )
+        .unwrap()
+        .with_property(COMMIT_NUM_RETRIES, "0")
+        .commit()
+        .await
+        .unwrap();
+        let mut first = load(&catalog, &identifier).await;
+        let mut second = load(&catalog, &identifier).await;
+        let previous_metadata_location = second.metadata_location().to_string();
+
+        first.new_transaction().commit().await.unwrap();
+        assert!(second.new_transaction().commit().await.is_err());
+        // A failed commit leaves the table unchanged
+        assert_eq!(second.metadata_location(), previous_metadata_location);
     }
 }
@@ -2,23 +2,39 @@
  * Defines the different [Operation]s on a [Table].
 */
 
+use std::{collections::HashSet, iter};
+
 use anyhow::{anyhow, Result};
 use object_store::path::Path;
+use serde_bytes::ByteBuf;
 
 use crate::{
+    expr::{
+        manifest_evaluator::ManifestEvaluator, metrics_evaluator::MetricsEvaluator,
+        residual::ResidualEvaluator, Expression, UnboundExpression,
+    },
     model::{
+        bytes::value_to_bytes,
         manifest::{
-            Content, DataFileV1, DataFileV2, FileFormat, ManifestEntry, ManifestEntryV1,
-            ManifestEntryV2, PartitionValues, Status,
+            Content, DataFileV2, FileFormat, ManifestEntry, ManifestEntryV1, ManifestEntryV2,
+            PartitionValues, Status,
         },
         manifest_list::{FieldSummary, ManifestFile, ManifestFileV1, ManifestFileV2},
-        schema::SchemaV2,
+        partition::PartitionField,
+        schema::{AllType, PrimitiveType, SchemaStruct, SchemaV2},
+        snapshot::Operation as SnapshotOperation,
         table_metadata::TableMetadata,
+        types::Value,
     },
-    table::Table,
+    table::{files::read_manifest, read_manifest_list, Table},
+};
+
+use super::{
+    table_property, MANIFEST_MERGE_ENABLED, MANIFEST_MIN_MERGE_COUNT, MANIFEST_TARGET_SIZE_BYTES,
 };
 
 ///Table operations
+#[derive(Clone)]
 pub enum Operation {
     /// Update schema
     UpdateSchema(SchemaV2),
@@ -30,8 +46,10 @@ pub enum Operation {
     // ReplaceSortOrder,
     // /// Update the table location
     // UpdateLocation,
-    // /// Append new files to the table
-    // NewAppend,
+    /// Append new data files to the table
+    NewAppend(Vec<DataFileV2>),
+    /// Append new data files to the table and merge its manifests
+    MergeAppend(Vec<DataFileV2>),
     /// Quickly append new files to the table
     NewFastAppend(Vec<String>),
     // /// Replace files in the table and commit
@@ -42,8 +60,8 @@ pub enum Operation {
     // NewOverwrite,
     // /// Remove or replace rows in existing data files
     // NewRowDelta,
-    // /// Delete files in the table and commit
-    // NewDelete,
+    /// Delete files in the table and commit
+    NewDelete(DeleteFiles),
     // /// Expire snapshots in the table
     // ExpireSnapshots,
     // /// Manage snapshots in the table
@@ -52,225 +70,691 @@ pub enum Operation {
     // IO,
 }
 
+/// Selects the data files that are removed by a delete
+#[derive(Clone)]
+pub enum DeleteFiles {
+    /// The data files with the given paths
+    Paths(Vec<String>),
+    /// The data files whose partition values prove that all of their rows match the filter
+    RowFilter(UnboundExpression),
+}
+
 impl Operation {
+    /// Check that the operation can still be applied after the table was changed by a concurrent commit.
+    /// The base is the state of the table when the operation was created.
+    pub async fn validate(&self, base: &Table, table: &Table) -> Result<()> {
+        let metadata = table.metadata();
+        match self {
+            Operation::UpdateSchema(_) if metadata.current_schema() != base.schema() => {
+                Err(anyhow!(
+                    "Failed to update the schema: the schema was changed by a concurrent commit."
+                ))
+            }
+            Operation::UpdateSpec(spec_id) if metadata.get_spec(*spec_id).is_none() => {
+                Err(anyhow!(
+                    "Failed to update the partition spec: the spec {} doesn't exist in the table.",
+                    spec_id
+                ))
+            }
+            // The partition spec of the table might have been changed by a concurrent commit
+            Operation::NewAppend(files) | Operation::MergeAppend(files) => {
+                validate_data_files(files, metadata.default_spec())
+            }
+            // The files to delete might have been removed by a concurrent commit
+            Operation::NewDelete(DeleteFiles::Paths(paths)) => {
+                let base_files = live_data_files(base).await?;
+                let files = live_data_files(table).await?;
+                match paths
+                    .iter()
+                    .find(|path| base_files.contains(*path) && !files.contains(*path))
+                {
+                    Some(path) => Err(anyhow!(
+                        "Failed to delete {}: the file was removed by a concurrent commit.",
+                        path
+                    )),
+                    None => Ok(()),
+                }
+            }
+            // Other operations don't conflict with concurrent commits
+            _ => Ok(()),
+        }
+    }
     pub async fn execute(self, table: &mut Table) -> Result<()> {
         match self {
+            Operation::NewAppend(files) => {
+                validate_data_files(&files, table.metadata().default_spec())?;
+                append_files(table, files, false).await
+            }
+            Operation::MergeAppend(files) => {
+                validate_data_files(&files, table.metadata().default_spec())?;
+                append_files(table, files, true).await
+            }
             Operation::NewFastAppend(paths) => {
-                let object_store = table.object_store();
-                let table_metadata = table.metadata();
-                let manifest_bytes = match table_metadata {
-                    TableMetadata::V1(metadata) => {
-                        let manifest_schema =
-                            apache_avro::Schema::parse_str(&ManifestEntry::schema(
-                                &PartitionValues::schema(
-                                    table_metadata.default_spec(),
-                                    table_metadata.current_schema(),
-                                )?,
-                                &table_metadata.format_version(),
-                            ))?;
-                        let mut manifest_writer =
-                            apache_avro::Writer::new(&manifest_schema, Vec::new());
-                        for path in paths {
-                            let manifest_entry = ManifestEntryV1 {
-                                status: Status::Added,
-                                snapshot_id: metadata.current_snapshot_id.unwrap_or(1),
-                                data_file: DataFileV1 {
-                                    file_path: path,
-                                    file_format: FileFormat::Parquet,
-                                    partition: PartitionValues::from_iter(
-                                        table_metadata
-                                            .default_spec()
-                                            .iter()
-                                            .map(|field| (field.name.to_owned(), None)),
-                                    ),
-                                    record_count: 4,
-                                    file_size_in_bytes: 1200,
-                                    block_size_in_bytes: 400,
-                                    file_ordinal: None,
-                                    sort_columns: None,
-                                    column_sizes: None,
-                                    value_counts: None,
-                                    null_value_counts: None,
-                                    nan_value_counts: None,
-                                    distinct_counts: None,
-                                    lower_bounds: None,
-                                    upper_bounds: None,
-                                    key_metadata: None,
-                                    split_offsets: None,
-                                    sort_order_id: None,
-                                },
-                            };
-                            manifest_writer.append_ser(manifest_entry)?;
-                        }
-                        manifest_writer.into_inner()?
-                    }
-                    TableMetadata::V2(metadata) => {
-                        let manifest_schema =
-                            apache_avro::Schema::parse_str(&ManifestEntry::schema(
-                                &PartitionValues::schema(
-                                    table_metadata.default_spec(),
-                                    table_metadata.current_schema(),
-                                )?,
-                                &table_metadata.format_version(),
-                            ))?;
-                        let mut manifest_writer =
-                            apache_avro::Writer::new(&manifest_schema, Vec::new());
-                        for path in paths {
-                            let manifest_entry = ManifestEntry::V2(ManifestEntryV2 {
-                                status: Status::Added,
-                                snapshot_id: metadata.current_snapshot_id,
-                                sequence_number: metadata
-                                    .snapshots
-                                    .as_ref()
-                                    .map(|snapshots| snapshots.last().unwrap().sequence_number),
-                                data_file: DataFileV2 {
-                                    content: Content::Data,
-                                    file_path: path,
-                                    file_format: FileFormat::Parquet,
-                                    partition: PartitionValues::from_iter(
-                                        table_metadata
-                                            .default_spec()
-                                            .iter()
-                                            .map(|field| (field.name.to_owned(), None)),
-                                    ),
-                                    record_count: 4,
-                                    file_size_in_bytes: 1200,
-                                    column_sizes: None,
-                                    value_counts: None,
-                                    null_value_counts: None,
-                                    nan_value_counts: None,
-                                    distinct_counts: None,
-                                    lower_bounds: None,
-                                    upper_bounds: None,
-                                    key_metadata: None,
-                                    split_offsets: None,
-                                    equality_ids: None,
-                                    sort_order_id: None,
-                                },
-                            });
-                            manifest_writer.append_ser(manifest_entry)?;
-                        }
-                        manifest_writer.into_inner()?
+                let partition = PartitionValues::from_iter(
+                    table
+                        .metadata()
+                        .default_spec()
+                        .iter()
+                        .map(|field| (field.name.to_owned(), None)),
+                );
+                let files = paths
+                    .into_iter()
+                    .map(|path| DataFileV2 {
+                        content: Content::Data,
+                        file_path: path,
+                        file_format: FileFormat::Parquet,
+                        partition: partition.clone(),
+                        record_count: 4,
+                        file_size_in_bytes: 1200,
+                        column_sizes: None,
+                        value_counts: None,
+                        null_value_counts: None,
+                        nan_value_counts: None,
+                        distinct_counts: None,
+                        lower_bounds: None,
+                        upper_bounds: None,
+                        key_metadata: None,
+                        split_offsets: None,
+                        equality_ids: None,
+                        sort_order_id: None,
+                    })
+                    .collect();
+                append_files(table, files, false).await
+            }
+            Operation::NewDelete(files) => delete_files(table, files).await,
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Check that the files are data files whose partition values match the partition spec.
+fn validate_data_files(files: &[DataFileV2], spec: &[PartitionField]) -> Result<()> {
+    for file in files {
+        if file.content != Content::Data {
+            return Err(anyhow!(
+                "Failed to append {}: only data files can be appended.",
+                file.file_path
+            ));
+        }
+        if file.partition.names()
+            != spec
+                .iter()
+                .map(|field| field.name.as_str())
+                .collect::<Vec<_>>()
+        {
+            return Err(anyhow!(
+                "Failed to append {}: the partition values don't match the partition spec of the table.",
+                file.file_path
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Write the data files to a new manifest of the current snapshot. If merge is set, the manifests of the table are
+/// merged afterwards.
+async fn append_files(table: &mut Table, files: Vec<DataFileV2>, merge: bool) -> Result<()> {
+    table.set_snapshot_operation(SnapshotOperation::Append);
+    let table_metadata = table.metadata();
+    let entries: Vec<ManifestEntry> = files
+        .into_iter()
+        .map(|file| manifest_entry(table_metadata, Status::Added, file))
+        .collect();
+    let manifest_list_location = table_metadata
+        .manifest_list()
+        .ok_or_else(|| anyhow!("No manifest list in table metadata."))?;
+    let manifest = write_manifest(
+        table,
+        &new_manifest_location(table_metadata),
+        table_metadata.default_spec_id(),
+        &entries,
+    )
+    .await?;
+    let mut manifests = read_manifest_list(
+        manifest_list_location,
+        table_metadata.format_version(),
+        table.object_store(),
+    )
+    .await?;
+    if merge {
+        manifests = merge_manifests(table, manifest, manifests).await?;
+    } else {
+        manifests.push(manifest);
+    }
+    write_manifest_list(table, &manifests).await
+}
+
+/// Merge the new manifest with the existing manifests of the table. The manifests are grouped by partition spec and
+/// content and packed in order into bins of the target size. Every bin with more than one manifest is rewritten to a
+/// single manifest, except for the bin of the new manifest as long as it has fewer manifests than the minimum count
+/// to merge.
+async fn merge_manifests(
+    table: &Table,
+    new_manifest: ManifestFile,
+    existing_manifests: Vec<ManifestFile>,
+) -> Result<Vec<ManifestFile>> {
+    let properties = table.metadata().properties();
+    if !table_property(properties, MANIFEST_MERGE_ENABLED, true)? {
+        return Ok(existing_manifests
+            .into_iter()
+            .chain(iter::once(new_manifest))
+            .collect());
+    }
+    let min_count: usize = table_property(properties, MANIFEST_MIN_MERGE_COUNT, 100)?;
+    let target_size: i64 = table_property(properties, MANIFEST_TARGET_SIZE_BYTES, 8 * 1024 * 1024)?;
+    let new_manifest_path = new_manifest.manifest_path().to_owned();
+
+    let mut groups: Vec<Vec<ManifestFile>> = Vec::new();
+    for manifest in iter::once(new_manifest).chain(existing_manifests) {
+        match groups.iter_mut().find(|group| {
+            group[0].partition_spec_id() == manifest.partition_spec_id()
+                && group[0].content() == manifest.content()
+        }) {
+            Some(group) => group.push(manifest),
+            None => groups.push(vec![manifest]),
+        }
+    }
+
+    let mut manifests = Vec::new();
+    let mut new_manifest_merged = false;
+    for group in groups {
+        for bin in pack_manifests(group, target_size) {
+            let contains_new_manifest = bin
+                .iter()
+                .any(|manifest| manifest.manifest_path() == new_manifest_path);
+            if bin.len() == 1 || (contains_new_manifest && bin.len() < min_count) {
+                manifests.extend(bin);
+            } else {
+                let location = new_manifest_location(table.metadata());
+                manifests.push(merge_bin(table, &location, &bin).await?);
+                new_manifest_merged |= contains_new_manifest;
+            }
+        }
+    }
+    // The entries of the new manifest were rewritten to a merged manifest
+    if new_manifest_merged {
+        table
+            .object_store()
+            .delete(&new_manifest_path.as_str().into())
+            .await?;
+    }
+    Ok(manifests)
+}
+
+/// Pack the manifests in order into bins whose total length doesn't exceed the target size. Manifests that are larger
+/// than the target size end up in a bin of their own.
+fn pack_manifests(manifests: Vec<ManifestFile>, target_size: i64) -> Vec<Vec<ManifestFile>> {
+    let mut bins: Vec<Vec<ManifestFile>> = Vec::new();
+    let mut bin_size = 0;
+    for manifest in manifests {
+        let length = manifest.manifest_length();
+        match bins.last_mut() {
+            Some(bin) if bin_size + length <= target_size => {
+                bin_size += length;
+                bin.push(manifest);
+            }
+            _ => {
+                bin_size = length;
+                bins.push(vec![manifest]);
+            }
+        }
+    }
+    bins
+}
+
+/// Rewrite the entries of the manifests to a single manifest at the location.
+async fn merge_bin(table: &Table, location: &Path, bin: &[ManifestFile]) -> Result<ManifestFile> {
+    let table_metadata = table.metadata();
+    let snapshot_id = table_metadata
+        .current_snapshot_id()
+        .ok_or_else(|| anyhow!("No current snapshot in table metadata."))?;
+    let mut entries = Vec::new();
+    for manifest in bin {
+        for entry in read_manifest(
+            table.object_store(),
+            manifest,
+            table_metadata.format_version(),
+        )
+        .await?
+        {
+            entries.extend(carry_over(entry, manifest, snapshot_id));
+        }
+    }
+    write_manifest(table, location, bin[0].partition_spec_id(), &entries).await
+}
+
+/// Carry an entry of a merged manifest over to the merged manifest. Entries of the current snapshot are kept as they
+/// are, the live entries of earlier snapshots become existing entries and their deletes are dropped. The inherited
+/// snapshot id and sequence number are written explicitly, because the merged manifest is added by the current
+/// snapshot.
+fn carry_over(
+    entry: ManifestEntry,
+    manifest: &ManifestFile,
+    snapshot_id: i64,
+) -> Option<ManifestEntry> {
+    let added_snapshot_id = match &entry {
+        ManifestEntry::V1(entry) => entry.snapshot_id,
+        ManifestEntry::V2(entry) => entry
+            .snapshot_id
+            .unwrap_or_else(|| manifest.added_snapshot_id()),
+    };
+    if added_snapshot_id == snapshot_id {
+        return Some(entry);
+    }
+    if *entry.status() == Status::Deleted {
+        return None;
+    }
+    Some(match entry {
+        ManifestEntry::V1(entry) => ManifestEntry::V1(ManifestEntryV1 {
+            status: Status::Existing,
+            ..entry
+        }),
+        ManifestEntry::V2(entry) => ManifestEntry::V2(ManifestEntryV2 {
+            status: Status::Existing,
+            snapshot_id: Some(added_snapshot_id),
+            sequence_number: entry
+                .sequence_number
+                .or_else(|| Some(manifest.sequence_number())),
+            data_file: entry.data_file,
+        }),
+    })
+}
+
+/// Remove the selected data files from the current snapshot without rewriting any data. The manifests that contain
+/// removed files are rewritten with deleted entries for them, their other entries are carried over.
+async fn delete_files(table: &mut Table, files: DeleteFiles) -> Result<()> {
+    table.set_snapshot_operation(SnapshotOperation::Delete);
+    let table_metadata = table.metadata();
+    let snapshot_id = table_metadata
+        .current_snapshot_id()
+        .ok_or_else(|| anyhow!("No current snapshot in table metadata."))?;
+    let manifest_list_location = table_metadata
+        .manifest_list()
+        .ok_or_else(|| anyhow!("No manifest list in table metadata."))?;
+    let (mut missing_paths, filter) = match files {
+        DeleteFiles::Paths(paths) => (paths.into_iter().collect::<HashSet<_>>(), None),
+        DeleteFiles::RowFilter(filter) => (
+            HashSet::new(),
+            Some(filter.bind(table_metadata.current_schema())?),
+        ),
+    };
+    let metrics_evaluator = filter.as_ref().map(MetricsEvaluator::new);
+
+    let mut manifests = Vec::new();
+    for manifest in read_manifest_list(
+        manifest_list_location,
+        table_metadata.format_version(),
+        table.object_store(),
+    )
+    .await?
+    {
+        if manifest.content() != Content::Data {
+            manifests.push(manifest);
+            continue;
+        }
+        let spec_id = manifest.partition_spec_id();
+        let spec = table_metadata.get_spec(spec_id).ok_or_else(|| {
+            anyhow!(
+                "Partition spec {} doesn't exist in the table metadata.",
+                spec_id
+            )
+        })?;
+        let residual_evaluator = match &filter {
+            Some(filter) if !ManifestEvaluator::new(filter, spec)?.eval(&manifest)? => {
+                manifests.push(manifest);
+                continue;
+            }
+            Some(filter) => Some(ResidualEvaluator::new(filter, spec)),
+            None => None,
+        };
+
+        let mut entries = Vec::new();
+        let mut deleted = false;
+        for entry in read_manifest(
+            table.object_store(),
+            &manifest,
+            table_metadata.format_version(),
+        )
+        .await?
+        {
+            let delete = *entry.status() != Status::Deleted
+                && match (&residual_evaluator, &metrics_evaluator) {
+                    (Some(residual_evaluator), Some(metrics_evaluator)) => {
+                        matches_all_rows(&entry, residual_evaluator, metrics_evaluator)?
                     }
+                    _ => missing_paths.remove(entry.file_path()),
                 };
-                let manifest_list_location: Path = table_metadata
-                    .manifest_list()
-                    .ok_or_else(|| anyhow!("No manifest list in table metadata."))?
-                    .into();
-                let manifest_location: Path = (manifest_list_location
-                    .to_string()
-                    .trim_end_matches(".avro")
-                    .to_owned()
-                    + "-m0.avro")
-                    .into();
-                object_store
-                    .put(&manifest_location, manifest_bytes.into())
-                    .await?;
-                let manifest_list_bytes = match table_metadata {
-                    TableMetadata::V1(_) => {
-                        let manifest_list_schema = apache_avro::Schema::parse_str(
-                            &ManifestFile::schema(&table_metadata.format_version()),
-                        )?;
-                        let mut manifest_list_writer =
-                            apache_avro::Writer::new(&manifest_list_schema, Vec::new());
-                        let bytes: Vec<u8> = object_store
-                            .get(&manifest_list_location)
-                            .await?
-                            .bytes()
-                            .await?
-                            .into();
-                        if !bytes.is_empty() {
-                            let reader = apache_avro::Reader::new(&*bytes)?;
-                            manifest_list_writer.extend(reader.filter_map(Result::ok))?;
-                        }
-                        let manifest_file = ManifestFile::V1(ManifestFileV1 {
-                            manifest_path: manifest_location.to_string(),
-                            manifest_length: 1200,
-                            partition_spec_id: 0,
-                            added_snapshot_id: 39487483032,
-                            added_files_count: Some(1),
-                            existing_files_count: Some(2),
-                            deleted_files_count: Some(0),
-                            added_rows_count: Some(1000),
-                            existing_rows_count: Some(8000),
-                            deleted_rows_count: Some(0),
-                            partitions: Some(vec![FieldSummary {
-                                contains_null: true,
-                                contains_nan: Some(false),
-                                lower_bound: None,
-                                upper_bound: None,
-                            }]),
-                            key_metadata: None,
-                        });
-                        manifest_list_writer.append_ser(manifest_file)?;
-
-                        manifest_list_writer.into_inner()?
+            if delete {
+                deleted = true;
+                entries.push(deleted_entry(entry, &manifest, snapshot_id));
+            } else {
+                entries.extend(carry_over(entry, &manifest, snapshot_id));
+            }
+        }
+        if deleted {
+            manifests.push(
+                write_manifest(
+                    table,
+                    &new_manifest_location(table_metadata),
+                    spec_id,
+                    &entries,
+                )
+                .await?,
+            );
+        } else {
+            manifests.push(manifest);
+        }
+    }
+    if !missing_paths.is_empty() {
+        return Err(anyhow!(
+            "Failed to delete the files {:?}: they are not data files of the table.",
+            missing_paths
+        ));
+    }
+    write_manifest_list(table, &manifests).await
+}
+
+/// Get the paths of the data files in the current snapshot of the table.
+async fn live_data_files(table: &Table) -> Result<HashSet<String>> {
+    let table_metadata = table.metadata();
+    let mut files = HashSet::new();
+    for manifest in table.manifests() {
+        if manifest.content() != Content::Data {
+            continue;
+        }
+        for entry in read_manifest(
+            table.object_store(),
+            manifest,
+            table_metadata.format_version(),
+        )
+        .await?
+        {
+            if *entry.status() != Status::Deleted {
+                files.insert(entry.file_path().to_string());
+            }
+        }
+    }
+    Ok(files)
+}
+
+/// Check if all rows of the data file match the filter of the evaluators. Fails if the partition values of the file
+/// can't prove it, but the file might contain matching rows, as deleting these rows would require a rewrite.
+fn matches_all_rows(
+    entry: &ManifestEntry,
+    residual_evaluator: &ResidualEvaluator,
+    metrics_evaluator: &MetricsEvaluator,
+) -> Result<bool> {
+    match residual_evaluator.residual(entry.partition_values())? {
+        Expression::True => Ok(true),
+        Expression::False => Ok(false),
+        _ if !metrics_evaluator.eval(entry)? => Ok(false),
+        _ => Err(anyhow!(
+            "Failed to delete {}: the filter might only match some of its rows.",
+            entry.file_path()
+        )),
+    }
+}
+
+/// Mark an entry of a rewritten manifest as deleted by the current snapshot. The data sequence number of the file is
+/// kept.
+fn deleted_entry(entry: ManifestEntry, manifest: &ManifestFile, snapshot_id: i64) -> ManifestEntry {
+    match entry {
+        ManifestEntry::V1(entry) => ManifestEntry::V1(ManifestEntryV1 {
+            status: Status::Deleted,
+            snapshot_id,
+            ..entry
+        }),
+        ManifestEntry::V2(entry) => ManifestEntry::V2(ManifestEntryV2 {
+            status: Status::Deleted,
+            snapshot_id: Some(snapshot_id),
+            sequence_number: entry
+                .sequence_number
+                .or_else(|| Some(manifest.sequence_number())),
+            data_file: entry.data_file,
+        }),
+    }
+}
+
+/// Create a manifest entry of the current snapshot for the data file.
+fn manifest_entry(
+    metadata: &TableMetadata,
+    status: Status,
+    data_file: DataFileV2,
+) -> ManifestEntry {
+    match metadata {
+        TableMetadata::V1(table_metadata) => ManifestEntry::V1(ManifestEntryV1 {
+            status,
+            snapshot_id: table_metadata.current_snapshot_id.unwrap_or(1),
+            data_file: data_file.into(),
+        }),
+        TableMetadata::V2(table_metadata) => ManifestEntry::V2(ManifestEntryV2 {
+            status,
+            snapshot_id: table_metadata.current_snapshot_id,
+            sequence_number: Some(sequence_number(metadata)),
+            data_file,
+        }),
+    }
+}
+
+/// Create a unique location for a new manifest in the metadata folder of the table.
+fn new_manifest_location(metadata: &TableMetadata) -> Path {
+    (metadata.location().to_owned() + "/metadata/" + &uuid::Uuid::new_v4().to_string() + "-m0.avro")
+        .into()
+}
+
+/// Get the sequence number of the current snapshot, which is 0 for version 1 tables.
+fn sequence_number(metadata: &TableMetadata) -> i64 {
+    metadata
+        .current_snapshot_id()
+        .and_then(|snapshot_id| metadata.snapshot(snapshot_id))
+        .map(|snapshot| snapshot.sequence_number)
+        .unwrap_or_default()
+}
+
+/// Write the entries to a manifest file at the location. Returns the description of the written manifest for the
+/// manifest list, with its length, file and row counts and partition summaries.
+async fn write_manifest(
+    table: &Table,
+    location: &Path,
+    spec_id: i32,
+    entries: &[ManifestEntry],
+) -> Result<ManifestFile> {
+    let table_metadata = table.metadata();
+    let spec = table_metadata.get_spec(spec_id).ok_or_else(|| {
+        anyhow!(
+            "Partition spec {} doesn't exist in the table metadata.",
+            spec_id
+        )
+    })?;
+    let manifest_schema = apache_avro::Schema::parse_str(&ManifestEntry::schema(
+        &PartitionValues::schema(spec, table_metadata.current_schema())?,
+        &table_metadata.format_version(),
+    ))?;
+    let mut manifest_writer = apache_avro::Writer::new(&manifest_schema, Vec::new());
+    for entry in entries {
+        manifest_writer.append_ser(entry)?;
+    }
+    let manifest_bytes = manifest_writer.into_inner()?;
+    let manifest_length = manifest_bytes.len() as i64;
+    table
+        .object_store()
+        .put(location, manifest_bytes.into())
+        .await?;
+
+    let count = |status: Status| {
+        let entries = entries.iter().filter(|entry| *entry.status() == status);
+        (
+            entries.clone().count() as i32,
+            entries.map(|entry| entry.record_count()).sum::<i64>(),
+        )
+    };
+    let (added_files_count, added_rows_count) = count(Status::Added);
+    let (existing_files_count, existing_rows_count) = count(Status::Existing);
+    let (deleted_files_count, deleted_rows_count) = count(Status::Deleted);
+    let partitions = Some(partition_summaries(
+        entries,
+        spec,
+        table_metadata.current_schema(),
+    )?);
+    let snapshot_id = table_metadata
+        .current_snapshot_id()
+        .ok_or_else(|| anyhow!("No current snapshot in table metadata."))?;
+    Ok(match table_metadata {
+        TableMetadata::V1(_) => ManifestFile::V1(ManifestFileV1 {
+            manifest_path: location.to_string(),
+            manifest_length,
+            partition_spec_id: spec_id,
+            added_snapshot_id: snapshot_id,
+            added_files_count: Some(added_files_count),
+            existing_files_count: Some(existing_files_count),
+            deleted_files_count: Some(deleted_files_count),
+            added_rows_count: Some(added_rows_count),
+            existing_rows_count: Some(existing_rows_count),
+            deleted_rows_count: Some(deleted_rows_count),
+            partitions,
+            key_metadata: None,
+        }),
+        TableMetadata::V2(_) => {
+            let sequence_number = sequence_number(table_metadata);
+            ManifestFile::V2(ManifestFileV2 {
+                manifest_path: location.to_string(),
+                manifest_length,
+                partition_spec_id: spec_id,
+                // A manifest contains either data files or delete files of both kinds
+                content: match entries.first().map(|entry| entry.content()) {
+                    Some(Content::PositionDeletes | Content::EqualityDeletes) => {
+                        Content::PositionDeletes
                     }
-                    TableMetadata::V2(_) => {
-                        let manifest_list_schema = apache_avro::Schema::parse_str(
-                            &ManifestFile::schema(&table_metadata.format_version()),
-                        )?;
-                        let mut manifest_list_writer =
-                            apache_avro::Writer::new(&manifest_list_schema, Vec::new());
-                        let bytes: Vec<u8> = object_store
-                            .get(&manifest_list_location)
-                            .await?
-                            .bytes()
-                            .await?
-                            .into();
-                        if !bytes.is_empty() {
-                            let reader = apache_avro::Reader::new(&*bytes)?;
-                            manifest_list_writer.extend(reader.filter_map(Result::ok))?;
+                    _ => Content::Data,
+                },
+                sequence_number,
+                min_sequence_number: entries
+                    .iter()
+                    .map(|entry| entry.sequence_number().unwrap_or(sequence_number))
+                    .min()
+                    .unwrap_or(sequence_number),
+                added_snapshot_id: snapshot_id,
+                added_files_count,
+                existing_files_count,
+                deleted_files_count,
+                added_rows_count,
+                existing_rows_count,
+                deleted_rows_count,
+                partitions,
+                key_metadata: None,
+            })
+        }
+    })
+}
+
+/// Summarize the partition values of the entries for every field of the partition spec. The bounds only consider
+/// values that are neither null nor NaN.
+fn partition_summaries(
+    entries: &[ManifestEntry],
+    spec: &[PartitionField],
+    schema: &SchemaStruct,
+) -> Result<Vec<FieldSummary>> {
+    spec.iter()
+        .enumerate()
+        .map(|(index, field)| {
+            let source_type = &schema
+                .get(field.source_id as usize)
+                .ok_or_else(|| anyhow!("Column {} not in table schema.", field.source_id))?
+                .field_type;
+            let floating = matches!(
+                field.transform.result_type(source_type)?,
+                AllType::Primitive(PrimitiveType::Float | PrimitiveType::Double)
+            );
+            let mut summary = FieldSummary {
+                contains_null: false,
+                contains_nan: if floating { Some(false) } else { None },
+                lower_bound: None,
+                upper_bound: None,
+            };
+            let mut lower: Option<&Value> = None;
+            let mut upper: Option<&Value> = None;
+            for entry in entries {
+                match entry.partition_values().get(index).and_then(Option::as_ref) {
+                    None => summary.contains_null = true,
+                    Some(Value::Double(float)) if float.is_nan() => {
+                        summary.contains_nan = Some(true)
+                    }
+                    Some(Value::LongFloat(double)) if double.is_nan() => {
+                        summary.contains_nan = Some(true)
+                    }
+                    Some(value) => {
+                        if lower.is_none_or(|lower| value < lower) {
+                            lower = Some(value);
+                        }
+                        if upper.is_none_or(|upper| value > upper) {
+                            upper = Some(value);
                         }
-                        let manifest_file = ManifestFile::V2(ManifestFileV2 {
-                            manifest_path: manifest_location.to_string(),
-                            manifest_length: 1200,
-                            partition_spec_id: 0,
-                            content: Content::Data,
-                            sequence_number: 566,
-                            min_sequence_number: 0,
-                            added_snapshot_id: 39487483032,
-                            added_files_count: 1,
-                            existing_files_count: 2,
-                            deleted_files_count: 0,
-                            added_rows_count: 1000,
-                            existing_rows_count: 8000,
-                            deleted_rows_count: 0,
-                            partitions: Some(vec![FieldSummary {
-                                contains_null: true,
-                                contains_nan: Some(false),
-                                lower_bound: None,
-                                upper_bound: None,
-                            }]),
-                            key_metadata: None,
-                        });
-                        manifest_list_writer.append_ser(manifest_file)?;
-
-                        manifest_list_writer.into_inner()?
                     }
-                };
-                object_store
-                    .put(&manifest_list_location, manifest_list_bytes.into())
-                    .await?;
-                Ok(())
+                }
             }
-            _ => Ok(()),
-        }
+            summary.lower_bound = lower.map(value_to_bytes).transpose()?.map(ByteBuf::from);
+            summary.upper_bound = upper.map(value_to_bytes).transpose()?.map(ByteBuf::from);
+            Ok(summary)
+        })
+        .collect()
+}
+
+/// Write the manifests to the manifest list of the current snapshot.
+async fn write_manifest_list(table: &Table, manifests: &[ManifestFile]) -> Result<()> {
+    let table_metadata = table.metadata();
+    let manifest_list_location = table_metadata
+        .manifest_list()
+        .ok_or_else(|| anyhow!("No manifest list in table metadata."))?;
+    let manifest_list_schema =
+        apache_avro::Schema::parse_str(&ManifestFile::schema(&table_metadata.format_version()))?;
+    let mut manifest_list_writer = apache_avro::Writer::new(&manifest_list_schema, Vec::new());
+    for manifest in manifests {
+        manifest_list_writer.append_ser(manifest)?;
     }
+    table
+        .object_store()
+        .put(
+            &manifest_list_location.into(),
+            manifest_list_writer.into_inner()?.into(),
+        )
+        .await?;
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
 
-    use std::sync::Arc;
+    use std::{collections::HashMap, sync::Arc};
 
     use object_store::{memory::InMemory, ObjectStore};
+    use serde_bytes::ByteBuf;
 
     use crate::{
-        model::schema::{AllType, PrimitiveType, SchemaStruct, SchemaV2, StructField},
-        table::table_builder::TableBuilder,
+        expr::Reference,
+        model::{
+            manifest::{
+                AvroMap, Content, DataFileV2, FileFormat, ManifestEntry, ManifestEntryV2,
+                PartitionValues, Status,
+            },
+            manifest_list::{FieldSummary, ManifestFile},
+            partition::{PartitionField, PartitionSpec, Transform},
+            schema::{AllType, PrimitiveType, SchemaStruct, SchemaV2, StructField},
+            snapshot::Operation as SnapshotOperation,
+            types::Value,
+        },
+        table::{
+            files::read_manifest,
+            scan::FileScanTask,
+            table_builder::TableBuilder,
+            transaction::{
+                MANIFEST_MERGE_ENABLED, MANIFEST_MIN_MERGE_COUNT, MANIFEST_TARGET_SIZE_BYTES,
+            },
+            Table,
+        },
     };
 
+    use super::partition_summaries;
+
     #[tokio::test]
     async fn test_append_files() {
         let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
@@ -319,4 +803,498 @@ mod tests {
         let metadata_location = table.metadata_location();
         assert_eq!(metadata_location, "test/append/metadata/v2.metadata.json");
     }
+
+    fn data_file(path: &str, partition: &[(&str, &str)]) -> DataFileV2 {
+        DataFileV2 {
+            content: Content::Data,
+            file_path: path.to_string(),
+            file_format: FileFormat::Avro,
+            partition: PartitionValues::from_iter(
+                partition.iter().map(|(name, value)| {
+                    (name.to_string(), Some(Value::String(value.to_string())))
+                }),
+            ),
+            record_count: 10,
+            file_size_in_bytes: 2048,
+            column_sizes: Some(AvroMap::from(HashMap::from_iter(vec![(1, 100), (2, 300)]))),
+            value_counts: Some(AvroMap::from(HashMap::from_iter(vec![(1, 10), (2, 10)]))),
+            null_value_counts: Some(AvroMap::from(HashMap::from_iter(vec![(1, 0), (2, 4)]))),
+            nan_value_counts: None,
+            distinct_counts: None,
+            lower_bounds: None,
+            upper_bounds: None,
+            key_metadata: None,
+            split_offsets: Some(vec![4, 1024]),
+            equality_ids: None,
+            sort_order_id: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_append_data_files() {
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let schema = SchemaV2 {
+            schema_id: 1,
+            identifier_field_ids: Some(vec![1, 2]),
+            name_mapping: None,
+            struct_fields: SchemaStruct {
+                fields: vec![
+                    StructField {
+                        id: 1,
+                        name: "one".to_string(),
+                        required: false,
+                        field_type: AllType::Primitive(PrimitiveType::String),
+                        doc: None,
+                    },
+                    StructField {
+                        id: 2,
+                        name: "two".to_string(),
+                        required: false,
+                        field_type: AllType::Primitive(PrimitiveType::String),
+                        doc: None,
+                    },
+                ],
+            },
+        };
+        let mut table = TableBuilder::new_filesystem_table(
+            "test/append_data_files",
+            schema,
+            Arc::clone(&object_store),
+        )
+        .unwrap()
+        .with_partition_spec(PartitionSpec {
+            spec_id: 0,
+            fields: vec![PartitionField {
+                source_id: 1,
+                field_id: 1000,
+                name: "one".to_string(),
+                transform: Transform::Identity,
+            }],
+        })
+        .commit()
+        .await
+        .unwrap();
+
+        let files = vec![
+            data_file("test/append_data_files/data/file1.avro", &[("one", "a")]),
+            data_file("test/append_data_files/data/file2.avro", &[("one", "b")]),
+        ];
+        table
+            .new_transaction()
+            .append(files.clone())
+            .commit()
+            .await
+            .unwrap();
+
+        // The manifest entries contain the descriptors as they were given
+        let tasks = table.scan().plan_files().await.unwrap();
+        let written: Vec<DataFileV2> = tasks
+            .iter()
+            .map(|task| match task.data_file() {
+                ManifestEntry::V2(entry) => entry.data_file.clone(),
+                ManifestEntry::V1(_) => panic!("Table is not a version 2 table."),
+            })
+            .collect();
+        assert_eq!(written, files);
+
+        // The manifest list describes the written manifest
+        let manifest = match &table.manifests()[0] {
+            ManifestFile::V2(manifest) => manifest.clone(),
+            ManifestFile::V1(_) => panic!("Table is not a version 2 table."),
+        };
+        let manifest_length = object_store
+            .head(&manifest.manifest_path.as_str().into())
+            .await
+            .unwrap()
+            .size;
+        assert_eq!(manifest.manifest_length, manifest_length as i64);
+        assert_eq!(
+            Some(manifest.added_snapshot_id),
+            table.metadata().current_snapshot_id()
+        );
+        let sequence_number = table
+            .metadata()
+            .snapshot(manifest.added_snapshot_id)
+            .unwrap()
+            .sequence_number;
+        assert_eq!(manifest.sequence_number, sequence_number);
+        assert_eq!(manifest.min_sequence_number, sequence_number);
+        assert_eq!(
+            (manifest.added_files_count, manifest.added_rows_count),
+            (2, 20)
+        );
+        assert_eq!(
+            manifest.partitions,
+            Some(vec![FieldSummary {
+                contains_null: false,
+                contains_nan: None,
+                lower_bound: Some(ByteBuf::from("a".as_bytes())),
+                upper_bound: Some(ByteBuf::from("b".as_bytes())),
+            }])
+        );
+        let tasks = table
+            .scan()
+            .filter(Reference::new("one").equal(Value::String("b".to_string())))
+            .plan_files()
+            .await
+            .unwrap();
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(
+            tasks[0].data_file().file_path(),
+            "test/append_data_files/data/file2.avro"
+        );
+
+        // Delete files and partition values of another spec can't be appended
+        let mut delete_file = data_file("test/append_data_files/data/file3.avro", &[("one", "a")]);
+        delete_file.content = Content::PositionDeletes;
+        assert!(table
+            .new_transaction()
+            .append(vec![delete_file])
+            .commit()
+            .await
+            .is_err());
+        assert!(table
+            .new_transaction()
+            .append(vec![data_file(
+                "test/append_data_files/data/file3.avro",
+                &[("two", "a")]
+            )])
+            .commit()
+            .await
+            .is_err());
+        assert_eq!(table.scan().plan_files().await.unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_partition_summaries() {
+        let schema = SchemaStruct {
+            fields: vec![StructField {
+                id: 1,
+                name: "price".to_string(),
+                required: false,
+                field_type: AllType::Primitive(PrimitiveType::Double),
+                doc: None,
+            }],
+        };
+        let spec = vec![PartitionField {
+            source_id: 1,
+            field_id: 1000,
+            name: "price".to_string(),
+            transform: Transform::Identity,
+        }];
+        let entries: Vec<ManifestEntry> = [Some(3.0), Some(f64::NAN), None, Some(1.0)]
+            .into_iter()
+            .map(|price| {
+                let mut file = data_file("test/data/file.parquet", &[]);
+                file.partition = PartitionValues::from_iter(vec![(
+                    "price".to_string(),
+                    price.map(Value::LongFloat),
+                )]);
+                ManifestEntry::V2(ManifestEntryV2 {
+                    status: Status::Added,
+                    snapshot_id: Some(1),
+                    sequence_number: Some(1),
+                    data_file: file,
+                })
+            })
+            .collect();
+        assert_eq!(
+            partition_summaries(&entries, &spec, &schema).unwrap(),
+            vec![FieldSummary {
+                contains_null: true,
+                contains_nan: Some(true),
+                lower_bound: Some(ByteBuf::from(1.0f64.to_le_bytes().to_vec())),
+                upper_bound: Some(ByteBuf::from(3.0f64.to_le_bytes().to_vec())),
+            }]
+        );
+    }
+
+    async fn merge_append_table(
+        location: &str,
+        properties: &[(&str, &str)],
+        object_store: Arc<dyn ObjectStore>,
+    ) -> Table {
+        let schema = SchemaV2 {
+            schema_id: 1,
+            identifier_field_ids: Some(vec![1, 2]),
+            name_mapping: None,
+            struct_fields: SchemaStruct {
+                fields: vec![
+                    StructField {
+                        id: 1,
+                        name: "one".to_string(),
+                        required: false,
+                        field_type: AllType::Primitive(PrimitiveType::String),
+                        doc: None,
+                    },
+                    StructField {
+                        id: 2,
+                        name: "two".to_string(),
+                        required: false,
+                        field_type: AllType::Primitive(PrimitiveType::String),
+                        doc: None,
+                    },
+                ],
+            },
+        };
+        let mut builder = TableBuilder::new_filesystem_table(location, schema, object_store)
+            .unwrap()
+            .with_partition_spec(PartitionSpec {
+                spec_id: 0,
+                fields: vec![PartitionField {
+                    source_id: 1,
+                    field_id: 1000,
+                    name: "one".to_string(),
+                    transform: Transform::Identity,
+                }],
+            });
+        for (key, value) in properties {
+            builder = builder.with_property(key, value);
+        }
+        let mut table = builder.commit().await.unwrap();
+        for (index, partition) in ["a", "b", "c"].iter().enumerate() {
+            table
+                .new_transaction()
+                .merge_append(vec![data_file(
+                    &format!("{}/data/file{}.avro", location, index),
+                    &[("one", partition)],
+                )])
+                .commit()
+                .await
+                .unwrap();
+        }
+        table
+    }
+
+    #[tokio::test]
+    async fn test_merge_append() {
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+
+        // The manifests are merged once there are as many as the minimum count
+        let table = merge_append_table(
+            "test/merge_append",
+            &[(MANIFEST_MIN_MERGE_COUNT, "3")],
+            Arc::clone(&object_store),
+        )
+        .await;
+        let manifests = table.manifests();
+        assert_eq!(manifests.len(), 1);
+        let manifest = match &manifests[0] {
+            ManifestFile::V2(manifest) => manifest.clone(),
+            ManifestFile::V1(_) => panic!("Table is not a version 2 table."),
+        };
+        assert_eq!(
+            (manifest.added_files_count, manifest.existing_files_count),
+            (1, 2)
+        );
+        assert_eq!(
+            manifest.partitions.as_ref().unwrap()[0].upper_bound,
+            Some(ByteBuf::from("c".as_bytes()))
+        );
+        let entries = read_manifest(
+            table.object_store(),
+            &manifests[0],
+            table.metadata().format_version(),
+        )
+        .await
+        .unwrap();
+        let snapshot_ids: Vec<i64> = table
+            .metadata()
+            .current_ancestors()
+            .iter()
+            .map(|snapshot| snapshot.snapshot_id)
+            .collect();
+        let mut carried_over: Vec<(String, Status, Option<i64>)> = entries
+            .into_iter()
+            .map(|entry| match entry {
+                ManifestEntry::V2(entry) => {
+                    (entry.data_file.file_path, entry.status, entry.snapshot_id)
+                }
+                ManifestEntry::V1(_) => panic!("Table is not a version 2 table."),
+            })
+            .collect();
+        carried_over.sort_by(|left, right| left.0.cmp(&right.0));
+        assert_eq!(
+            carried_over,
+            vec![
+                (
+                    "test/merge_append/data/file0.avro".to_string(),
+                    Status::Existing,
+                    Some(snapshot_ids[2])
+                ),
+                (
+                    "test/merge_append/data/file1.avro".to_string(),
+                    Status::Existing,
+                    Some(snapshot_ids[1])
+                ),
+                (
+                    "test/merge_append/data/file2.avro".to_string(),
+                    Status::Added,
+                    Some(snapshot_ids[0])
+                ),
+            ]
+        );
+        assert_eq!(table.scan().plan_files().await.unwrap().len(), 3);
+
+        // Manifests larger than the target size aren't merged
+        let table = merge_append_table(
+            "test/merge_append_target_size",
+            &[
+                (MANIFEST_MIN_MERGE_COUNT, "1"),
+                (MANIFEST_TARGET_SIZE_BYTES, "1"),
+            ],
+            Arc::clone(&object_store),
+        )
+        .await;
+        assert_eq!(table.manifests().len(), 3);
+
+        // Merging can be disabled
+        let table = merge_append_table(
+            "test/merge_append_disabled",
+            &[
+                (MANIFEST_MERGE_ENABLED, "false"),
+                (MANIFEST_MIN_MERGE_COUNT, "1"),
+            ],
+            Arc::clone(&object_store),
+        )
+        .await;
+        assert_eq!(table.manifests().len(), 3);
+
+        // The minimum count applies to the bin of the new manifest and not to all manifests of its partition spec. With
+        // a target size of two and a half manifests, the last append packs its manifest and the previous one into a bin
+        // that is too small to be merged.
+        let manifest_length = table
+            .manifests()
+            .iter()
+            .map(|manifest| manifest.manifest_length())
+            .max()
+            .unwrap();
+        let target_size = (manifest_length * 5 / 2).to_string();
+        let table = merge_append_table(
+            "test/merge_append_bins",
+            &[
+                (MANIFEST_MIN_MERGE_COUNT, "3"),
+                (MANIFEST_TARGET_SIZE_BYTES, &target_size),
+            ],
+            Arc::clone(&object_store),
+        )
+        .await;
+        assert_eq!(table.manifests().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_delete_files() {
+        let object_store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let mut table =
+            merge_append_table("test/delete_files", &[], Arc::clone(&object_store)).await;
+        let data_files = |tasks: Vec<FileScanTask>| {
+            let mut paths: Vec<String> = tasks
+                .iter()
+                .map(|task| task.data_file().file_path().to_string())
+                .collect();
+            paths.sort();
+            paths
+        };
+
+        // The partition values prove that all rows of the file match
+        table
+            .new_transaction()
+            .delete_from_row_filter(Reference::new("one").equal(Value::String("a".to_string())))
+            .commit()
+            .await
+            .unwrap();
+        let snapshot_id = table.metadata().current_snapshot_id().unwrap();
+        assert_eq!(
+            table.metadata().snapshot_operation(snapshot_id),
+            Some(SnapshotOperation::Delete)
+        );
+        assert_eq!(
+            data_files(table.scan().plan_files().await.unwrap()),
+            vec![
+                "test/delete_files/data/file1.avro".to_string(),
+                "test/delete_files/data/file2.avro".to_string()
+            ]
+        );
+        let manifest = table
+            .manifests()
+            .iter()
+            .find(|manifest| manifest.added_snapshot_id() == snapshot_id)
+            .unwrap();
+        let entries = read_manifest(
+            table.object_store(),
+            manifest,
+            table.metadata().format_version(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(*entries[0].status(), Status::Deleted);
+        assert_eq!(entries[0].snapshot_id(), Some(snapshot_id));
+
+        // Files are deleted by path
+        table
+            .new_transaction()
+            .delete_files(vec!["test/delete_files/data/file2.avro".to_string()])
+            .commit()
+            .await
+            .unwrap();
+        assert_eq!(
+            data_files(table.scan().plan_files().await.unwrap()),
+            vec!["test/delete_files/data/file1.avro".to_string()]
+        );
+
+        // Unknown paths and filters that only match some rows of a file fail
+        assert!(table
+            .new_transaction()
+            .delete_files(vec!["test/delete_files/data/file0.avro".to_string()])
+            .commit()
+            .await
+            .is_err());
+        assert!(table
+            .new_transaction()
+            .delete_from_row_filter(Reference::new("two").equal(Value::String("a".to_string())))
+            .commit()
+            .await
+            .is_err());
+        assert_eq!(table.scan().plan_files().await.unwrap().len(), 1);
+
+        // Appending and deleting files in one snapshot is an overwrite
+        table
+            .new_transaction()
+            .append(vec![data_file(
+                "test/delete_files/data/file3.avro",
+                &[("one", "d")],
+            )])
+            .delete_files(vec!["test/delete_files/data/file1.avro".to_string()])
+            .commit()
+            .await
+            .unwrap();
+        let snapshot_id = table.metadata().current_snapshot_id().unwrap();
+        assert_eq!(
+            table.metadata().snapshot_operation(snapshot_id),
+            Some(SnapshotOperation::Overwrite)
+        );
+        assert_eq!(
+            data_files(table.scan().plan_files().await.unwrap()),
+            vec!["test/delete_files/data/file3.avro".to_string()]
+        );
+
+        // A file that was removed by a concurrent commit can't be deleted again
+        let mut concurrent = Table::load_file_system_table("test/delete_files", &object_store)
+            .await
+            .unwrap();
+        table
+            .new_transaction()
+            .delete_files(vec!["test/delete_files/data/file3.avro".to_string()])
+            .commit()
+            .await
+            .unwrap();
+        let err = concurrent
+            .new_transaction()
+            .delete_files(vec!["test/delete_files/data/file3.avro".to_string()])
+            .commit()
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("removed by a concurrent commit"));
+    }
 }
@@ -21,6 +21,7 @@ pub mod transaction;
 pub mod view_builder;
 
 /// An iceberg view
+#[derive(Clone)]
 pub struct View {
     /// Type of the View, either filesystem or metastore.
     table_type: TableType,
@@ -116,6 +117,12 @@ impl View {
             TableType::Metastore(_, catalog) => Some(catalog),
         }
     }
+    /// Associate a metastore view with another catalog, for example a catalog that wraps the one it was loaded from.
+    pub(crate) fn set_catalog(&mut self, catalog: Arc<dyn Catalog>) {
+        if let TableType::Metastore(_, view_catalog) = &mut self.table_type {
+            *view_catalog = catalog;
+        }
+    }
     /// Get the object_store associated to the view
     pub fn object_store(&self) -> Arc<dyn ObjectStore> {
         match &self.table_type {
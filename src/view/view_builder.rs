@@ -2,6 +2,7 @@
 Defining the [ViewBuilder] struct for creating catalog views and starting create/replace transactions
 */
 
+use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::SystemTime;
 
@@ -10,7 +11,6 @@ use object_store::ObjectStore;
 use uuid::Uuid;
 
 use crate::catalog::identifier::Identifier;
-use crate::catalog::relation::Relation;
 use crate::model::schema::Schema;
 use crate::model::schema::SchemaV2;
 use crate::model::view_metadata::{
@@ -24,7 +24,10 @@ use super::{Catalog, TableType};
 ///Builder pattern to create a view
 pub struct ViewBuilder {
     table_type: TableType,
-    metadata: ViewMetadataV1,
+    sql: String,
+    location: String,
+    schema: SchemaV2,
+    properties: HashMap<String, String>,
 }
 
 impl ViewBuilder {
@@ -36,46 +39,11 @@ impl ViewBuilder {
         identifier: Identifier,
         catalog: Arc<dyn Catalog>,
     ) -> Result<Self> {
-        let summary = Summary {
-            operation: Operation::Create,
-            engine_version: None,
-        };
-        let representation = Representation::Sql {
+        Ok(ViewBuilder {
             sql: sql.to_owned(),
-            dialect: "ANSI".to_owned(),
-            schema_id: None,
-            default_catalog: None,
-            default_namespace: None,
-            field_aliases: None,
-            field_docs: None,
-        };
-        let version = Version {
-            version_id: 1,
-            timestamp_ms: SystemTime::now()
-                .duration_since(SystemTime::UNIX_EPOCH)
-                .map_err(|err| anyhow!(err.to_string()))?
-                .as_millis() as i64,
-            summary,
-            representations: vec![representation],
-        };
-        let version_log = vec![VersionLogStruct {
-            timestamp_ms: SystemTime::now()
-                .duration_since(SystemTime::UNIX_EPOCH)
-                .map_err(|err| anyhow!(err.to_string()))?
-                .as_millis() as i64,
-            version_id: 1,
-        }];
-        let metadata = ViewMetadataV1 {
             location: base_path.to_owned() + &identifier.to_string().replace(".", "/"),
-            schemas: Some(vec![Schema::V2(schema)]),
-            current_schema_id: Some(1),
-            versions: vec![version],
-            current_version_id: 1,
-            version_log,
-            properties: None,
-        };
-        Ok(ViewBuilder {
-            metadata,
+            schema,
+            properties: HashMap::new(),
             table_type: TableType::Metastore(identifier, catalog),
         })
     }
@@ -86,84 +54,41 @@ impl ViewBuilder {
         schema: SchemaV2,
         object_store: Arc<dyn ObjectStore>,
     ) -> Result<Self> {
-        let summary = Summary {
-            operation: Operation::Create,
-            engine_version: None,
-        };
-        let representation = Representation::Sql {
+        Ok(ViewBuilder {
             sql: sql.to_owned(),
-            dialect: "ANSI".to_owned(),
-            schema_id: None,
-            default_catalog: None,
-            default_namespace: None,
-            field_aliases: None,
-            field_docs: None,
-        };
-        let version = Version {
-            version_id: 1,
-            timestamp_ms: SystemTime::now()
-                .duration_since(SystemTime::UNIX_EPOCH)
-                .map_err(|err| anyhow!(err.to_string()))?
-                .as_millis() as i64,
-            summary,
-            representations: vec![representation],
-        };
-        let version_log = vec![VersionLogStruct {
-            timestamp_ms: SystemTime::now()
-                .duration_since(SystemTime::UNIX_EPOCH)
-                .map_err(|err| anyhow!(err.to_string()))?
-                .as_millis() as i64,
-            version_id: 1,
-        }];
-        let metadata = ViewMetadataV1 {
             location: location.to_string(),
-            schemas: Some(vec![Schema::V2(schema)]),
-            current_schema_id: Some(1),
-            versions: vec![version],
-            current_version_id: 1,
-            version_log,
-            properties: None,
-        };
-        Ok(ViewBuilder {
-            metadata,
+            schema,
+            properties: HashMap::new(),
             table_type: TableType::FileSystem(object_store),
         })
     }
+    /// Sets a view property.
+    pub fn with_property(mut self, key: &str, value: &str) -> Self {
+        self.properties.insert(key.to_string(), value.to_string());
+        self
+    }
     /// Building a table writes the metadata file and commits the table to either the metastore or the filesystem
     pub async fn commit(self) -> Result<View> {
         match self.table_type {
             TableType::Metastore(identifier, catalog) => {
-                let object_store = catalog.object_store();
-                let location = &self.metadata.location;
-                let uuid = Uuid::new_v4();
-                let version = &self.metadata.current_version_id;
-                let metadata_json = serde_json::to_string(&self.metadata)
-                    .map_err(|err| anyhow!(err.to_string()))?;
-                let path: Path = (location.to_string()
-                    + "/metadata/"
-                    + &version.to_string()
-                    + "-"
-                    + &uuid.to_string()
-                    + ".metadata.json")
-                    .into();
-                object_store
-                    .put(&path, metadata_json.into())
+                catalog
+                    .create_view(
+                        identifier,
+                        &self.sql,
+                        self.schema,
+                        Some(&self.location),
+                        self.properties,
+                    )
                     .await
-                    .map_err(|err| anyhow!(err.to_string()))?;
-                if let Relation::View(view) =
-                    catalog.register_table(identifier, path.as_ref()).await?
-                {
-                    Ok(view)
-                } else {
-                    Err(anyhow!("Building the table failed because registering the table in the catalog didn't return a table."))
-                }
             }
             TableType::FileSystem(object_store) => {
-                let location = &self.metadata.location;
+                let metadata =
+                    new_view_metadata(&self.sql, &self.location, self.schema, self.properties)?;
+                let location = &metadata.location;
                 let uuid = Uuid::new_v4();
-                let version = &self.metadata.current_version_id;
-                let metadata_json = serde_json::to_string(&self.metadata)
-                    .map_err(|err| anyhow!(err.to_string()))?;
+                let version = &metadata.current_version_id;
+                let metadata_json =
+                    serde_json::to_string(&metadata).map_err(|err| anyhow!(err.to_string()))?;
                 let temp_path: Path =
                     (location.to_string() + "/metadata/" + &uuid.to_string() + ".metadata.json")
                         .into();
@@ -190,3 +115,54 @@ impl ViewBuilder {
         }
     }
 }
+
+/// Create the metadata of a new view with a single sql representation.
+pub(crate) fn new_view_metadata(
+    sql: &str,
+    location: &str,
+    schema: SchemaV2,
+    properties: HashMap<String, String>,
+) -> Result<ViewMetadataV1> {
+    let summary = Summary {
+        operation: Operation::Create,
+        engine_version: None,
+    };
+    let representation = Representation::Sql {
+        sql: sql.to_owned(),
+        dialect: "ANSI".to_owned(),
+        schema_id: None,
+        default_catalog: None,
+        default_namespace: None,
+        field_aliases: None,
+        field_docs: None,
+    };
+    let version = Version {
+        version_id: 1,
+        timestamp_ms: SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map_err(|err| anyhow!(err.to_string()))?
+            .as_millis() as i64,
+        summary,
+        representations: vec![representation],
+    };
+    let version_log = vec![VersionLogStruct {
+        timestamp_ms: SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map_err(|err| anyhow!(err.to_string()))?
+            .as_millis() as i64,
+        version_id: 1,
+    }];
+    Ok(ViewMetadataV1 {
+        location: location.to_string(),
+        current_schema_id: Some(schema.schema_id),
+        schemas: Some(vec![Schema::V2(schema)]),
+        versions: vec![version],
+        current_version_id: 1,
+        version_log,
+        properties: if properties.is_empty() {
+            None
+        } else {
+            Some(properties)
+        },
+    })
+}